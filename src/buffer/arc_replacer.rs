@@ -0,0 +1,261 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use crate::utils::config::*;
+
+use super::ghost_list::GhostList;
+use super::replacer::{AccessType, Replacer, ReplacerStats};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArcList {
+    /// T1: resident pages seen once recently.
+    Mru,
+    /// T2: resident pages seen at least twice recently.
+    Mfu,
+}
+
+#[derive(Debug, Clone)]
+struct ArcEntry {
+    page_id: PageId,
+    list: ArcList,
+    is_evictable: bool,
+}
+
+#[derive(Debug, Default, Clone)]
+struct ARCReplacerInner {
+    /// Resident frames, most recently used at the front.
+    mru: VecDeque<FrameId>,
+    mfu: VecDeque<FrameId>,
+    /// Ghost lists of recently evicted page ids, most recent at the front.
+    mru_ghost: GhostList,
+    mfu_ghost: GhostList,
+    entries: HashMap<FrameId, ArcEntry>,
+    /// Target size of the MRU list (`p` in the paper).
+    mru_target_size: usize,
+    curr_size: usize,
+    stats: ReplacerStats,
+}
+
+/// ARCReplacer implements the Adaptive Replacement Cache policy (Megiddo & Modha).
+///
+/// Resident frames live either in the MRU list (seen once) or in the MFU list (seen again while
+/// resident or shortly after eviction). Evicted page ids are remembered in two ghost lists; a
+/// re-reference that hits a ghost list shifts the target size of the MRU list towards the list
+/// that would have kept the page, so the policy adapts between recency and frequency. Pages
+/// touched by a single sequential scan never leave the MRU list and cannot push out the MFU list.
+#[derive(Debug)]
+pub struct ARCReplacer {
+    inner: Mutex<ARCReplacerInner>,
+    replacer_size: AtomicUsize,
+}
+
+impl ARCReplacer {
+    /// Create a new ARCReplacer tracking at most `num_frames` frames.
+    pub fn new(num_frames: usize) -> Self {
+        ARCReplacer {
+            inner: Mutex::new(ARCReplacerInner::default()),
+            replacer_size: AtomicUsize::new(num_frames),
+        }
+    }
+}
+
+fn remove_from<T: PartialEq>(list: &mut VecDeque<T>, item: &T) -> bool {
+    match list.iter().position(|x| x == item) {
+        Some(pos) => {
+            list.remove(pos);
+            true
+        }
+        None => false,
+    }
+}
+
+impl ARCReplacerInner {
+    fn list_mut(&mut self, list: ArcList) -> &mut VecDeque<FrameId> {
+        match list {
+            ArcList::Mru => &mut self.mru,
+            ArcList::Mfu => &mut self.mfu,
+        }
+    }
+
+    /// Evict the least recently used evictable frame of the given list, remembering its page in
+    /// the matching ghost list.
+    fn evict_from(&mut self, list: ArcList) -> Option<FrameId> {
+        let frames = match list {
+            ArcList::Mru => &self.mru,
+            ArcList::Mfu => &self.mfu,
+        };
+        let pos = frames
+            .iter()
+            .rposition(|frame_id| self.entries[frame_id].is_evictable)?;
+        let frame_id = self.list_mut(list).remove(pos).unwrap();
+        let entry = self.entries.remove(&frame_id).unwrap();
+        match list {
+            ArcList::Mru => self.mru_ghost.push_front(entry.page_id),
+            ArcList::Mfu => self.mfu_ghost.push_front(entry.page_id),
+        }
+        self.curr_size -= 1;
+        Some(frame_id)
+    }
+
+    /// Evict a frame: the MRU list gives one up while it is above its target size, the MFU list
+    /// otherwise, and either falls back to the other if it has no evictable frame.
+    fn evict(&mut self) -> Option<FrameId> {
+        if self.curr_size == 0 {
+            return None;
+        }
+        let (first, second) = if self.mru.len() > self.mru_target_size {
+            (ArcList::Mru, ArcList::Mfu)
+        } else {
+            (ArcList::Mfu, ArcList::Mru)
+        };
+        let victim = self.evict_from(first).or_else(|| self.evict_from(second))?;
+        self.stats.evictions += 1;
+        Some(victim)
+    }
+
+    /// Drop whatever the frame currently holds, as its page is being replaced.
+    fn forget_frame(&mut self, frame_id: FrameId) {
+        if let Some(entry) = self.entries.remove(&frame_id) {
+            remove_from(self.list_mut(entry.list), &frame_id);
+            if entry.is_evictable {
+                self.curr_size -= 1;
+            }
+        }
+    }
+}
+
+impl Replacer for ARCReplacer {
+    fn evict(&self) -> Option<FrameId> {
+        self.inner.lock().unwrap().evict()
+    }
+
+    fn record_access(&self, frame_id: FrameId, page_id: PageId, access_type: AccessType) {
+        assert!(
+            frame_id >= 0 && (frame_id as usize) < self.replacer_size.load(Ordering::Relaxed),
+            "invalid frame id {}",
+            frame_id
+        );
+        let mut inner = self.inner.lock().unwrap();
+        let inner = &mut *inner;
+
+        let is_scan = access_type == AccessType::Scan;
+
+        // Case 1: the page is resident, promote it to the front of the MFU list. A scan leaves it
+        // where it is.
+        if let Some(entry) = inner.entries.get_mut(&frame_id) {
+            if entry.page_id == page_id {
+                inner.stats.hits += 1;
+                if is_scan {
+                    return;
+                }
+                let list = entry.list;
+                entry.list = ArcList::Mfu;
+                remove_from(inner.list_mut(list), &frame_id);
+                inner.mfu.push_front(frame_id);
+                return;
+            }
+        }
+        inner.forget_frame(frame_id);
+        inner.stats.misses += 1;
+
+        let capacity = self.replacer_size.load(Ordering::Relaxed);
+        if is_scan {
+            // A scan coming back to an evicted page says nothing about its reuse: forget its
+            // ghost, whichever list it is in, so the page comes in as a new one without adapting
+            // the MRU target.
+            inner.mru_ghost.remove(page_id);
+            inner.mfu_ghost.remove(page_id);
+        }
+        let list = if inner.mru_ghost.contains(page_id) {
+            // Case 2: ghost hit in the MRU ghost list, grow the MRU target.
+            let delta = (inner.mfu_ghost.len() / inner.mru_ghost.len()).max(1);
+            inner.mru_target_size = (inner.mru_target_size + delta).min(capacity);
+            inner.mru_ghost.remove(page_id);
+            ArcList::Mfu
+        } else if inner.mfu_ghost.contains(page_id) {
+            // Case 3: ghost hit in the MFU ghost list, shrink the MRU target.
+            let delta = (inner.mru_ghost.len() / inner.mfu_ghost.len()).max(1);
+            inner.mru_target_size = inner.mru_target_size.saturating_sub(delta);
+            inner.mfu_ghost.remove(page_id);
+            ArcList::Mfu
+        } else {
+            // Case 4: a brand new page. Keep the directory within 2 * capacity entries.
+            if inner.mru.len() + inner.mru_ghost.len() >= capacity {
+                inner.mru_ghost.pop_back();
+            } else if inner.mru.len()
+                + inner.mfu.len()
+                + inner.mru_ghost.len()
+                + inner.mfu_ghost.len()
+                >= 2 * capacity
+            {
+                inner.mfu_ghost.pop_back();
+            }
+            ArcList::Mru
+        };
+        inner.list_mut(list).push_front(frame_id);
+        inner.entries.insert(
+            frame_id,
+            ArcEntry {
+                page_id,
+                list,
+                is_evictable: false,
+            },
+        );
+    }
+
+    fn set_evictable(&self, frame_id: FrameId, set_evictable: bool) {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(entry) = inner.entries.get_mut(&frame_id) else {
+            return;
+        };
+        if entry.is_evictable == set_evictable {
+            return;
+        }
+        entry.is_evictable = set_evictable;
+        if set_evictable {
+            inner.curr_size += 1;
+        } else {
+            inner.curr_size -= 1;
+        }
+    }
+
+    fn remove(&self, frame_id: FrameId) {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(entry) = inner.entries.get(&frame_id) else {
+            return;
+        };
+        assert!(
+            entry.is_evictable,
+            "cannot remove non-evictable frame {}",
+            frame_id
+        );
+        inner.forget_frame(frame_id);
+    }
+
+    fn size(&self) -> usize {
+        self.inner.lock().unwrap().curr_size
+    }
+
+    fn eviction_order(&self) -> Vec<FrameId> {
+        // Replay the policy on a copy where every frame is evictable: which list gives up the
+        // next victim depends on how many frames the MRU list has left.
+        let mut inner = self.inner.lock().unwrap().clone();
+        for entry in inner.entries.values_mut() {
+            entry.is_evictable = true;
+        }
+        inner.curr_size = inner.entries.len();
+        std::iter::from_fn(|| inner.evict()).collect()
+    }
+
+    fn resize(&self, num_frames: usize) {
+        let mut inner = self.inner.lock().unwrap();
+        self.replacer_size.store(num_frames, Ordering::Relaxed);
+        // The ghost lists shrink back within the new capacity as new pages come in.
+        inner.mru_target_size = inner.mru_target_size.min(num_frames);
+    }
+
+    fn stats(&self) -> ReplacerStats {
+        self.inner.lock().unwrap().stats
+    }
+}
@@ -0,0 +1,58 @@
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use super::buffer_pool::BufferPool;
+
+/// BackgroundWriter periodically writes dirty, unpinned pages of a buffer pool back to disk, so
+/// that foreground evictions rarely have to wait for a synchronous write-back.
+///
+/// Every `interval` the writer flushes at most `max_pages_per_round` pages (its I/O budget),
+/// oldest recovery LSN first. The thread stops when the writer is dropped.
+pub struct BackgroundWriter {
+    /// Set to true to ask the thread to exit; the condvar wakes it up early.
+    shutdown: Arc<(Mutex<bool>, Condvar)>,
+    background_thread: Option<JoinHandle<()>>,
+}
+
+impl BackgroundWriter {
+    /// Start a background writer thread for the given buffer pool.
+    pub fn start(bpm: Arc<dyn BufferPool>, interval: Duration, max_pages_per_round: usize) -> Self {
+        let shutdown = Arc::new((Mutex::new(false), Condvar::new()));
+        let thread_shutdown = shutdown.clone();
+        let background_thread = std::thread::spawn(move || {
+            let (lock, cvar) = &*thread_shutdown;
+            loop {
+                let stopped = lock.lock().unwrap();
+                let (stopped, _) = cvar
+                    .wait_timeout_while(stopped, interval, |stopped| !*stopped)
+                    .unwrap();
+                if *stopped {
+                    break;
+                }
+                drop(stopped);
+                bpm.write_back_dirty_pages(max_pages_per_round);
+            }
+        });
+        BackgroundWriter {
+            shutdown,
+            background_thread: Some(background_thread),
+        }
+    }
+
+    /// Stop the background thread and wait for the current round to finish.
+    pub fn stop(&mut self) {
+        let (lock, cvar) = &*self.shutdown;
+        *lock.lock().unwrap() = true;
+        cvar.notify_all();
+        if let Some(handle) = self.background_thread.take() {
+            handle.join().unwrap();
+        }
+    }
+}
+
+impl Drop for BackgroundWriter {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
@@ -0,0 +1,92 @@
+use std::sync::Mutex;
+
+use crate::utils::config::*;
+
+/// The kind of bulk operation a `BufferAccessStrategy` is used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferAccessStrategyType {
+    /// Large sequential scan.
+    BulkRead,
+    /// Large bulk load, e.g. `COPY` or `CREATE TABLE AS`.
+    BulkWrite,
+    /// Maintenance pass over a whole relation.
+    Vacuum,
+}
+
+impl BufferAccessStrategyType {
+    /// Default ring size in frames, before it is capped by the size of the buffer pool.
+    pub fn default_ring_size(self) -> usize {
+        match self {
+            // 256KB worth of pages.
+            BufferAccessStrategyType::BulkRead | BufferAccessStrategyType::Vacuum => {
+                256 * 1024 / BUSTUB_PAGE_SIZE
+            }
+            // 16MB worth of pages, so write-back can be batched.
+            BufferAccessStrategyType::BulkWrite => 16 * 1024 * 1024 / BUSTUB_PAGE_SIZE,
+        }
+    }
+}
+
+/// A slot of the ring: the frame it owns and the page the strategy loaded into it.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RingSlot {
+    pub frame_id: FrameId,
+    pub page_id: PageId,
+}
+
+#[derive(Debug)]
+struct RingState {
+    slots: Vec<Option<RingSlot>>,
+    current: usize,
+}
+
+/// BufferAccessStrategy confines a bulk operation to a small private ring of frames, similar to
+/// PostgreSQL's `BufferAccessStrategy`.
+///
+/// When the caller misses in the buffer pool, the frame of the current ring slot is recycled if
+/// it still holds the page this strategy put there and nobody has it pinned. Otherwise a frame is
+/// taken from the free list or the replacer as usual and becomes part of the ring. A large scan
+/// therefore only ever displaces `ring_size` frames of the hot working set.
+#[derive(Debug)]
+pub struct BufferAccessStrategy {
+    strategy_type: BufferAccessStrategyType,
+    ring: Mutex<RingState>,
+}
+
+impl BufferAccessStrategy {
+    /// Create a strategy with a ring of `ring_size` frames.
+    pub fn new(strategy_type: BufferAccessStrategyType, ring_size: usize) -> Self {
+        assert!(ring_size > 0, "ring size must be positive");
+        BufferAccessStrategy {
+            strategy_type,
+            ring: Mutex::new(RingState {
+                slots: vec![None; ring_size],
+                current: 0,
+            }),
+        }
+    }
+
+    /// Return the kind of bulk operation this strategy is used for.
+    pub fn get_strategy_type(&self) -> BufferAccessStrategyType {
+        self.strategy_type
+    }
+
+    /// Return the number of frames in the ring.
+    pub fn ring_size(&self) -> usize {
+        self.ring.lock().unwrap().slots.len()
+    }
+
+    /// Advance to the next slot and return what it currently holds.
+    pub(crate) fn next_slot(&self) -> Option<RingSlot> {
+        let mut ring = self.ring.lock().unwrap();
+        ring.current = (ring.current + 1) % ring.slots.len();
+        ring.slots[ring.current]
+    }
+
+    /// Record that the current slot now owns `frame_id`, loaded with `page_id`.
+    pub(crate) fn set_current_slot(&self, frame_id: FrameId, page_id: PageId) {
+        let mut ring = self.ring.lock().unwrap();
+        let current = ring.current;
+        ring.slots[current] = Some(RingSlot { frame_id, page_id });
+    }
+}
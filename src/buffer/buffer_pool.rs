@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+
+use crate::recovery::log_manager::LogManager;
+use crate::storage::page::page::Page;
+use crate::storage::page::page_guard::{
+    BasicPageGuard, OptimisticPageGuard, ReadPageGuard, WritePageGuard,
+};
+use crate::utils::config::*;
+
+use super::buffer_access_strategy::{BufferAccessStrategy, BufferAccessStrategyType};
+use super::buffer_pool_stats::{BufferPoolStats, PinRecord};
+use super::replacer::AccessType;
+
+/// BufferPool is the interface through which the rest of the system reaches pages. It is
+/// implemented by `BufferPoolManager` and by `ParallelBufferPoolManager`, so storage structures
+/// work on top of either one through an `Arc<dyn BufferPool>`.
+///
+/// The async fetches are inherent methods of each implementation, as async methods cannot be
+/// called through a trait object.
+pub trait BufferPool: Send + Sync {
+    /// Return the size (number of frames) of the buffer pool.
+    fn get_pool_size(&self) -> usize;
+
+    /// Grow or shrink the buffer pool to `pool_size` frames at runtime.
+    fn resize(&self, pool_size: usize);
+
+    /// Attach the log manager. From then on a dirty page is only written back once the log is
+    /// durable up to the page's LSN.
+    fn set_log_manager(&self, log_manager: Arc<dyn LogManager>);
+
+    /// Return a snapshot of the buffer pool counters.
+    fn get_stats(&self) -> BufferPoolStats;
+
+    /// Reset all buffer pool counters to zero.
+    fn reset_stats(&self);
+
+    /// Enable or disable pin tracking, see `get_outstanding_pins`.
+    fn set_pin_tracking(&self, enabled: bool);
+
+    /// Return the pins taken while pin tracking was enabled that have not been released yet.
+    fn get_outstanding_pins(&self) -> Vec<PinRecord>;
+
+    /// Create a new pinned page, or return `None` if every frame is pinned.
+    #[track_caller]
+    fn new_page(&self) -> Option<Arc<Page>>;
+
+    /// Create a new page, recycling a frame of the strategy's ring if possible.
+    #[track_caller]
+    fn new_page_with_strategy(&self, strategy: &BufferAccessStrategy) -> Option<Arc<Page>>;
+
+    /// Fetch and pin the requested page, reading it from disk if necessary. Return `None` if the
+    /// page needs to be read in but no frame is available.
+    #[track_caller]
+    fn fetch_page(&self, page_id: PageId, access_type: AccessType) -> Option<Arc<Page>>;
+
+    /// Fetch the requested page on behalf of a bulk operation. On a miss the page is read into a
+    /// frame of the strategy's ring instead of displacing the rest of the buffer pool.
+    #[track_caller]
+    fn fetch_page_with_strategy(
+        &self,
+        page_id: PageId,
+        access_type: AccessType,
+        strategy: &BufferAccessStrategy,
+    ) -> Option<Arc<Page>>;
+
+    /// Unpin the target page, marking it dirty if `is_dirty` is set. Return false if the page is
+    /// not in the buffer pool or its pin count is already 0.
+    fn unpin_page(&self, page_id: PageId, is_dirty: bool) -> bool;
+
+    /// Flush the target page to disk and unset its dirty flag. Return false if the page is not
+    /// in the buffer pool.
+    fn flush_page(&self, page_id: PageId) -> bool;
+
+    /// Flush all the pages in the buffer pool to disk.
+    fn flush_all_pages(&self);
+
+    /// Delete a page from the buffer pool and deallocate it. Return false if the page is pinned,
+    /// or is not allocated: it was never allocated or has already been deleted.
+    fn delete_page(&self, page_id: PageId) -> bool;
+
+    /// Return true if the page has been allocated and not deleted since, in this run or before
+    /// the database was reopened.
+    fn is_page_allocated(&self, page_id: PageId) -> bool;
+
+    /// Create a `BufferAccessStrategy` of the given type, sized for this buffer pool.
+    fn get_access_strategy(&self, strategy_type: BufferAccessStrategyType) -> BufferAccessStrategy;
+
+    /// Return a snapshot of the dirty page table, mapping every dirty resident page to its
+    /// recovery LSN.
+    fn get_dirty_page_table(&self) -> HashMap<PageId, Lsn>;
+
+    /// Write back up to `max_pages` dirty pages that nobody has pinned, oldest recovery LSN
+    /// first, and return how many were written.
+    fn write_back_dirty_pages(&self, max_pages: usize) -> usize;
+
+    /// Write the ids of the resident pages to `path`, coldest first. Return the number of pages
+    /// written.
+    fn dump_warm_pages(&self, path: &str) -> io::Result<usize>;
+
+    /// Prefetch the pages listed in a file written by `dump_warm_pages`. Return the number of
+    /// pages loaded.
+    fn restore_warm_pages(&self, path: &str) -> io::Result<usize>;
+
+    /// Fetch a page wrapped in a `BasicPageGuard`, which unpins it when dropped.
+    #[track_caller]
+    fn fetch_page_basic(&self, page_id: PageId) -> Option<BasicPageGuard<'_>>;
+
+    /// Create a new page wrapped in a `BasicPageGuard`.
+    #[track_caller]
+    fn new_page_guarded(&self) -> Option<BasicPageGuard<'_>>;
+
+    /// Fetch a page through a strategy and take its read latch, wrapped in a `ReadPageGuard`.
+    #[track_caller]
+    fn fetch_page_read_with_strategy(
+        &self,
+        page_id: PageId,
+        strategy: &BufferAccessStrategy,
+    ) -> Option<ReadPageGuard<'_>>;
+
+    /// Fetch a page through a strategy and take its write latch, wrapped in a `WritePageGuard`.
+    #[track_caller]
+    fn fetch_page_write_with_strategy(
+        &self,
+        page_id: PageId,
+        strategy: &BufferAccessStrategy,
+    ) -> Option<WritePageGuard<'_>>;
+
+    /// Create a new page through a strategy, wrapped in a `BasicPageGuard`.
+    #[track_caller]
+    fn new_page_guarded_with_strategy(
+        &self,
+        strategy: &BufferAccessStrategy,
+    ) -> Option<BasicPageGuard<'_>>;
+
+    /// Fetch a page and take its read latch, wrapped in a `ReadPageGuard`.
+    #[track_caller]
+    fn fetch_page_read(&self, page_id: PageId) -> Option<ReadPageGuard<'_>> {
+        self.fetch_page_basic(page_id)
+            .map(BasicPageGuard::upgrade_read)
+    }
+
+    /// Fetch a page and take its write latch, wrapped in a `WritePageGuard`.
+    #[track_caller]
+    fn fetch_page_write(&self, page_id: PageId) -> Option<WritePageGuard<'_>> {
+        self.fetch_page_basic(page_id)
+            .map(BasicPageGuard::upgrade_write)
+    }
+
+    /// Fetch a page for optimistic, latch-free reads, wrapped in an `OptimisticPageGuard`.
+    #[track_caller]
+    fn fetch_page_optimistic(&self, page_id: PageId) -> Option<OptimisticPageGuard<'_>> {
+        self.fetch_page_basic(page_id)
+            .map(BasicPageGuard::upgrade_optimistic)
+    }
+}
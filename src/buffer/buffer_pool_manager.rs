@@ -0,0 +1,1133 @@
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::io;
+use std::panic::Location;
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+
+use tokio::sync::oneshot;
+
+use crate::recovery::log_manager::LogManager;
+use crate::storage::disk::disk_manager::DiskManager;
+use crate::storage::disk::disk_scheduler::{DiskRequest, DiskScheduler};
+use crate::storage::page::page::Page;
+use crate::storage::page::page_guard::{
+    BasicPageGuard, ReadPageGuard, WritePageGuard,
+};
+use crate::storage::page::page_header::{PageHeader, PageType};
+use crate::log;
+use crate::utils::config::*;
+
+use super::buffer_access_strategy::{BufferAccessStrategy, BufferAccessStrategyType};
+use super::buffer_pool::BufferPool;
+use super::buffer_pool_stats::{BufferPoolCounters, BufferPoolStats, PinRecord};
+use super::replacer::{AccessType, Replacer, ReplacerPolicy, ReplacerStats};
+
+/// Completion of a request queued on the disk scheduler.
+type PendingIo = oneshot::Receiver<Vec<u8>>;
+
+/// Book-keeping protected by the buffer pool latch.
+#[derive(Debug, Default)]
+struct BufferPoolState {
+    /// Array of buffer pool pages, indexed by frame id. After the pool shrinks, frames at or
+    /// beyond the pool size are retired as soon as they are unpinned, and dropped from the end.
+    pages: Vec<Arc<Page>>,
+    /// Page table for keeping track of buffer pool pages.
+    page_table: HashMap<PageId, FrameId>,
+    /// List of free frames that don't have any pages on them.
+    free_list: VecDeque<FrameId>,
+    /// Deallocated page ids, handed out again by `new_page` before the file is extended. They
+    /// are only kept in memory, so pages freed before a restart are not reused after it.
+    free_pages: BTreeSet<PageId>,
+    /// Call site and thread of every outstanding pin, while pin tracking is enabled.
+    pin_records: HashMap<PageId, Vec<PinRecord>>,
+}
+
+/// BufferPoolManager reads disk pages to and from its internal buffer pool.
+pub struct BufferPoolManager {
+    /// Number of pages in the buffer pool.
+    pool_size: AtomicUsize,
+    /// How many instances are in the parallel BPM (if present, otherwise just 1 BPM).
+    num_instances: usize,
+    /// Index of this BPM instance in the parallel BPM (if present, otherwise just 0).
+    instance_index: usize,
+    /// The next page id to be allocated.
+    next_page_id: AtomicI32,
+    /// Scheduler used to issue reads and writes to the disk manager.
+    disk_scheduler: DiskScheduler,
+    /// Replacer to find unpinned pages for replacement.
+    replacer: Box<dyn Replacer>,
+    /// This latch protects the frames, the page table and the free list.
+    latch: Mutex<BufferPoolState>,
+    /// Log manager consulted before dirty pages are written back, once logging is enabled.
+    log_manager: RwLock<Option<Arc<dyn LogManager>>>,
+    /// Hit, miss, eviction and write-back counters, shared with reads completed by the disk
+    /// scheduler.
+    stats: Arc<BufferPoolCounters>,
+    /// Debug mode: record where every pin was taken so leaked pins can be reported.
+    pin_tracking: AtomicBool,
+}
+
+impl BufferPoolManager {
+    /// Creates a new BufferPoolManager using the LRU-K replacement policy.
+    pub fn new(pool_size: usize, disk_manager: Arc<Mutex<DiskManager>>, replacer_k: usize) -> Self {
+        Self::new_with_policy(pool_size, disk_manager, ReplacerPolicy::LruK(replacer_k))
+    }
+
+    /// Creates a new BufferPoolManager using the given replacement policy.
+    pub fn new_with_policy(
+        pool_size: usize,
+        disk_manager: Arc<Mutex<DiskManager>>,
+        policy: ReplacerPolicy,
+    ) -> Self {
+        Self::new_instance(pool_size, 1, 0, disk_manager, policy)
+    }
+
+    /// Creates a new BufferPoolManager that is one of `num_instances` instances of a parallel
+    /// buffer pool. The instance only allocates page ids `p` with
+    /// `p % num_instances == instance_index`.
+    pub fn new_instance(
+        pool_size: usize,
+        num_instances: usize,
+        instance_index: usize,
+        disk_manager: Arc<Mutex<DiskManager>>,
+        policy: ReplacerPolicy,
+    ) -> Self {
+        assert!(
+            num_instances > 0,
+            "If BPI is not part of a pool, then the pool size should just be 1"
+        );
+        assert!(
+            instance_index < num_instances,
+            "BPI index cannot be greater than the number of BPIs in the pool"
+        );
+        // Resume allocating after the pages already in the database file, so that reopening a
+        // database never hands out the id of an existing page again.
+        let num_pages = disk_manager.lock().unwrap().get_num_pages();
+        let next_page_id =
+            num_pages + (instance_index + num_instances - num_pages % num_instances) % num_instances;
+        let free_pages =
+            Self::scan_free_pages(&disk_manager, num_pages, num_instances, instance_index);
+        // Initially, every page is in the free list.
+        let state = BufferPoolState {
+            pages: (0..pool_size).map(|_| Arc::new(Page::new())).collect(),
+            page_table: HashMap::new(),
+            free_list: (0..pool_size as FrameId).collect(),
+            free_pages,
+            pin_records: HashMap::new(),
+        };
+        BufferPoolManager {
+            pool_size: AtomicUsize::new(pool_size),
+            num_instances,
+            instance_index,
+            next_page_id: AtomicI32::new(next_page_id as PageId),
+            disk_scheduler: DiskScheduler::new(disk_manager),
+            replacer: policy.build(pool_size),
+            latch: Mutex::new(state),
+            log_manager: RwLock::new(None),
+            stats: Arc::new(BufferPoolCounters::default()),
+            pin_tracking: AtomicBool::new(false),
+        }
+    }
+
+    /// Return the number of frames currently allocated. This is larger than the pool size while
+    /// frames retired by shrinking the pool are still pinned.
+    pub fn get_num_frames(&self) -> usize {
+        self.latch.lock().unwrap().pages.len()
+    }
+
+    /// Return the number of instances of the parallel buffer pool this instance belongs to.
+    pub fn get_num_instances(&self) -> usize {
+        self.num_instances
+    }
+
+    /// Return the index of this instance in its parallel buffer pool.
+    pub fn get_instance_index(&self) -> usize {
+        self.instance_index
+    }
+
+    /// Return the hit/miss counters of the replacer.
+    pub fn get_replacer_stats(&self) -> ReplacerStats {
+        self.replacer.stats()
+    }
+
+    /// Return the pages of the buffer pool, indexed by frame id.
+    pub fn get_pages(&self) -> Vec<Arc<Page>> {
+        self.latch.lock().unwrap().pages.clone()
+    }
+
+    /// Return the resident page ids, coldest first.
+    pub(crate) fn get_warm_page_ids(&self) -> Vec<PageId> {
+        let state = self.latch.lock().unwrap();
+        let mut frame_ids = self.replacer.eviction_order();
+        let ordered: HashSet<FrameId> = frame_ids.iter().copied().collect();
+        // Pinned frames the replacer does not know about are the hottest of all.
+        frame_ids.extend(
+            state
+                .page_table
+                .values()
+                .filter(|frame_id| !ordered.contains(frame_id)),
+        );
+        frame_ids
+            .into_iter()
+            .map(|frame_id| state.pages[frame_id as usize].get_page_id())
+            .filter(|page_id| state.page_table.contains_key(page_id))
+            .collect()
+    }
+
+    /// Read the given pages, coldest first, into free frames through the disk scheduler.
+    pub(crate) fn prefetch_pages(&self, page_ids: &[PageId]) -> usize {
+        let mut state = self.latch.lock().unwrap();
+        let page_ids: Vec<PageId> = page_ids
+            .iter()
+            .copied()
+            .filter(|&page_id| {
+                page_id != INVALID_PAGE_ID
+                    && page_id as usize % self.num_instances == self.instance_index
+                    && !state.page_table.contains_key(&page_id)
+            })
+            .collect();
+        let skip = page_ids.len().saturating_sub(state.free_list.len());
+        let mut loads = Vec::new();
+        for page_id in page_ids.into_iter().skip(skip) {
+            let frame_id = state.free_list.pop_front().unwrap();
+            let page = state.pages[frame_id as usize].clone();
+            page.set_page_id(page_id);
+            page.set_dirty(false);
+            let done = self.schedule_read(page_id);
+            state.page_table.insert(page_id, frame_id);
+            loads.push((frame_id, page_id, done));
+        }
+        let num_loaded = loads.len();
+        for (frame_id, page_id, done) in loads {
+            self.finish_read(&state.pages[frame_id as usize], done);
+            BufferPoolCounters::incr(&self.stats.misses);
+            self.replacer.record_access(frame_id, page_id, AccessType::Unknown);
+            self.replacer.set_evictable(frame_id, true);
+        }
+        num_loaded
+    }
+
+    /// Return the dirty resident pages along with their recovery LSN.
+    fn dirty_pages(state: &BufferPoolState) -> impl Iterator<Item = (PageId, Lsn)> + '_ {
+        state.page_table.iter().filter_map(|(&page_id, &frame_id)| {
+            let page = &state.pages[frame_id as usize];
+            page.is_dirty().then(|| (page_id, page.get_rec_lsn()))
+        })
+    }
+
+    /// Pin the page held by a frame for a write-back, without counting as an access.
+    fn pin_for_write_back(
+        &self,
+        state: &BufferPoolState,
+        frame_id: FrameId,
+    ) -> (FrameId, Arc<Page>) {
+        let page = state.pages[frame_id as usize].clone();
+        page.pin();
+        self.replacer.set_evictable(frame_id, false);
+        (frame_id, page)
+    }
+
+    /// Write back pages pinned by `pin_for_write_back`, mark them clean and unpin them.
+    ///
+    /// The buffer pool latch is not held while a page is written: the pin keeps it in its frame,
+    /// and its read latch keeps writers out while it is copied and marked clean. The read latch
+    /// is released before the pin, as releasing the last pin may retire the frame, which takes
+    /// its write latch.
+    fn write_back_pinned(&self, pages: &[(FrameId, Arc<Page>)]) {
+        for (frame_id, page) in pages {
+            page.r_latch();
+            if page.is_dirty() {
+                BufferPoolCounters::incr(&self.stats.dirty_write_backs);
+            }
+            let write = self.schedule_write(page);
+            page.set_dirty(false);
+            page.r_unlatch();
+            {
+                let mut state = self.latch.lock().unwrap();
+                if page.unpin() == 0 {
+                    self.release_frame(&mut state, *frame_id);
+                }
+            }
+            Self::wait_for(Some(write));
+        }
+    }
+
+    /// Fetch a page and take its read latch from an async task, wrapped in a `ReadPageGuard`.
+    ///
+    /// Unlike `fetch_page_read`, the task yields instead of blocking its worker thread while the
+    /// page is read from disk, while the log is flushed ahead of a dirty victim, or while a latch
+    /// is held. The future can be dropped at any point: the page is unpinned, and a read in flight
+    /// still completes. Return `None` if the page needs to be read in but every frame is pinned.
+    pub async fn fetch_page_read_async(&self, page_id: PageId) -> Option<ReadPageGuard<'_>> {
+        let page = self
+            .fetch_page_deferred(page_id, AccessType::Unknown)
+            .await?;
+        Some(BasicPageGuard::new(self, page).upgrade_read_async().await)
+    }
+
+    /// Fetch a page and take its write latch from an async task, wrapped in a `WritePageGuard`.
+    /// See `fetch_page_read_async`.
+    pub async fn fetch_page_write_async(&self, page_id: PageId) -> Option<WritePageGuard<'_>> {
+        let page = self
+            .fetch_page_deferred(page_id, AccessType::Unknown)
+            .await?;
+        Some(BasicPageGuard::new(self, page).upgrade_write_async().await)
+    }
+
+    #[track_caller]
+    fn new_page_impl(&self, strategy: Option<&BufferAccessStrategy>) -> Option<Arc<Page>> {
+        let mut state = self.latch.lock().unwrap();
+        let frame_id = self.acquire_frame(&mut state, strategy)?;
+        let (page_id, reused) = self.allocate_page(&mut state);
+        let page = state.pages[frame_id as usize].clone();
+        page.reset_memory();
+        // No change to the new page has been logged yet.
+        page.set_lsn(INVALID_LSN);
+        page.publish_data();
+        page.set_page_id(page_id);
+        page.set_dirty(false);
+        if reused {
+            // The old contents of the page are still on disk: make sure they get overwritten
+            // even if the new page is never modified.
+            page.mark_dirty();
+        }
+        state.page_table.insert(page_id, frame_id);
+        if let Some(strategy) = strategy {
+            strategy.set_current_slot(frame_id, page_id);
+        }
+        self.pin_frame(&mut state, frame_id, AccessType::Unknown);
+        Some(page)
+    }
+
+    #[track_caller]
+    fn fetch_page_impl(
+        &self,
+        page_id: PageId,
+        access_type: AccessType,
+        strategy: Option<&BufferAccessStrategy>,
+    ) -> Option<Arc<Page>> {
+        if page_id == INVALID_PAGE_ID {
+            return None;
+        }
+        self.validate_page_id(page_id);
+        let mut state = self.latch.lock().unwrap();
+        if let Some(&frame_id) = state.page_table.get(&page_id) {
+            BufferPoolCounters::incr(&self.stats.hits);
+            self.pin_frame(&mut state, frame_id, access_type);
+            return Some(state.pages[frame_id as usize].clone());
+        }
+        BufferPoolCounters::incr(&self.stats.misses);
+        let frame_id = self.acquire_frame(&mut state, strategy)?;
+        let page = state.pages[frame_id as usize].clone();
+        page.set_page_id(page_id);
+        page.set_dirty(false);
+        self.finish_read(&page, self.schedule_read(page_id));
+        state.page_table.insert(page_id, frame_id);
+        if let Some(strategy) = strategy {
+            strategy.set_current_slot(frame_id, page_id);
+        }
+        self.pin_frame(&mut state, frame_id, access_type);
+        Some(page)
+    }
+
+    /// Version of `fetch_page_impl` for async tasks, which never blocks its worker thread on the
+    /// disk, the log or a page latch. On a miss the read is only queued: the frame is write
+    /// latched until the read completes, and the disk scheduler releases the latch once it has
+    /// filled the frame. Fetchers of the page, this one included, wait for the read by latching
+    /// the page.
+    ///
+    /// A victim is taken right away if its latch is free and its write-back needs no log flush.
+    /// Otherwise it is pinned, so that it is neither evicted nor retired meanwhile, and the task
+    /// waits for its latch and flushes the log with the buffer pool latch released. The victim
+    /// is given back if somebody else fetched it in between, or if the future is dropped.
+    async fn fetch_page_deferred(
+        &self,
+        page_id: PageId,
+        access_type: AccessType,
+    ) -> Option<Arc<Page>> {
+        if page_id == INVALID_PAGE_ID {
+            return None;
+        }
+        self.validate_page_id(page_id);
+        let mut missed = false;
+        loop {
+            let mut victim = {
+                let mut state = self.latch.lock().unwrap();
+                if let Some(&frame_id) = state.page_table.get(&page_id) {
+                    BufferPoolCounters::incr(&self.stats.hits);
+                    self.pin_frame(&mut state, frame_id, access_type);
+                    return Some(state.pages[frame_id as usize].clone());
+                }
+                if !missed {
+                    BufferPoolCounters::incr(&self.stats.misses);
+                    missed = true;
+                }
+                if let Some(frame_id) = state.free_list.pop_front() {
+                    // Nobody holds the latch of a free frame, so this does not block.
+                    state.pages[frame_id as usize].w_latch();
+                    return Some(self.load_latched_frame(&mut state, frame_id, page_id, access_type));
+                }
+                let Some(frame_id) = self.replacer.evict() else {
+                    BufferPoolCounters::incr(&self.stats.pin_waits);
+                    return None;
+                };
+                let page = state.pages[frame_id as usize].clone();
+                // Only a deferred read still filling the victim holds its latch.
+                let is_latched = page.try_w_latch();
+                if is_latched && !self.needs_log_flush(&page) {
+                    // The write-back works on a copy of the victim, so it does not need to be
+                    // awaited.
+                    self.detach_frame(&mut state, frame_id);
+                    return Some(self.load_latched_frame(&mut state, frame_id, page_id, access_type));
+                }
+                page.pin();
+                self.replacer
+                    .record_access(frame_id, page.get_page_id(), AccessType::Unknown);
+                self.replacer.set_evictable(frame_id, false);
+                PinnedVictim {
+                    bpm: self,
+                    frame_id,
+                    page: Some(page),
+                    is_latched,
+                }
+            };
+            let page = victim.page.clone().unwrap();
+            if !victim.is_latched {
+                page.w_latch_async().await;
+                victim.is_latched = true;
+            }
+            // The latch keeps the victim from being modified again.
+            if page.is_dirty() {
+                self.flush_log_up_to_async(page.get_lsn()).await;
+            }
+            let frame_id = victim.frame_id;
+            let mut state = self.latch.lock().unwrap();
+            if page.get_pin_count() == 1
+                && (frame_id as usize) < self.get_pool_size()
+                && !state.page_table.contains_key(&page_id)
+            {
+                victim.page = None;
+                page.unpin();
+                self.replacer.set_evictable(frame_id, true);
+                self.replacer.remove(frame_id);
+                self.detach_frame(&mut state, frame_id);
+                return Some(self.load_latched_frame(&mut state, frame_id, page_id, access_type));
+            }
+            // The victim was fetched again, the pool shrank below it, or the page was read in
+            // by somebody else: give the victim back and start over.
+            drop(state);
+        }
+    }
+
+    /// Bring the page into a free frame whose write latch the caller holds, handing the latch
+    /// over to a deferred read of the page.
+    fn load_latched_frame(
+        &self,
+        state: &mut BufferPoolState,
+        frame_id: FrameId,
+        page_id: PageId,
+        access_type: AccessType,
+    ) -> Arc<Page> {
+        let page = state.pages[frame_id as usize].clone();
+        page.set_page_id(page_id);
+        page.set_dirty(false);
+        self.schedule_read_into(page.clone());
+        state.page_table.insert(page_id, frame_id);
+        self.pin_frame(state, frame_id, access_type);
+        page
+    }
+
+    /// Pick a frame for a page that is about to be brought in. A frame of the strategy's ring is
+    /// recycled first if it still holds the page the strategy loaded and is unpinned; otherwise
+    /// the frame comes from the free list or the replacer.
+    fn acquire_frame(
+        &self,
+        state: &mut BufferPoolState,
+        strategy: Option<&BufferAccessStrategy>,
+    ) -> Option<FrameId> {
+        if let Some(slot) = strategy.and_then(BufferAccessStrategy::next_slot) {
+            if state.page_table.get(&slot.page_id) == Some(&slot.frame_id)
+                && state.pages[slot.frame_id as usize].get_pin_count() == 0
+            {
+                self.replacer.remove(slot.frame_id);
+                self.evict_frame(state, slot.frame_id);
+                return Some(slot.frame_id);
+            }
+        }
+        if let Some(frame_id) = state.free_list.pop_front() {
+            return Some(frame_id);
+        }
+        let Some(frame_id) = self.replacer.evict() else {
+            BufferPoolCounters::incr(&self.stats.pin_waits);
+            return None;
+        };
+        self.evict_frame(state, frame_id);
+        Some(frame_id)
+    }
+
+    /// Detach the page held by a frame that left the replacer, writing it back if dirty.
+    fn evict_frame(&self, state: &mut BufferPoolState, frame_id: FrameId) {
+        Self::wait_for_deferred_read(&state.pages[frame_id as usize]);
+        Self::wait_for(self.detach_frame(state, frame_id));
+    }
+
+    /// Detach the page held by a frame that left the replacer and whose latch is free or held by
+    /// the caller, scheduling its write-back if it is dirty.
+    fn detach_frame(&self, state: &mut BufferPoolState, frame_id: FrameId) -> Option<PendingIo> {
+        BufferPoolCounters::incr(&self.stats.evictions);
+        let page = state.pages[frame_id as usize].clone();
+        let write_back = page
+            .is_dirty()
+            .then(|| self.schedule_write_back(state, frame_id));
+        state.page_table.remove(&page.get_page_id());
+        write_back
+    }
+
+    /// Schedule the write of the page held by an unpinned frame and mark it clean.
+    fn schedule_write_back(&self, state: &mut BufferPoolState, frame_id: FrameId) -> PendingIo {
+        let page = state.pages[frame_id as usize].clone();
+        if page.is_dirty() {
+            BufferPoolCounters::incr(&self.stats.dirty_write_backs);
+        }
+        let write = self.schedule_write(&page);
+        page.set_dirty(false);
+        write
+    }
+
+    /// Block until the deferred read of an unpinned frame, if any, has filled it. The read holds
+    /// the write latch of the frame until then; an async fetch may have unpinned it earlier. The
+    /// disk scheduler never waits for the buffer pool latch, so this cannot deadlock.
+    fn wait_for_deferred_read(page: &Page) {
+        page.w_latch();
+        page.w_unlatch();
+    }
+
+    /// Block until a scheduled disk request completes.
+    fn wait_for(io: Option<PendingIo>) {
+        if let Some(io) = io {
+            io.blocking_recv().unwrap();
+        }
+    }
+
+    /// Called when the last pin of a frame is released: the frame becomes evictable, or is
+    /// retired right away if the pool has shrunk below it.
+    fn release_frame(&self, state: &mut BufferPoolState, frame_id: FrameId) {
+        if (frame_id as usize) < self.get_pool_size() {
+            self.replacer.set_evictable(frame_id, true);
+            return;
+        }
+        self.retire_frame(state, frame_id);
+        self.truncate_frames(state);
+    }
+
+    /// Evict the page held by an unpinned frame beyond the pool size and leave the frame empty.
+    fn retire_frame(&self, state: &mut BufferPoolState, frame_id: FrameId) {
+        self.replacer.set_evictable(frame_id, true);
+        self.replacer.remove(frame_id);
+        self.evict_frame(state, frame_id);
+        let page = &state.pages[frame_id as usize];
+        page.reset_memory();
+        page.set_page_id(INVALID_PAGE_ID);
+    }
+
+    /// Drop the empty retired frames from the end of the frame array.
+    fn truncate_frames(&self, state: &mut BufferPoolState) {
+        let pool_size = self.get_pool_size();
+        while state.pages.len() > pool_size
+            && state.pages.last().unwrap().get_page_id() == INVALID_PAGE_ID
+        {
+            state.pages.pop();
+        }
+        self.replacer.resize(state.pages.len());
+    }
+
+    /// Pin the page held by the frame and make sure the replacer does not evict it.
+    #[track_caller]
+    fn pin_frame(&self, state: &mut BufferPoolState, frame_id: FrameId, access_type: AccessType) {
+        let page = state.pages[frame_id as usize].clone();
+        page.pin();
+        if self.pin_tracking.load(Ordering::SeqCst) {
+            state
+                .pin_records
+                .entry(page.get_page_id())
+                .or_default()
+                .push(PinRecord::new(page.get_page_id(), Location::caller()));
+        }
+        self.replacer
+            .record_access(frame_id, page.get_page_id(), access_type);
+        self.replacer.set_evictable(frame_id, false);
+    }
+
+    /// Enforce the write-ahead rule: make sure the log is durable up to `lsn` before a page with
+    /// that LSN reaches the disk, forcing a log flush if it is not.
+    fn flush_log_up_to(&self, lsn: Lsn) {
+        if let Some(log_manager) = self.log_to_flush(lsn) {
+            log_manager.flush(lsn);
+        }
+    }
+
+    /// Same as `flush_log_up_to`, but the log flush runs on a blocking thread while the task
+    /// yields.
+    async fn flush_log_up_to_async(&self, lsn: Lsn) {
+        if let Some(log_manager) = self.log_to_flush(lsn) {
+            tokio::task::spawn_blocking(move || log_manager.flush(lsn))
+                .await
+                .unwrap();
+        }
+    }
+
+    /// Return the log manager if the log must be flushed before a page with LSN `lsn` can
+    /// reach the disk.
+    fn log_to_flush(&self, lsn: Lsn) -> Option<Arc<dyn LogManager>> {
+        let log_manager = self.log_manager.read().unwrap().clone()?;
+        // Pages that were never logged still carry the `INVALID_LSN` they were created with.
+        // Any other LSN, 0 included, is a log record that must be durable first.
+        (lsn != INVALID_LSN && lsn > log_manager.get_persistent_lsn()).then_some(log_manager)
+    }
+
+    /// Return true if writing the page back would have to flush the log first. The caller must
+    /// hold a latch of the page.
+    fn needs_log_flush(&self, page: &Page) -> bool {
+        page.is_dirty() && self.log_to_flush(page.get_lsn()).is_some()
+    }
+
+    /// Queue a read of the page on the disk scheduler. The page it read is handed back when the
+    /// request completes.
+    fn schedule_read(&self, page_id: PageId) -> PendingIo {
+        let (callback, done) = DiskScheduler::create_promise();
+        self.disk_scheduler.schedule(DiskRequest::new(
+            false,
+            vec![0; BUSTUB_PAGE_SIZE],
+            page_id,
+            callback,
+        ));
+        done
+    }
+
+    /// Queue a read of the page held by a frame straight into the frame. The write latch of the
+    /// frame is handed over to the read, which releases it once the frame is filled: the caller
+    /// must hold the write latch and must not release it.
+    fn schedule_read_into(&self, page: Arc<Page>) {
+        let page_id = page.get_page_id();
+        let stats = self.stats.clone();
+        let fill = move |data: Vec<u8>| {
+            Self::check_read(&stats, page_id, &data);
+            page.get_data_mut().copy_from_slice(&data);
+            page.w_unlatch();
+        };
+        self.disk_scheduler.schedule(DiskRequest::new(
+            false,
+            vec![0; BUSTUB_PAGE_SIZE],
+            page_id,
+            Box::new(fill),
+        ));
+    }
+
+    /// Block until a scheduled read completes and copy the page it read into the frame. The
+    /// caller must be allowed to write the frame: it holds the write latch, or owns the frame.
+    fn finish_read(&self, page: &Page, read: PendingIo) {
+        let data = read.blocking_recv().unwrap();
+        Self::check_read(&self.stats, page.get_page_id(), &data);
+        page.get_data_mut().copy_from_slice(&data);
+        page.publish_data();
+    }
+
+    /// Validate the page header of a page read from disk. Pages without a header, e.g. never
+    /// written ones, are not checked. A page that fails is still loaded, but the failure is
+    /// logged and counted in the `corrupt_reads` counter.
+    fn check_read(stats: &BufferPoolCounters, page_id: PageId, data: &[u8]) {
+        let header = PageHeader::new(data);
+        if !header.is_formatted() {
+            return;
+        }
+        if let Err(err) = header.validate(Some(page_id)) {
+            log!("page {} read from disk failed validation: {}", page_id, err);
+            BufferPoolCounters::incr(&stats.corrupt_reads);
+        }
+    }
+
+    /// Queue a write of a copy of the page on the disk scheduler, once the log is durable up to
+    /// the LSN of the page. The frame itself is left untouched, and can be modified again as soon
+    /// as this returns. The caller must be allowed to read the frame: it holds a latch, or owns
+    /// the frame.
+    fn schedule_write(&self, page: &Page) -> PendingIo {
+        let mut data = page.get_data().to_vec();
+        let mut header = PageHeader::new(&mut data[..]);
+        if header.is_formatted() {
+            header.update_checksum();
+        }
+        self.flush_log_up_to(header.get_lsn());
+        let (callback, done) = DiskScheduler::create_promise();
+        self.disk_scheduler
+            .schedule(DiskRequest::new(true, data, page.get_page_id(), callback));
+        done
+    }
+
+    /// Allocate a page on disk, reusing the lowest deallocated page if there is one. Return the
+    /// page id and whether it was reused.
+    fn allocate_page(&self, state: &mut BufferPoolState) -> (PageId, bool) {
+        if let Some(page_id) = state.free_pages.pop_first() {
+            return (page_id, true);
+        }
+        let page_id = self
+            .next_page_id
+            .fetch_add(self.num_instances as PageId, Ordering::SeqCst);
+        self.validate_page_id(page_id);
+        (page_id, false)
+    }
+
+    /// Make sure the page id belongs to this instance.
+    fn validate_page_id(&self, page_id: PageId) {
+        // Allocated pages mod back to this BPI.
+        assert_eq!(
+            page_id as usize % self.num_instances,
+            self.instance_index,
+            "page {} does not belong to buffer pool instance {}",
+            page_id,
+            self.instance_index
+        );
+    }
+
+    /// Deallocate a page on disk, so that a later `new_page` can reuse it. The page is
+    /// overwritten with an empty page of type `PageType::Free`, which is how `scan_free_pages`
+    /// finds it again once the database is reopened.
+    fn deallocate_page(&self, state: &mut BufferPoolState, page_id: PageId) {
+        state.free_pages.insert(page_id);
+        let mut data = vec![0; BUSTUB_PAGE_SIZE];
+        let mut header = PageHeader::new(&mut data[..]);
+        header.init(page_id, PageType::Free);
+        header.set_lsn(INVALID_LSN);
+        header.update_checksum();
+        let (callback, done) = DiskScheduler::create_promise();
+        self.disk_scheduler
+            .schedule(DiskRequest::new(true, data, page_id, callback));
+        Self::wait_for(Some(done));
+    }
+
+    /// Return the pages of this instance in the first `num_pages` of the database file that
+    /// were deallocated before it was reopened.
+    fn scan_free_pages(
+        disk_manager: &Mutex<DiskManager>,
+        num_pages: usize,
+        num_instances: usize,
+        instance_index: usize,
+    ) -> BTreeSet<PageId> {
+        let mut disk_manager = disk_manager.lock().unwrap();
+        let mut data = vec![0; BUSTUB_PAGE_SIZE];
+        (instance_index..num_pages)
+            .step_by(num_instances)
+            .map(|page_id| page_id as PageId)
+            .filter(|&page_id| {
+                disk_manager.read_page(page_id, &mut data);
+                PageHeader::new(&data[..]).validate(Some(page_id)) == Ok(PageType::Free)
+            })
+            .collect()
+    }
+
+    /// Return true if the page has been allocated and not deallocated since, in this run or
+    /// before the database was reopened.
+    fn is_allocated(&self, state: &BufferPoolState, page_id: PageId) -> bool {
+        page_id >= 0
+            && page_id as usize % self.num_instances == self.instance_index
+            && page_id < self.next_page_id.load(Ordering::SeqCst)
+            && !state.free_pages.contains(&page_id)
+    }
+}
+
+impl BufferPool for BufferPoolManager {
+    /// Return the size (number of frames) of the buffer pool.
+    fn get_pool_size(&self) -> usize {
+        self.pool_size.load(Ordering::SeqCst)
+    }
+
+    /// Grow or shrink the buffer pool to `pool_size` frames at runtime.
+    ///
+    /// Growing adds empty frames to the free list. Shrinking retires the frames at or beyond the
+    /// new size: free ones are released immediately, resident unpinned ones are evicted (written
+    /// back if dirty), and pinned ones are evicted as soon as their last pin is released. Pages
+    /// held by retired frames stay valid for as long as their pinners hold them.
+    fn resize(&self, pool_size: usize) {
+        assert!(pool_size > 0, "buffer pool size must be positive");
+        let mut state = self.latch.lock().unwrap();
+        let old_size = self.pool_size.swap(pool_size, Ordering::SeqCst);
+        if pool_size >= old_size {
+            let num_frames = state.pages.len().max(pool_size);
+            self.replacer.resize(num_frames);
+            // Retired frames that were still pinned are back in service; empty ones are free.
+            for frame_id in old_size..state.pages.len().min(pool_size) {
+                if state.pages[frame_id].get_page_id() == INVALID_PAGE_ID {
+                    state.free_list.push_back(frame_id as FrameId);
+                }
+            }
+            for frame_id in state.pages.len()..pool_size {
+                state.pages.push(Arc::new(Page::new()));
+                state.free_list.push_back(frame_id as FrameId);
+            }
+            return;
+        }
+        state.free_list.retain(|&frame_id| (frame_id as usize) < pool_size);
+        for frame_id in pool_size..state.pages.len() {
+            let page = &state.pages[frame_id];
+            if page.get_page_id() != INVALID_PAGE_ID && page.get_pin_count() == 0 {
+                self.retire_frame(&mut state, frame_id as FrameId);
+            }
+        }
+        self.truncate_frames(&mut state);
+    }
+
+    /// Attach the log manager. From then on a dirty page is only written back once the log is
+    /// durable up to the page's LSN.
+    fn set_log_manager(&self, log_manager: Arc<dyn LogManager>) {
+        *self.log_manager.write().unwrap() = Some(log_manager);
+    }
+
+    /// Return a snapshot of the buffer pool counters.
+    fn get_stats(&self) -> BufferPoolStats {
+        self.stats.snapshot()
+    }
+
+    /// Reset all buffer pool counters to zero.
+    fn reset_stats(&self) {
+        self.stats.reset();
+    }
+
+    /// Enable or disable pin tracking. While enabled, the call site and thread of every pin is
+    /// recorded until the matching unpin, so pages that are never unpinned can be reported by
+    /// `get_outstanding_pins`, and are logged when the buffer pool is dropped.
+    fn set_pin_tracking(&self, enabled: bool) {
+        let mut state = self.latch.lock().unwrap();
+        self.pin_tracking.store(enabled, Ordering::SeqCst);
+        if !enabled {
+            state.pin_records.clear();
+        }
+    }
+
+    /// Return the pins taken while pin tracking was enabled that have not been released yet.
+    fn get_outstanding_pins(&self) -> Vec<PinRecord> {
+        let state = self.latch.lock().unwrap();
+        let mut records: Vec<PinRecord> = state.pin_records.values().flatten().cloned().collect();
+        records.sort_by_key(|record| record.page_id);
+        records
+    }
+
+    /// Create a new page in the buffer pool.
+    ///
+    /// The frame is picked from the free list first, then from the replacer (writing the victim
+    /// back if it is dirty). The new page is pinned and returned, or `None` if all frames are
+    /// currently in use and not evictable.
+    #[track_caller]
+    fn new_page(&self) -> Option<Arc<Page>> {
+        self.new_page_impl(None)
+    }
+
+    /// Create a new page, recycling a frame of the strategy's ring if possible.
+    #[track_caller]
+    fn new_page_with_strategy(&self, strategy: &BufferAccessStrategy) -> Option<Arc<Page>> {
+        self.new_page_impl(Some(strategy))
+    }
+
+    /// Fetch the requested page from the buffer pool, reading it from disk if necessary.
+    ///
+    /// Return `None` if the page needs to be read in but no frame is available.
+    #[track_caller]
+    fn fetch_page(&self, page_id: PageId, access_type: AccessType) -> Option<Arc<Page>> {
+        self.fetch_page_impl(page_id, access_type, None)
+    }
+
+    /// Fetch the requested page on behalf of a bulk operation. On a miss the page is read into a
+    /// frame of the strategy's ring instead of displacing the rest of the buffer pool.
+    #[track_caller]
+    fn fetch_page_with_strategy(
+        &self,
+        page_id: PageId,
+        access_type: AccessType,
+        strategy: &BufferAccessStrategy,
+    ) -> Option<Arc<Page>> {
+        self.fetch_page_impl(page_id, access_type, Some(strategy))
+    }
+
+    /// Unpin the target page from the buffer pool. Pages modified through a `WritePageGuard` are
+    /// already marked dirty; `is_dirty` marks the page dirty too if it is not.
+    ///
+    /// Return false if the page is not in the buffer pool or its pin count is already 0.
+    fn unpin_page(&self, page_id: PageId, is_dirty: bool) -> bool {
+        if is_dirty {
+            let page = {
+                let state = self.latch.lock().unwrap();
+                let Some(&frame_id) = state.page_table.get(&page_id) else {
+                    return false;
+                };
+                state.pages[frame_id as usize].clone()
+            };
+            // The LSN is read under the page latch, so it must be taken without the buffer pool
+            // latch held. The caller's pin keeps the page in its frame meanwhile.
+            page.r_latch();
+            if page.get_page_id() == page_id && page.get_pin_count() > 0 {
+                page.mark_dirty();
+            }
+            page.r_unlatch();
+        }
+        let mut state = self.latch.lock().unwrap();
+        let Some(&frame_id) = state.page_table.get(&page_id) else {
+            return false;
+        };
+        let page = state.pages[frame_id as usize].clone();
+        if page.get_pin_count() <= 0 {
+            return false;
+        }
+        if let Some(records) = state.pin_records.get_mut(&page_id) {
+            // Release the most recent pin of this thread, or the oldest one if it has none.
+            let thread_id = std::thread::current().id();
+            let pos = records
+                .iter()
+                .rposition(|record| record.thread_id == thread_id)
+                .unwrap_or(0);
+            records.remove(pos);
+            if records.is_empty() {
+                state.pin_records.remove(&page_id);
+            }
+        }
+        if page.unpin() == 0 {
+            self.release_frame(&mut state, frame_id);
+        }
+        true
+    }
+
+    /// Flush the target page to disk, regardless of the dirty flag, and unset the dirty flag.
+    /// Waits for a writer holding the page latch to finish.
+    ///
+    /// Return false if the page could not be found in the page table.
+    fn flush_page(&self, page_id: PageId) -> bool {
+        let page = {
+            let state = self.latch.lock().unwrap();
+            let Some(&frame_id) = state.page_table.get(&page_id) else {
+                return false;
+            };
+            self.pin_for_write_back(&state, frame_id)
+        };
+        self.write_back_pinned(&[page]);
+        true
+    }
+
+    /// Flush all the pages in the buffer pool to disk.
+    fn flush_all_pages(&self) {
+        let pages: Vec<(FrameId, Arc<Page>)> = {
+            let state = self.latch.lock().unwrap();
+            state
+                .page_table
+                .values()
+                .map(|&frame_id| self.pin_for_write_back(&state, frame_id))
+                .collect()
+        };
+        self.write_back_pinned(&pages);
+    }
+
+    /// Delete a page from the buffer pool and deallocate it, so that its page id is reused by a
+    /// later `new_page`.
+    ///
+    /// Return false if the page is pinned and cannot be deleted, or is not allocated, true
+    /// otherwise (including when the page is not resident at all). Refusing a page that is
+    /// already free keeps a repeated delete from deallocating it twice.
+    fn delete_page(&self, page_id: PageId) -> bool {
+        let mut state = self.latch.lock().unwrap();
+        if !self.is_allocated(&state, page_id) {
+            return false;
+        }
+        let Some(&frame_id) = state.page_table.get(&page_id) else {
+            self.deallocate_page(&mut state, page_id);
+            return true;
+        };
+        let page = state.pages[frame_id as usize].clone();
+        if page.get_pin_count() > 0 {
+            return false;
+        }
+        state.page_table.remove(&page_id);
+        self.replacer.remove(frame_id);
+        Self::wait_for_deferred_read(&page);
+        page.reset_memory();
+        page.set_page_id(INVALID_PAGE_ID);
+        page.set_dirty(false);
+        if (frame_id as usize) < self.get_pool_size() {
+            state.free_list.push_back(frame_id);
+        } else {
+            self.truncate_frames(&mut state);
+        }
+        self.deallocate_page(&mut state, page_id);
+        true
+    }
+
+    /// Return true if the page has been allocated and not deleted since, in this run or before
+    /// the database was reopened.
+    fn is_page_allocated(&self, page_id: PageId) -> bool {
+        let state = self.latch.lock().unwrap();
+        self.is_allocated(&state, page_id)
+    }
+
+    /// Create a `BufferAccessStrategy` of the given type, with its ring capped at an eighth of
+    /// the buffer pool.
+    fn get_access_strategy(
+        &self,
+        strategy_type: BufferAccessStrategyType,
+    ) -> BufferAccessStrategy {
+        let ring_size = strategy_type
+            .default_ring_size()
+            .min(self.get_pool_size() / 8)
+            .max(1);
+        BufferAccessStrategy::new(strategy_type, ring_size)
+    }
+
+    /// Return a snapshot of the dirty page table, mapping every dirty resident page to its
+    /// recovery LSN, i.e. its LSN right before it was first modified since it was last written
+    /// back. A checkpoint only needs to keep the log from the smallest of them onwards.
+    fn get_dirty_page_table(&self) -> HashMap<PageId, Lsn> {
+        let state = self.latch.lock().unwrap();
+        Self::dirty_pages(&state).collect()
+    }
+
+    /// Write back up to `max_pages` dirty pages that nobody has pinned, oldest recovery LSN
+    /// first, and return how many were written.
+    ///
+    /// This is what the `BackgroundWriter` calls so that foreground evictions rarely find a dirty
+    /// victim.
+    fn write_back_dirty_pages(&self, max_pages: usize) -> usize {
+        let candidates: Vec<(FrameId, Arc<Page>)> = {
+            let state = self.latch.lock().unwrap();
+            let mut dirty: Vec<(Lsn, PageId)> = Self::dirty_pages(&state)
+                .map(|(page_id, rec_lsn)| (rec_lsn, page_id))
+                .collect();
+            dirty.sort_unstable();
+            dirty
+                .into_iter()
+                .map(|(_, page_id)| state.page_table[&page_id])
+                .filter(|&frame_id| state.pages[frame_id as usize].get_pin_count() == 0)
+                .take(max_pages)
+                .map(|frame_id| self.pin_for_write_back(&state, frame_id))
+                .collect()
+        };
+        self.write_back_pinned(&candidates);
+        candidates.len()
+    }
+
+    /// Write the ids of the resident pages to `path`, in the order the replacer would evict them
+    /// (coldest first), so that a restarted buffer pool can be warmed up with
+    /// `restore_warm_pages`. Call it on clean shutdown, after the pages have been flushed.
+    ///
+    /// The file holds one little-endian `PageId` per resident page. Return the number of pages
+    /// written.
+    fn dump_warm_pages(&self, path: &str) -> io::Result<usize> {
+        let page_ids = self.get_warm_page_ids();
+        write_warm_page_ids(path, &page_ids)?;
+        Ok(page_ids.len())
+    }
+
+    /// Prefetch the pages listed in a file written by `dump_warm_pages` into free frames.
+    ///
+    /// All the reads are queued on the disk scheduler at once and the pages are loaded coldest
+    /// first, so the replacer ends up with the same ordering as before the restart. If the file
+    /// lists more pages than there are free frames, the coldest ones are skipped; pages that are
+    /// already resident or belong to another instance are ignored. The restored pages are left
+    /// unpinned. Return the number of pages loaded.
+    fn restore_warm_pages(&self, path: &str) -> io::Result<usize> {
+        Ok(self.prefetch_pages(&read_warm_page_ids(path)?))
+    }
+
+    /// Fetch a page wrapped in a `BasicPageGuard`, which unpins it when dropped.
+    #[track_caller]
+    fn fetch_page_basic(&self, page_id: PageId) -> Option<BasicPageGuard<'_>> {
+        self.fetch_page(page_id, AccessType::Unknown)
+            .map(|page| BasicPageGuard::new(self, page))
+    }
+
+    /// Create a new page wrapped in a `BasicPageGuard`.
+    #[track_caller]
+    fn new_page_guarded(&self) -> Option<BasicPageGuard<'_>> {
+        self.new_page().map(|page| BasicPageGuard::new(self, page))
+    }
+
+    /// Fetch a page through a strategy and take its read latch, wrapped in a `ReadPageGuard`.
+    #[track_caller]
+    fn fetch_page_read_with_strategy(
+        &self,
+        page_id: PageId,
+        strategy: &BufferAccessStrategy,
+    ) -> Option<ReadPageGuard<'_>> {
+        self.fetch_page_with_strategy(page_id, AccessType::Scan, strategy)
+            .map(|page| BasicPageGuard::new(self, page).upgrade_read())
+    }
+
+    /// Fetch a page through a strategy and take its write latch, wrapped in a `WritePageGuard`.
+    #[track_caller]
+    fn fetch_page_write_with_strategy(
+        &self,
+        page_id: PageId,
+        strategy: &BufferAccessStrategy,
+    ) -> Option<WritePageGuard<'_>> {
+        self.fetch_page_with_strategy(page_id, AccessType::Scan, strategy)
+            .map(|page| BasicPageGuard::new(self, page).upgrade_write())
+    }
+
+    /// Create a new page through a strategy, wrapped in a `BasicPageGuard`.
+    #[track_caller]
+    fn new_page_guarded_with_strategy(
+        &self,
+        strategy: &BufferAccessStrategy,
+    ) -> Option<BasicPageGuard<'_>> {
+        self.new_page_with_strategy(strategy)
+            .map(|page| BasicPageGuard::new(self, page))
+    }
+}
+
+/// Write a warm-up file: one little-endian `PageId` per page.
+pub(crate) fn write_warm_page_ids(path: &str, page_ids: &[PageId]) -> io::Result<()> {
+    let bytes: Vec<u8> = page_ids.iter().flat_map(|page_id| page_id.to_le_bytes()).collect();
+    std::fs::write(path, bytes)
+}
+
+/// Read a warm-up file written by `write_warm_page_ids`.
+pub(crate) fn read_warm_page_ids(path: &str) -> io::Result<Vec<PageId>> {
+    let bytes = std::fs::read(path)?;
+    if bytes.len() % std::mem::size_of::<PageId>() != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} is not a buffer pool dump", path),
+        ));
+    }
+    Ok(bytes
+        .chunks_exact(std::mem::size_of::<PageId>())
+        .map(|chunk| PageId::from_le_bytes(chunk.try_into().unwrap()))
+        .collect())
+}
+
+/// A victim pinned by an async fetch that waits for its latch or for the log with the buffer
+/// pool latch released. Unless the fetch takes the frame over, dropping it gives the victim back.
+struct PinnedVictim<'a> {
+    bpm: &'a BufferPoolManager,
+    frame_id: FrameId,
+    page: Option<Arc<Page>>,
+    is_latched: bool,
+}
+
+impl Drop for PinnedVictim<'_> {
+    fn drop(&mut self) {
+        let Some(page) = self.page.take() else {
+            return;
+        };
+        let mut state = self.bpm.latch.lock().unwrap();
+        if self.is_latched {
+            page.w_unlatch();
+        }
+        if page.unpin() == 0 {
+            self.bpm.release_frame(&mut state, self.frame_id);
+        }
+    }
+}
+
+impl Drop for BufferPoolManager {
+    fn drop(&mut self) {
+        if self.pin_tracking.load(Ordering::SeqCst) {
+            for record in self.get_outstanding_pins() {
+                log!("buffer pool shut down with a leaked pin: {}", record);
+            }
+        }
+    }
+}
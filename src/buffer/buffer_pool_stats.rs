@@ -0,0 +1,100 @@
+use std::fmt;
+use std::panic::Location;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread::ThreadId;
+
+use crate::utils::config::*;
+
+/// A snapshot of the counters of a buffer pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BufferPoolStats {
+    /// Fetches that found the page already resident.
+    pub hits: u64,
+    /// Fetches that had to read the page from disk.
+    pub misses: u64,
+    /// Frames taken away from a resident page to make room for another one.
+    pub evictions: u64,
+    /// Dirty pages written back to disk, whether on eviction, flush or by the background writer.
+    pub dirty_write_backs: u64,
+    /// Requests that could not get a frame because every frame was pinned.
+    pub pin_waits: u64,
+    /// Pages read from disk whose page header failed validation.
+    pub corrupt_reads: u64,
+}
+
+/// The live counters behind `BufferPoolStats`.
+#[derive(Debug, Default)]
+pub(crate) struct BufferPoolCounters {
+    pub hits: AtomicU64,
+    pub misses: AtomicU64,
+    pub evictions: AtomicU64,
+    pub dirty_write_backs: AtomicU64,
+    pub pin_waits: AtomicU64,
+    pub corrupt_reads: AtomicU64,
+}
+
+impl BufferPoolCounters {
+    pub fn incr(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> BufferPoolStats {
+        BufferPoolStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            dirty_write_backs: self.dirty_write_backs.load(Ordering::Relaxed),
+            pin_waits: self.pin_waits.load(Ordering::Relaxed),
+            corrupt_reads: self.corrupt_reads.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn reset(&self) {
+        for counter in [
+            &self.hits,
+            &self.misses,
+            &self.evictions,
+            &self.dirty_write_backs,
+            &self.pin_waits,
+            &self.corrupt_reads,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Where and by whom an outstanding pin was taken, recorded when pin tracking is enabled.
+#[derive(Debug, Clone)]
+pub struct PinRecord {
+    pub page_id: PageId,
+    /// The call site that fetched or created the page.
+    pub location: &'static Location<'static>,
+    pub thread_id: ThreadId,
+    pub thread_name: Option<String>,
+}
+
+impl PinRecord {
+    pub(crate) fn new(page_id: PageId, location: &'static Location<'static>) -> Self {
+        let thread = std::thread::current();
+        PinRecord {
+            page_id,
+            location,
+            thread_id: thread.id(),
+            thread_name: thread.name().map(str::to_string),
+        }
+    }
+}
+
+impl fmt::Display for PinRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "page {} pinned at {} by thread {:?}",
+            self.page_id, self.location, self.thread_id
+        )?;
+        if let Some(name) = &self.thread_name {
+            write!(f, " ({})", name)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,167 @@
+use std::sync::Mutex;
+
+use crate::utils::config::*;
+
+use super::replacer::{AccessType, Replacer, ReplacerStats};
+
+#[derive(Debug, Default, Clone, Copy)]
+struct ClockFrame {
+    /// Whether the frame is currently tracked by the replacer.
+    in_use: bool,
+    /// The page the frame held at its last access.
+    page_id: PageId,
+    /// Reference bit, set on every non-scan access and cleared as the hand sweeps past.
+    referenced: bool,
+    is_evictable: bool,
+}
+
+#[derive(Debug)]
+struct ClockReplacerInner {
+    frames: Vec<ClockFrame>,
+    hand: usize,
+    curr_size: usize,
+    stats: ReplacerStats,
+}
+
+/// ClockReplacer implements the clock (second chance) replacement policy.
+///
+/// Frames are arranged in a circle swept by a clock hand. An access only sets the frame's reference
+/// bit, so bookkeeping per access is O(1). When looking for a victim the hand clears the reference
+/// bit of every evictable frame it passes and evicts the first one whose bit is already clear.
+#[derive(Debug)]
+pub struct ClockReplacer {
+    inner: Mutex<ClockReplacerInner>,
+}
+
+impl ClockReplacer {
+    /// Create a new ClockReplacer tracking at most `num_frames` frames.
+    pub fn new(num_frames: usize) -> Self {
+        ClockReplacer {
+            inner: Mutex::new(ClockReplacerInner {
+                frames: vec![ClockFrame::default(); num_frames],
+                hand: 0,
+                curr_size: 0,
+                stats: ReplacerStats::default(),
+            }),
+        }
+    }
+}
+
+impl ClockReplacerInner {
+    fn frame_mut(&mut self, frame_id: FrameId) -> &mut ClockFrame {
+        assert!(
+            frame_id >= 0 && (frame_id as usize) < self.frames.len(),
+            "invalid frame id {}",
+            frame_id
+        );
+        &mut self.frames[frame_id as usize]
+    }
+}
+
+impl Replacer for ClockReplacer {
+    fn evict(&self) -> Option<FrameId> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.curr_size == 0 {
+            return None;
+        }
+        // Two full sweeps are enough: the first clears every reference bit.
+        let num_frames = inner.frames.len();
+        for _ in 0..2 * num_frames {
+            let hand = inner.hand;
+            inner.hand = (hand + 1) % num_frames;
+            let frame = &mut inner.frames[hand];
+            if !frame.in_use || !frame.is_evictable {
+                continue;
+            }
+            if frame.referenced {
+                frame.referenced = false;
+                continue;
+            }
+            *frame = ClockFrame::default();
+            inner.curr_size -= 1;
+            inner.stats.evictions += 1;
+            return Some(hand as FrameId);
+        }
+        None
+    }
+
+    fn record_access(&self, frame_id: FrameId, page_id: PageId, access_type: AccessType) {
+        let mut inner = self.inner.lock().unwrap();
+        let frame = inner.frame_mut(frame_id);
+        let is_hit = frame.in_use && frame.page_id == page_id;
+        if !is_hit {
+            frame.page_id = page_id;
+            frame.referenced = false;
+        }
+        frame.in_use = true;
+        // A scan does not earn the frame a second chance.
+        if access_type != AccessType::Scan {
+            frame.referenced = true;
+        }
+        if is_hit {
+            inner.stats.hits += 1;
+        } else {
+            inner.stats.misses += 1;
+        }
+    }
+
+    fn set_evictable(&self, frame_id: FrameId, set_evictable: bool) {
+        let mut inner = self.inner.lock().unwrap();
+        let frame = inner.frame_mut(frame_id);
+        if !frame.in_use || frame.is_evictable == set_evictable {
+            return;
+        }
+        frame.is_evictable = set_evictable;
+        if set_evictable {
+            inner.curr_size += 1;
+        } else {
+            inner.curr_size -= 1;
+        }
+    }
+
+    fn remove(&self, frame_id: FrameId) {
+        let mut inner = self.inner.lock().unwrap();
+        let frame = inner.frame_mut(frame_id);
+        if !frame.in_use {
+            return;
+        }
+        assert!(
+            frame.is_evictable,
+            "cannot remove non-evictable frame {}",
+            frame_id
+        );
+        *frame = ClockFrame::default();
+        inner.curr_size -= 1;
+    }
+
+    fn size(&self) -> usize {
+        self.inner.lock().unwrap().curr_size
+    }
+
+    fn eviction_order(&self) -> Vec<FrameId> {
+        let inner = self.inner.lock().unwrap();
+        let num_frames = inner.frames.len();
+        // Starting at the hand, unreferenced frames go on the first sweep, the others on the next.
+        let sweep = (0..num_frames).map(|i| (inner.hand + i) % num_frames);
+        let (first, second): (Vec<usize>, Vec<usize>) = sweep
+            .filter(|&i| inner.frames[i].in_use)
+            .partition(|&i| !inner.frames[i].referenced);
+        first
+            .into_iter()
+            .chain(second)
+            .map(|i| i as FrameId)
+            .collect()
+    }
+
+    fn resize(&self, num_frames: usize) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.frames.resize(num_frames, ClockFrame::default());
+        if inner.hand >= num_frames {
+            inner.hand = 0;
+        }
+    }
+
+    fn stats(&self) -> ReplacerStats {
+        self.inner.lock().unwrap().stats
+    }
+}
@@ -0,0 +1,77 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::utils::config::*;
+
+/// GhostList is a FIFO of recently evicted page ids, used by the ARC and 2Q replacers to remember
+/// pages that are no longer resident.
+///
+/// Membership lookups and removals go through a hash index, so they are O(1). A removed page id
+/// stays in the queue until it reaches the back or the queue is compacted; the sequence number it
+/// was pushed with tells live entries from stale ones.
+#[derive(Debug, Default, Clone)]
+pub struct GhostList {
+    /// Page ids with the sequence number they were pushed with, newest at the front.
+    queue: VecDeque<(PageId, u64)>,
+    index: HashMap<PageId, u64>,
+    next_seq: u64,
+}
+
+impl GhostList {
+    /// Return the number of page ids in the list.
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    pub fn contains(&self, page_id: PageId) -> bool {
+        self.index.contains_key(&page_id)
+    }
+
+    /// Remember `page_id` as the most recently evicted page. A page already in the list moves to
+    /// the front.
+    pub fn push_front(&mut self, page_id: PageId) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.index.insert(page_id, seq);
+        self.queue.push_front((page_id, seq));
+        self.compact();
+    }
+
+    /// Forget `page_id`, returning whether it was in the list.
+    pub fn remove(&mut self, page_id: PageId) -> bool {
+        let removed = self.index.remove(&page_id).is_some();
+        self.compact();
+        removed
+    }
+
+    /// Forget the least recently evicted page id.
+    pub fn pop_back(&mut self) -> Option<PageId> {
+        while let Some((page_id, seq)) = self.queue.pop_back() {
+            if self.index.get(&page_id) == Some(&seq) {
+                self.index.remove(&page_id);
+                return Some(page_id);
+            }
+        }
+        None
+    }
+
+    /// Forget the oldest page ids until at most `len` are left.
+    pub fn truncate(&mut self, len: usize) {
+        while self.len() > len {
+            self.pop_back();
+        }
+    }
+
+    /// Drop stale entries once they make up more than half of the queue, which keeps removals
+    /// amortized O(1).
+    fn compact(&mut self) {
+        if self.queue.len() > 2 * self.index.len() + 1 {
+            let index = &self.index;
+            self.queue
+                .retain(|(page_id, seq)| index.get(page_id) == Some(seq));
+        }
+    }
+}
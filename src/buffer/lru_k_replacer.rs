@@ -1,22 +1,150 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
 use crate::utils::config::*;
-struct lru_k_replacer {
-	
+
+use super::replacer::{AccessType, Replacer, ReplacerStats};
+
+#[derive(Debug, Default)]
+struct LRUKNode {
+    /// The page the frame held at its last access.
+    page_id: Option<PageId>,
+    /// Timestamps of the last (at most) K accesses, oldest first.
+    history: VecDeque<usize>,
+    is_evictable: bool,
+}
+
+#[derive(Debug, Default)]
+struct LRUKReplacerInner {
+    node_store: HashMap<FrameId, LRUKNode>,
+    current_timestamp: usize,
+    curr_size: usize,
+    stats: ReplacerStats,
+}
+
+/// LRUKReplacer implements the LRU-k replacement policy.
+///
+/// The LRU-k algorithm evicts a frame whose backward k-distance is maximum of all frames. Backward
+/// k-distance is computed as the difference in time between current timestamp and the timestamp of
+/// kth previous access.
+///
+/// A frame with less than k historical references is given +inf as its backward k-distance. When
+/// multiple frames have +inf backward k-distance, classical LRU is used to choose the victim.
+#[derive(Debug)]
+pub struct LRUKReplacer {
+    inner: Mutex<LRUKReplacerInner>,
+    replacer_size: AtomicUsize,
+    k: usize,
 }
 
-impl lru_k_replacer {
-    fn Evit(frame_id: FrameId) -> bool {
-        return true;
+impl LRUKReplacer {
+    /// Create a new LRUKReplacer tracking at most `num_frames` frames.
+    pub fn new(num_frames: usize, k: usize) -> Self {
+        assert!(k > 0, "k must be positive");
+        LRUKReplacer {
+            inner: Mutex::new(LRUKReplacerInner::default()),
+            replacer_size: AtomicUsize::new(num_frames),
+            k,
+        }
     }
-    fn RecordAccess(frame_id: FrameId) {
-        return;
+
+    fn check_frame_id(&self, frame_id: FrameId) {
+        assert!(
+            frame_id >= 0 && (frame_id as usize) < self.replacer_size.load(Ordering::Relaxed),
+            "invalid frame id {}",
+            frame_id
+        );
     }
-    fn Remove(frame_id: FrameId) {
-        return;
+}
+
+impl Replacer for LRUKReplacer {
+    fn evict(&self) -> Option<FrameId> {
+        let mut inner = self.inner.lock().unwrap();
+        // Frames with fewer than k accesses sort first; within a class the oldest tracked
+        // timestamp (first access, or kth previous access) has the largest distance.
+        let victim = inner
+            .node_store
+            .iter()
+            .filter(|(_, node)| node.is_evictable)
+            .min_by_key(|(_, node)| (node.history.len() >= self.k, node.history.front().copied()))
+            .map(|(frame_id, _)| *frame_id)?;
+        inner.node_store.remove(&victim);
+        inner.curr_size -= 1;
+        inner.stats.evictions += 1;
+        Some(victim)
     }
-    fn SetEvictable(frame_id: FrameId) {
-        return;
+
+    fn record_access(&self, frame_id: FrameId, page_id: PageId, access_type: AccessType) {
+        self.check_frame_id(frame_id);
+        let mut inner = self.inner.lock().unwrap();
+        let timestamp = inner.current_timestamp;
+        inner.current_timestamp += 1;
+        let node = inner.node_store.entry(frame_id).or_default();
+        let is_hit = node.page_id == Some(page_id);
+        if !is_hit {
+            // The history belongs to the page, not to the frame.
+            node.page_id = Some(page_id);
+            node.history.clear();
+        }
+        // A scan only starts tracking a page; it never adds to an existing history, so scanned
+        // pages keep their backward k-distance.
+        if access_type != AccessType::Scan || node.history.is_empty() {
+            node.history.push_back(timestamp);
+            if node.history.len() > self.k {
+                node.history.pop_front();
+            }
+        }
+        if is_hit {
+            inner.stats.hits += 1;
+        } else {
+            inner.stats.misses += 1;
+        }
     }
-    fn Size() -> usize {
-        return 0;
+
+    fn set_evictable(&self, frame_id: FrameId, set_evictable: bool) {
+        self.check_frame_id(frame_id);
+        let mut inner = self.inner.lock().unwrap();
+        let Some(node) = inner.node_store.get_mut(&frame_id) else {
+            return;
+        };
+        if node.is_evictable == set_evictable {
+            return;
+        }
+        node.is_evictable = set_evictable;
+        if set_evictable {
+            inner.curr_size += 1;
+        } else {
+            inner.curr_size -= 1;
+        }
     }
-}
\ No newline at end of file
+
+    fn remove(&self, frame_id: FrameId) {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(node) = inner.node_store.get(&frame_id) else {
+            return;
+        };
+        assert!(node.is_evictable, "cannot remove non-evictable frame {}", frame_id);
+        inner.node_store.remove(&frame_id);
+        inner.curr_size -= 1;
+    }
+
+    fn size(&self) -> usize {
+        self.inner.lock().unwrap().curr_size
+    }
+
+    fn eviction_order(&self) -> Vec<FrameId> {
+        let inner = self.inner.lock().unwrap();
+        let mut frames: Vec<(&FrameId, &LRUKNode)> = inner.node_store.iter().collect();
+        frames.sort_by_key(|(_, node)| (node.history.len() >= self.k, node.history.front().copied()));
+        frames.into_iter().map(|(&frame_id, _)| frame_id).collect()
+    }
+
+    fn resize(&self, num_frames: usize) {
+        self.replacer_size.store(num_frames, Ordering::Relaxed);
+    }
+
+    fn stats(&self) -> ReplacerStats {
+        self.inner.lock().unwrap().stats
+    }
+}
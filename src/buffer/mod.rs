@@ -1 +1,12 @@
-pub mod lru_k_replacer;
\ No newline at end of file
+pub mod arc_replacer;
+pub mod background_writer;
+pub mod buffer_access_strategy;
+pub mod buffer_pool;
+pub mod buffer_pool_manager;
+pub mod buffer_pool_stats;
+pub mod clock_replacer;
+pub mod ghost_list;
+pub mod lru_k_replacer;
+pub mod parallel_buffer_pool_manager;
+pub mod replacer;
+pub mod two_queue_replacer;
@@ -0,0 +1,348 @@
+use std::collections::HashMap;
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::recovery::log_manager::LogManager;
+use crate::storage::disk::disk_manager::DiskManager;
+use crate::storage::page::page::Page;
+use crate::storage::page::page_guard::{BasicPageGuard, ReadPageGuard, WritePageGuard};
+use crate::utils::config::*;
+
+use super::buffer_access_strategy::{BufferAccessStrategy, BufferAccessStrategyType};
+use super::buffer_pool::BufferPool;
+use super::buffer_pool_manager::{read_warm_page_ids, write_warm_page_ids, BufferPoolManager};
+use super::buffer_pool_stats::{BufferPoolStats, PinRecord};
+use super::replacer::{AccessType, ReplacerPolicy};
+
+/// ParallelBufferPoolManager shards frames across several independent `BufferPoolManager`
+/// instances, each with its own latch, page table and replacer. A page always lives in the
+/// instance `page_id % num_instances`, and new pages are allocated from the instances in
+/// round-robin order.
+pub struct ParallelBufferPoolManager {
+    instances: Vec<BufferPoolManager>,
+    /// Instance the next `new_page` call starts looking at.
+    start_index: AtomicUsize,
+}
+
+impl ParallelBufferPoolManager {
+    /// Creates `num_instances` instances of `pool_size` frames each, using LRU-K.
+    pub fn new(
+        num_instances: usize,
+        pool_size: usize,
+        disk_manager: Arc<Mutex<DiskManager>>,
+        replacer_k: usize,
+    ) -> Self {
+        Self::new_with_policy(
+            num_instances,
+            pool_size,
+            disk_manager,
+            ReplacerPolicy::LruK(replacer_k),
+        )
+    }
+
+    /// Creates `num_instances` instances of `pool_size` frames each, using the given policy.
+    pub fn new_with_policy(
+        num_instances: usize,
+        pool_size: usize,
+        disk_manager: Arc<Mutex<DiskManager>>,
+        policy: ReplacerPolicy,
+    ) -> Self {
+        let instances = (0..num_instances)
+            .map(|instance_index| {
+                BufferPoolManager::new_instance(
+                    pool_size,
+                    num_instances,
+                    instance_index,
+                    disk_manager.clone(),
+                    policy,
+                )
+            })
+            .collect();
+        ParallelBufferPoolManager {
+            instances,
+            start_index: AtomicUsize::new(0),
+        }
+    }
+
+    /// Return the number of instances.
+    pub fn get_num_instances(&self) -> usize {
+        self.instances.len()
+    }
+
+    /// Return the instance responsible for the given page.
+    pub fn get_buffer_pool_manager(&self, page_id: PageId) -> &BufferPoolManager {
+        &self.instances[page_id as usize % self.instances.len()]
+    }
+
+    /// Fetch a page and take its read latch from an async task, see
+    /// `BufferPoolManager::fetch_page_read_async`.
+    pub async fn fetch_page_read_async(&self, page_id: PageId) -> Option<ReadPageGuard<'_>> {
+        if page_id == INVALID_PAGE_ID {
+            return None;
+        }
+        self.get_buffer_pool_manager(page_id)
+            .fetch_page_read_async(page_id)
+            .await
+    }
+
+    /// Fetch a page and take its write latch from an async task, see
+    /// `BufferPoolManager::fetch_page_write_async`.
+    pub async fn fetch_page_write_async(&self, page_id: PageId) -> Option<WritePageGuard<'_>> {
+        if page_id == INVALID_PAGE_ID {
+            return None;
+        }
+        self.get_buffer_pool_manager(page_id)
+            .fetch_page_write_async(page_id)
+            .await
+    }
+
+    /// Iterate over all instances, starting at the one whose turn it is to allocate.
+    fn next_instances(&self) -> impl Iterator<Item = &BufferPoolManager> {
+        let num_instances = self.instances.len();
+        let start = self.start_index.fetch_add(1, Ordering::SeqCst) % num_instances;
+        (0..num_instances).map(move |i| &self.instances[(start + i) % num_instances])
+    }
+}
+
+impl BufferPool for ParallelBufferPoolManager {
+    /// Return the total number of frames across all instances.
+    fn get_pool_size(&self) -> usize {
+        self.instances.iter().map(BufferPoolManager::get_pool_size).sum()
+    }
+
+    /// Resize every instance to `pool_size` frames, see `BufferPoolManager::resize`.
+    fn resize(&self, pool_size: usize) {
+        for bpm in &self.instances {
+            bpm.resize(pool_size);
+        }
+    }
+
+    /// Attach the log manager to every instance.
+    fn set_log_manager(&self, log_manager: Arc<dyn LogManager>) {
+        for bpm in &self.instances {
+            bpm.set_log_manager(log_manager.clone());
+        }
+    }
+
+    /// Return the counters of all instances added together.
+    fn get_stats(&self) -> BufferPoolStats {
+        self.instances
+            .iter()
+            .map(BufferPoolManager::get_stats)
+            .fold(BufferPoolStats::default(), |total, stats| BufferPoolStats {
+                hits: total.hits + stats.hits,
+                misses: total.misses + stats.misses,
+                evictions: total.evictions + stats.evictions,
+                dirty_write_backs: total.dirty_write_backs + stats.dirty_write_backs,
+                pin_waits: total.pin_waits + stats.pin_waits,
+                corrupt_reads: total.corrupt_reads + stats.corrupt_reads,
+            })
+    }
+
+    /// Reset the counters of every instance.
+    fn reset_stats(&self) {
+        for bpm in &self.instances {
+            bpm.reset_stats();
+        }
+    }
+
+    /// Enable or disable pin tracking in every instance.
+    fn set_pin_tracking(&self, enabled: bool) {
+        for bpm in &self.instances {
+            bpm.set_pin_tracking(enabled);
+        }
+    }
+
+    /// Return the outstanding pins of every instance.
+    fn get_outstanding_pins(&self) -> Vec<PinRecord> {
+        self.instances
+            .iter()
+            .flat_map(BufferPoolManager::get_outstanding_pins)
+            .collect()
+    }
+
+    /// Create a new page in one of the instances. Instances are tried in round-robin order
+    /// starting from a different one on every call, until one has a frame available.
+    #[track_caller]
+    fn new_page(&self) -> Option<Arc<Page>> {
+        for bpm in self.next_instances() {
+            if let Some(page) = bpm.new_page() {
+                return Some(page);
+            }
+        }
+        None
+    }
+
+    /// Create a new page in one of the instances, through a bulk operation's strategy.
+    #[track_caller]
+    fn new_page_with_strategy(&self, strategy: &BufferAccessStrategy) -> Option<Arc<Page>> {
+        for bpm in self.next_instances() {
+            if let Some(page) = bpm.new_page_with_strategy(strategy) {
+                return Some(page);
+            }
+        }
+        None
+    }
+
+    /// Fetch the requested page from the instance responsible for it.
+    #[track_caller]
+    fn fetch_page(&self, page_id: PageId, access_type: AccessType) -> Option<Arc<Page>> {
+        if page_id == INVALID_PAGE_ID {
+            return None;
+        }
+        self.get_buffer_pool_manager(page_id)
+            .fetch_page(page_id, access_type)
+    }
+
+    /// Fetch the requested page from the instance responsible for it, through a strategy.
+    #[track_caller]
+    fn fetch_page_with_strategy(
+        &self,
+        page_id: PageId,
+        access_type: AccessType,
+        strategy: &BufferAccessStrategy,
+    ) -> Option<Arc<Page>> {
+        if page_id == INVALID_PAGE_ID {
+            return None;
+        }
+        self.get_buffer_pool_manager(page_id)
+            .fetch_page_with_strategy(page_id, access_type, strategy)
+    }
+
+    /// Unpin the target page in the instance responsible for it.
+    fn unpin_page(&self, page_id: PageId, is_dirty: bool) -> bool {
+        self.get_buffer_pool_manager(page_id)
+            .unpin_page(page_id, is_dirty)
+    }
+
+    /// Flush the target page in the instance responsible for it.
+    fn flush_page(&self, page_id: PageId) -> bool {
+        self.get_buffer_pool_manager(page_id).flush_page(page_id)
+    }
+
+    /// Flush all the pages of every instance.
+    fn flush_all_pages(&self) {
+        for bpm in &self.instances {
+            bpm.flush_all_pages();
+        }
+    }
+
+    /// Delete the page from the instance responsible for it.
+    fn delete_page(&self, page_id: PageId) -> bool {
+        self.get_buffer_pool_manager(page_id).delete_page(page_id)
+    }
+
+    fn is_page_allocated(&self, page_id: PageId) -> bool {
+        page_id >= 0 && self.get_buffer_pool_manager(page_id).is_page_allocated(page_id)
+    }
+
+    /// Create a `BufferAccessStrategy` sized for a single instance. Ring frames belong to the
+    /// instance that loaded them, so a ring is only recycled for pages of the same instance.
+    fn get_access_strategy(&self, strategy_type: BufferAccessStrategyType) -> BufferAccessStrategy {
+        self.instances[0].get_access_strategy(strategy_type)
+    }
+
+    /// Return the dirty page tables of all instances merged together.
+    fn get_dirty_page_table(&self) -> HashMap<PageId, Lsn> {
+        self.instances
+            .iter()
+            .flat_map(BufferPoolManager::get_dirty_page_table)
+            .collect()
+    }
+
+    /// Write back up to `max_pages` dirty pages, taking them from the instances in round-robin
+    /// order so that no instance is starved.
+    fn write_back_dirty_pages(&self, max_pages: usize) -> usize {
+        let mut num_written = 0;
+        for bpm in self.next_instances() {
+            num_written += bpm.write_back_dirty_pages(max_pages - num_written);
+        }
+        num_written
+    }
+
+    /// Write the resident pages of every instance to `path`, see
+    /// `BufferPoolManager::dump_warm_pages`. Return the number of pages written.
+    fn dump_warm_pages(&self, path: &str) -> io::Result<usize> {
+        let page_ids: Vec<PageId> = self
+            .instances
+            .iter()
+            .flat_map(BufferPoolManager::get_warm_page_ids)
+            .collect();
+        write_warm_page_ids(path, &page_ids)?;
+        Ok(page_ids.len())
+    }
+
+    /// Prefetch the pages listed in a file written by `dump_warm_pages`, each into the instance
+    /// responsible for it. Return the number of pages loaded.
+    fn restore_warm_pages(&self, path: &str) -> io::Result<usize> {
+        let page_ids = read_warm_page_ids(path)?;
+        Ok(self.instances.iter().map(|bpm| bpm.prefetch_pages(&page_ids)).sum())
+    }
+
+    /// Fetch a page wrapped in a `BasicPageGuard`.
+    #[track_caller]
+    fn fetch_page_basic(&self, page_id: PageId) -> Option<BasicPageGuard<'_>> {
+        if page_id == INVALID_PAGE_ID {
+            return None;
+        }
+        self.get_buffer_pool_manager(page_id)
+            .fetch_page_basic(page_id)
+    }
+
+    /// Create a new page wrapped in a `BasicPageGuard`.
+    #[track_caller]
+    fn new_page_guarded(&self) -> Option<BasicPageGuard<'_>> {
+        for bpm in self.next_instances() {
+            if let Some(guard) = bpm.new_page_guarded() {
+                return Some(guard);
+            }
+        }
+        None
+    }
+
+    /// Fetch a page through a strategy from the instance responsible for it and take its read
+    /// latch.
+    #[track_caller]
+    fn fetch_page_read_with_strategy(
+        &self,
+        page_id: PageId,
+        strategy: &BufferAccessStrategy,
+    ) -> Option<ReadPageGuard<'_>> {
+        if page_id == INVALID_PAGE_ID {
+            return None;
+        }
+        self.get_buffer_pool_manager(page_id)
+            .fetch_page_read_with_strategy(page_id, strategy)
+    }
+
+    /// Fetch a page through a strategy from the instance responsible for it and take its write
+    /// latch.
+    #[track_caller]
+    fn fetch_page_write_with_strategy(
+        &self,
+        page_id: PageId,
+        strategy: &BufferAccessStrategy,
+    ) -> Option<WritePageGuard<'_>> {
+        if page_id == INVALID_PAGE_ID {
+            return None;
+        }
+        self.get_buffer_pool_manager(page_id)
+            .fetch_page_write_with_strategy(page_id, strategy)
+    }
+
+    /// Create a new page through a strategy in one of the instances, wrapped in a
+    /// `BasicPageGuard`.
+    #[track_caller]
+    fn new_page_guarded_with_strategy(
+        &self,
+        strategy: &BufferAccessStrategy,
+    ) -> Option<BasicPageGuard<'_>> {
+        for bpm in self.next_instances() {
+            if let Some(guard) = bpm.new_page_guarded_with_strategy(strategy) {
+                return Some(guard);
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,93 @@
+use crate::utils::config::*;
+
+use super::arc_replacer::ARCReplacer;
+use super::clock_replacer::ClockReplacer;
+use super::lru_k_replacer::LRUKReplacer;
+use super::two_queue_replacer::TwoQueueReplacer;
+
+/// The kind of access that caused a frame to be touched. Every replacer treats `Scan` as a hint
+/// that the page is read once by a sequential scan: the access is recorded, but it never makes an
+/// already tracked page hotter. The other kinds are treated as regular accesses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AccessType {
+    #[default]
+    Unknown,
+    Lookup,
+    Scan,
+    Index,
+}
+
+/// Counters kept by every replacer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ReplacerStats {
+    /// Accesses to a page that was already resident in the frame.
+    pub hits: u64,
+    /// Accesses that brought a new page into the frame.
+    pub misses: u64,
+    /// Frames picked as victims by `evict`.
+    pub evictions: u64,
+}
+
+/// Replacer tracks page usage of the frames in the buffer pool and picks a victim frame when the
+/// pool is full. Implementations are internally synchronized so the buffer pool can share them.
+pub trait Replacer: Send + Sync {
+    /// Find the frame to evict according to the replacement policy. Only frames that are marked as
+    /// evictable are candidates. On success the frame is no longer tracked by the replacer.
+    fn evict(&self) -> Option<FrameId>;
+
+    /// Record that the given frame, currently holding `page_id`, was accessed at the current
+    /// point in time.
+    fn record_access(&self, frame_id: FrameId, page_id: PageId, access_type: AccessType);
+
+    /// Toggle whether a frame is evictable or not. This also controls the replacer's size.
+    fn set_evictable(&self, frame_id: FrameId, set_evictable: bool);
+
+    /// Remove an evictable frame from the replacer, along with its access history.
+    fn remove(&self, frame_id: FrameId);
+
+    /// Return the number of evictable frames.
+    fn size(&self) -> usize;
+
+    /// Return every tracked frame, evictable or not, in the exact order successive calls to
+    /// `evict` would pick them if they were all evictable and no access came in between: coldest
+    /// first, hottest last.
+    fn eviction_order(&self) -> Vec<FrameId>;
+
+    /// Change the number of frames the replacer can track, when the buffer pool is resized.
+    /// Frames beyond the new size must already have been removed.
+    fn resize(&self, num_frames: usize);
+
+    /// Return the hit, miss and eviction counters.
+    fn stats(&self) -> ReplacerStats;
+}
+
+/// The replacement policy used by a buffer pool, chosen when the pool is constructed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplacerPolicy {
+    /// LRU-K with the given K.
+    LruK(usize),
+    /// Clock (second chance).
+    Clock,
+    /// Adaptive Replacement Cache.
+    Adaptive,
+    /// 2Q with the default A1in / A1out sizes.
+    TwoQueue,
+}
+
+impl ReplacerPolicy {
+    /// Build a replacer of this policy able to track `num_frames` frames.
+    pub fn build(self, num_frames: usize) -> Box<dyn Replacer> {
+        match self {
+            ReplacerPolicy::LruK(k) => Box::new(LRUKReplacer::new(num_frames, k)),
+            ReplacerPolicy::Clock => Box::new(ClockReplacer::new(num_frames)),
+            ReplacerPolicy::Adaptive => Box::new(ARCReplacer::new(num_frames)),
+            ReplacerPolicy::TwoQueue => Box::new(TwoQueueReplacer::new(num_frames)),
+        }
+    }
+}
+
+impl Default for ReplacerPolicy {
+    fn default() -> Self {
+        ReplacerPolicy::LruK(LRUK_REPLACER_K)
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use crate::utils::config::*;
+
+use super::ghost_list::GhostList;
+use super::replacer::{AccessType, Replacer, ReplacerStats};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TwoQueueList {
+    /// A1in: FIFO of resident pages referenced once.
+    A1In,
+    /// Am: LRU of resident pages referenced again after leaving A1in.
+    Am,
+}
+
+#[derive(Debug, Clone)]
+struct TwoQueueEntry {
+    page_id: PageId,
+    list: TwoQueueList,
+    is_evictable: bool,
+}
+
+#[derive(Debug, Default, Clone)]
+struct TwoQueueReplacerInner {
+    /// Resident frames, newest at the front.
+    a1_in: VecDeque<FrameId>,
+    am: VecDeque<FrameId>,
+    /// Ghost FIFO of page ids evicted from A1in, newest at the front.
+    a1_out: GhostList,
+    entries: HashMap<FrameId, TwoQueueEntry>,
+    curr_size: usize,
+    stats: ReplacerStats,
+}
+
+/// TwoQueueReplacer implements the full 2Q replacement policy (Johnson & Shasha).
+///
+/// A page referenced for the first time enters the A1in FIFO. Re-references while it sits in
+/// A1in do not promote it, so a one-off scan drains through A1in without disturbing the hot set.
+/// Pages evicted from A1in are remembered in the A1out ghost queue; only a page re-referenced
+/// while in A1out is admitted to the LRU-managed Am queue.
+#[derive(Debug)]
+pub struct TwoQueueReplacer {
+    inner: Mutex<TwoQueueReplacerInner>,
+    replacer_size: AtomicUsize,
+    /// Target size of A1in (Kin).
+    a1_in_size: AtomicUsize,
+    /// Maximum size of A1out (Kout).
+    a1_out_size: AtomicUsize,
+}
+
+impl TwoQueueReplacer {
+    /// Create a new TwoQueueReplacer tracking at most `num_frames` frames, with the tuning
+    /// recommended by the paper: Kin = 25% and Kout = 50% of the buffer pool.
+    pub fn new(num_frames: usize) -> Self {
+        Self::new_with_sizes(num_frames, (num_frames / 4).max(1), (num_frames / 2).max(1))
+    }
+
+    /// Create a new TwoQueueReplacer with explicit Kin / Kout sizes.
+    pub fn new_with_sizes(num_frames: usize, a1_in_size: usize, a1_out_size: usize) -> Self {
+        TwoQueueReplacer {
+            inner: Mutex::new(TwoQueueReplacerInner::default()),
+            replacer_size: AtomicUsize::new(num_frames),
+            a1_in_size: AtomicUsize::new(a1_in_size),
+            a1_out_size: AtomicUsize::new(a1_out_size),
+        }
+    }
+}
+
+impl TwoQueueReplacerInner {
+    fn list_mut(&mut self, list: TwoQueueList) -> &mut VecDeque<FrameId> {
+        match list {
+            TwoQueueList::A1In => &mut self.a1_in,
+            TwoQueueList::Am => &mut self.am,
+        }
+    }
+
+    /// Evict the oldest evictable frame of the given queue.
+    fn evict_from(&mut self, list: TwoQueueList) -> Option<(FrameId, PageId)> {
+        let frames = match list {
+            TwoQueueList::A1In => &self.a1_in,
+            TwoQueueList::Am => &self.am,
+        };
+        let pos = frames
+            .iter()
+            .rposition(|frame_id| self.entries[frame_id].is_evictable)?;
+        let frame_id = self.list_mut(list).remove(pos).unwrap();
+        let entry = self.entries.remove(&frame_id).unwrap();
+        self.curr_size -= 1;
+        Some((frame_id, entry.page_id))
+    }
+
+    /// Evict a frame: A1in gives one up while it is above `a1_in_size` or Am is empty, Am
+    /// otherwise. Pages evicted from A1in are remembered in A1out, which keeps at most
+    /// `a1_out_size` of them.
+    fn evict(&mut self, a1_in_size: usize, a1_out_size: usize) -> Option<FrameId> {
+        if self.curr_size == 0 {
+            return None;
+        }
+        if self.a1_in.len() <= a1_in_size && !self.am.is_empty() {
+            if let Some((frame_id, _)) = self.evict_from(TwoQueueList::Am) {
+                self.stats.evictions += 1;
+                return Some(frame_id);
+            }
+        }
+        let victim = match self.evict_from(TwoQueueList::A1In) {
+            Some((frame_id, page_id)) => {
+                self.a1_out.push_front(page_id);
+                self.a1_out.truncate(a1_out_size);
+                frame_id
+            }
+            None => self.evict_from(TwoQueueList::Am)?.0,
+        };
+        self.stats.evictions += 1;
+        Some(victim)
+    }
+
+    /// Drop whatever the frame currently holds, as its page is being replaced.
+    fn forget_frame(&mut self, frame_id: FrameId) {
+        if let Some(entry) = self.entries.remove(&frame_id) {
+            let list = self.list_mut(entry.list);
+            if let Some(pos) = list.iter().position(|&f| f == frame_id) {
+                list.remove(pos);
+            }
+            if entry.is_evictable {
+                self.curr_size -= 1;
+            }
+        }
+    }
+}
+
+impl Replacer for TwoQueueReplacer {
+    fn evict(&self) -> Option<FrameId> {
+        self.inner.lock().unwrap().evict(
+            self.a1_in_size.load(Ordering::Relaxed),
+            self.a1_out_size.load(Ordering::Relaxed),
+        )
+    }
+
+    fn record_access(&self, frame_id: FrameId, page_id: PageId, access_type: AccessType) {
+        assert!(
+            frame_id >= 0 && (frame_id as usize) < self.replacer_size.load(Ordering::Relaxed),
+            "invalid frame id {}",
+            frame_id
+        );
+        let mut inner = self.inner.lock().unwrap();
+        let inner = &mut *inner;
+
+        if let Some(entry) = inner.entries.get(&frame_id) {
+            if entry.page_id == page_id {
+                // Hits in Am refresh the LRU position unless they come from a scan; hits in A1in
+                // are deliberately ignored.
+                if entry.list == TwoQueueList::Am && access_type != AccessType::Scan {
+                    let pos = inner.am.iter().position(|&f| f == frame_id).unwrap();
+                    inner.am.remove(pos);
+                    inner.am.push_front(frame_id);
+                }
+                inner.stats.hits += 1;
+                return;
+            }
+        }
+        inner.forget_frame(frame_id);
+        inner.stats.misses += 1;
+
+        // A scan coming back to a page in A1out does not prove it is hot, so it re-enters A1in.
+        let list = if inner.a1_out.remove(page_id) && access_type != AccessType::Scan {
+            TwoQueueList::Am
+        } else {
+            TwoQueueList::A1In
+        };
+        inner.list_mut(list).push_front(frame_id);
+        inner.entries.insert(
+            frame_id,
+            TwoQueueEntry {
+                page_id,
+                list,
+                is_evictable: false,
+            },
+        );
+    }
+
+    fn set_evictable(&self, frame_id: FrameId, set_evictable: bool) {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(entry) = inner.entries.get_mut(&frame_id) else {
+            return;
+        };
+        if entry.is_evictable == set_evictable {
+            return;
+        }
+        entry.is_evictable = set_evictable;
+        if set_evictable {
+            inner.curr_size += 1;
+        } else {
+            inner.curr_size -= 1;
+        }
+    }
+
+    fn remove(&self, frame_id: FrameId) {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(entry) = inner.entries.get(&frame_id) else {
+            return;
+        };
+        assert!(
+            entry.is_evictable,
+            "cannot remove non-evictable frame {}",
+            frame_id
+        );
+        inner.forget_frame(frame_id);
+    }
+
+    fn size(&self) -> usize {
+        self.inner.lock().unwrap().curr_size
+    }
+
+    fn stats(&self) -> ReplacerStats {
+        self.inner.lock().unwrap().stats
+    }
+
+    fn eviction_order(&self) -> Vec<FrameId> {
+        // Replay the policy on a copy where every frame is evictable: which queue gives up the
+        // next victim depends on how many frames A1in has left.
+        let mut inner = self.inner.lock().unwrap().clone();
+        for entry in inner.entries.values_mut() {
+            entry.is_evictable = true;
+        }
+        inner.curr_size = inner.entries.len();
+        let a1_in_size = self.a1_in_size.load(Ordering::Relaxed);
+        let a1_out_size = self.a1_out_size.load(Ordering::Relaxed);
+        std::iter::from_fn(|| inner.evict(a1_in_size, a1_out_size)).collect()
+    }
+
+    fn resize(&self, num_frames: usize) {
+        // Keep Kin and Kout at the same fraction of the buffer pool.
+        let _inner = self.inner.lock().unwrap();
+        let old_size = self
+            .replacer_size
+            .swap(num_frames, Ordering::Relaxed)
+            .max(1);
+        for size in [&self.a1_in_size, &self.a1_out_size] {
+            let scaled = size.load(Ordering::Relaxed) * num_frames / old_size;
+            size.store(scaled.max(1), Ordering::Relaxed);
+        }
+    }
+}
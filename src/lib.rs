@@ -1,3 +1,5 @@
+pub mod buffer;
 pub mod primer; 
+pub mod recovery;
 pub mod utils;
 pub mod storage;
\ No newline at end of file
@@ -1,26 +1,35 @@
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 
 use crate::primer::trie::Trie;
 
 use super::trie::TrieFn;
 
-/// ValueGuard wraps the Trie and a reference to the value, allowing access to the value.
+/// ValueGuard holds a value of a TrieStore together with the version of the Trie it was read from,
+/// so the value stays valid and unchanged whatever is written to the store afterwards.
 #[derive(Debug)]
-pub struct ValueGuard<T: Default> {
+pub struct ValueGuard<T> {
     root: Trie<T>,
-    value: Arc<RwLock<T>>,
+    value: Arc<T>,
+}
+
+impl<T: Default> Default for ValueGuard<T> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<T: Default> ValueGuard<T> {
     pub fn new() -> Self {
         ValueGuard {
             root: Trie::<T>::new(),
-            value: Arc::new(RwLock::new(T::default())),
+            value: Arc::new(T::default()),
         }
     }
+}
 
-    pub fn get_value(&self) -> Arc<RwLock<T>> {
-        self.value.clone()
+impl<T> ValueGuard<T> {
+    pub fn get_value(&self) -> &T {
+        &self.value
     }
 
     pub fn get_root(&self) -> Trie<T> {
@@ -28,51 +37,61 @@ impl<T: Default> ValueGuard<T> {
     }
 }
 
-/// TrieStore is a struct based on Trie, which supports concurrent operations using RwLock.
+/// TrieStore is a key-value store over a persistent Trie. Readers take a snapshot of the current
+/// version and never wait for writers; writers are serialized, build the next version off to the
+/// side and then swap it in.
 #[derive(Debug)]
-pub struct TrieStore<T: Default> {
+pub struct TrieStore<T> {
     root: Arc<RwLock<Trie<T>>>,
+    /// Serializes writers, so that each one builds on the version the previous one swapped in.
+    write_lock: Arc<Mutex<()>>,
 }
 
-impl<T: Default> TrieStore<T> {
-    /// Creates a new TrieStore with an empty Trie.
-    pub fn new() -> Self {
-        TrieStore {
-            root: Arc::new(RwLock::new(Trie::<T>::new())),
-        }
+impl<T> Default for TrieStore<T> {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
+impl<T> Clone for TrieStore<T> {
     /// Clones the TrieStore, sharing the same underlying data.
-    pub fn clone(&self) -> TrieStore<T> {
+    fn clone(&self) -> TrieStore<T> {
         TrieStore {
             root: self.root.clone(),
+            write_lock: self.write_lock.clone(),
+        }
+    }
+}
+
+impl<T> TrieStore<T> {
+    /// Creates a new TrieStore with an empty Trie.
+    pub fn new() -> Self {
+        TrieStore {
+            root: Arc::new(RwLock::new(Trie::<T>::new())),
+            write_lock: Arc::new(Mutex::new(())),
         }
     }
 
     /// Gets the value associated with the given key, if it exists, wrapped in a ValueGuard.
     pub fn get(&self, key: String) -> Option<ValueGuard<T>> {
-        let read_guard = self.root.read().unwrap(); // Acquire a read lock
-        if let Some(value) = read_guard.get(key.clone()) {
-            Some(ValueGuard {
-                root: read_guard.clone(),
-                value: value,
-            })
-        } else {
-            None
-        }
+        let root = self.root.read().unwrap().clone();
+        let value = root.get(key)?;
+        Some(ValueGuard { root, value })
     }
 
     /// Inserts the given value into the Trie associated with the specified key.
     pub fn put(&self, key: String, value: T) {
-        let mut write_guard = self.root.write().unwrap(); // Acquire a write lock
-        let new_root = write_guard.put(key.clone(), value);
-        *write_guard = new_root;
+        let _write_guard = self.write_lock.lock().unwrap();
+        let root = self.root.read().unwrap().clone();
+        let new_root = root.put(key, value);
+        *self.root.write().unwrap() = new_root;
     }
 
     /// Removes the value associated with the given key from the Trie.
     pub fn remove(&self, key: String) {
-        let mut write_guard = self.root.write().unwrap(); // Acquire a write lock
-        let new_root = write_guard.remove(key.clone());
-        *write_guard = new_root;
+        let _write_guard = self.write_lock.lock().unwrap();
+        let root = self.root.read().unwrap().clone();
+        let new_root = root.remove(key);
+        *self.root.write().unwrap() = new_root;
     }
 }
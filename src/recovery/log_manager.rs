@@ -0,0 +1,14 @@
+use crate::utils::config::*;
+
+/// LogManager is the part of the write-ahead log the buffer pool depends on.
+///
+/// Before a dirty page is written back, the log must be durable at least up to the page's LSN
+/// (the write-ahead rule). The buffer pool asks for the persistent LSN and forces a log flush when
+/// the log is behind the page.
+pub trait LogManager: Send + Sync {
+    /// Return the LSN of the last log record that is durable on disk, or `INVALID_LSN` if none.
+    fn get_persistent_lsn(&self) -> Lsn;
+
+    /// Force the log to disk up to and including `lsn`. Returns once those records are durable.
+    fn flush(&self, lsn: Lsn);
+}
@@ -1,4 +1,4 @@
-use std::{borrow::{Borrow, BorrowMut}, fmt::Error, fs::File, future::Future, io::{Read, Seek, Write}, pin::Pin, sync::{Arc, Mutex}};
+use std::{borrow::BorrowMut, fs::{File, OpenOptions}, future::Future, io::{Read, Seek, Write}, pin::Pin, sync::{Arc, Mutex}};
 use crate::{log, utils::config::{PageId, BUSTUB_PAGE_SIZE}};
 
 pub struct DiskManager {
@@ -31,14 +31,10 @@ impl DiskManager {
         if !db_file.contains('.') {
             log!("Invalid file name");
         }
-        let (prev, _) = db_file.split_at(db_file.find('.').unwrap());
+        let (prev, _) = db_file.split_at(db_file.rfind('.').unwrap());
         let log_name = format!("{}{}", prev, ".log");
-        let log_file = File::open(log_name.clone()).unwrap_or_else(|_|{
-            File::create(log_name.clone()).unwrap()
-        });
-        let db_io_flie = Arc::new(Mutex::new(File::open(db_file).unwrap_or_else(|_|{
-            File::create(db_file).unwrap()
-        })));
+        let log_file = Self::open_or_create(&log_name);
+        let db_io_flie = Arc::new(Mutex::new(Self::open_or_create(db_file)));
         DiskManager {
             log_io: log_file,
             log_name,
@@ -71,20 +67,26 @@ impl DiskManager {
         let db_io = binding.borrow_mut();
         let offset = page_id as u64 * BUSTUB_PAGE_SIZE as u64;
         if offset >= self.get_file_size(&self.db_name) {
-            log!("Invalid page id");
-            // return Err(Error);
+            // The page was allocated but never written back, so it is still all zeroes.
+            page_data[..BUSTUB_PAGE_SIZE].fill(0);
+            return;
         }
         db_io.seek(std::io::SeekFrom::Start(offset)).unwrap();
-        db_io.read_exact(page_data).unwrap_or_else(|_|{
-            log!("Failed to read page");
-        });
-        if let Some(count) = db_io.read(page_data).ok() {
-            if count < BUSTUB_PAGE_SIZE {
-                log!("Read less than a page");
-                // return Err(Error);
-                page_data[count..BUSTUB_PAGE_SIZE].fill(0);
+        let mut count = 0;
+        while count < BUSTUB_PAGE_SIZE {
+            match db_io.read(&mut page_data[count..BUSTUB_PAGE_SIZE]) {
+                Ok(0) => break,
+                Ok(n) => count += n,
+                Err(_) => {
+                    log!("Failed to read page");
+                    break;
+                }
             }
         }
+        if count < BUSTUB_PAGE_SIZE {
+            log!("Read less than a page");
+            page_data[count..BUSTUB_PAGE_SIZE].fill(0);
+        }
     }
     pub async fn write_log(&mut self, log_data : &[u8], size : usize) {
         if size == 0 {
@@ -112,7 +114,7 @@ impl DiskManager {
         self.log_io.read_exact(log_data).unwrap_or_else(|_|{
             log!("Failed to read log");
         });
-        if let Some(count) = self.log_io.read(log_data).ok() {
+        if let Ok(count) = self.log_io.read(log_data) {
             if count < size {
                 log!("Read less than a page");
                 // return Err(Error);
@@ -129,7 +131,21 @@ impl DiskManager {
     pub fn get_flush_state(&self) -> bool {
         self.flush_log
     }
+    /// Return the number of pages in the database file, counting a partially written last page.
+    pub fn get_num_pages(&self) -> usize {
+        (self.get_file_size(&self.db_name) as usize).div_ceil(BUSTUB_PAGE_SIZE)
+    }
     
+    fn open_or_create(file_name : &str) -> File {
+        OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(file_name)
+            .unwrap()
+    }
+
     fn get_file_size(&self, file_name : &str) -> u64 {
         let file = File::open(file_name).unwrap();
         file.metadata().unwrap().len()
@@ -5,43 +5,107 @@ use crate::utils::config::PageId;
 
 use super::disk_manager::DiskManager;
 
-struct DiskRequest {
+/// Called by the worker thread with the buffer of a `DiskRequest` once the request has completed.
+pub type DiskCallback = Box<dyn FnOnce(Vec<u8>) + Send>;
+
+/// Represents a Write or Read request for the DiskManager to execute.
+pub struct DiskRequest {
+    /// Flag indicating whether the request is a write or a read.
     is_write: bool,
+    /// Buffer a page is either read into from disk (on a read) or written out to disk from (on a
+    /// write). The request owns it until it completes.
     data: Vec<u8>,
+    /// ID of the page being read from / written to disk.
     page_id: PageId,
-    callback: oneshot::Sender<bool>,
+    /// Callback used to hand the buffer back to the request issuer once the request has completed.
+    callback: DiskCallback,
+}
+
+impl DiskRequest {
+    /// Create a request to write `data` to the page, or to read the page into `data`.
+    pub fn new(
+        is_write: bool,
+        data: Vec<u8>,
+        page_id: PageId,
+        callback: DiskCallback,
+    ) -> Self {
+        DiskRequest {
+            is_write,
+            data,
+            page_id,
+            callback,
+        }
+    }
 }
 
+/// The DiskScheduler schedules disk read and write operations. A request is scheduled by calling
+/// `schedule` with an appropriate `DiskRequest`. The scheduler maintains a background worker thread
+/// that processes the scheduled requests in FIFO order using the disk manager.
 pub struct DiskScheduler {
     disk_manager: Arc<Mutex<DiskManager>>,
     request_sender: Sender<Option<DiskRequest>>,
+    background_thread: Option<JoinHandle<()>>,
 }
 
 impl DiskScheduler {
     pub fn new(disk_manager: Arc<Mutex<DiskManager>>) -> Self{
         let (rs, rr) = mpsc::channel();
-        let disk_scheduler = DiskScheduler {
+        let mut disk_scheduler = DiskScheduler {
             disk_manager,
             request_sender: rs,
+            background_thread: None,
         };
-        disk_scheduler.start_worker_thread(rr);
+        disk_scheduler.background_thread = Some(disk_scheduler.start_worker_thread(rr));
         disk_scheduler
     }
+
+    /// Schedules a request for the DiskManager to execute.
+    pub fn schedule(&self, request: DiskRequest) {
+        self.request_sender.send(Some(request)).unwrap();
+    }
+
+    /// Create a promise/future pair: the promise is the callback of a `DiskRequest`, and the
+    /// future resolves to the buffer of the request once it has completed.
+    pub fn create_promise() -> (DiskCallback, oneshot::Receiver<Vec<u8>>) {
+        let (sender, receiver) = oneshot::channel();
+        let callback: DiskCallback = Box::new(move |data| {
+            // The issuer may have stopped waiting; that is not an error for the worker.
+            let _ = sender.send(data);
+        });
+        (callback, receiver)
+    }
+
+    /// Return the disk manager the scheduler issues requests to.
+    pub fn get_disk_manager(&self) -> Arc<Mutex<DiskManager>> {
+        self.disk_manager.clone()
+    }
+
     fn start_worker_thread(&self, request_receiver: Receiver<Option<DiskRequest>>) -> JoinHandle<()> {
         let disk_manager = self.disk_manager.clone();
         std::thread::spawn(move || {
-            while let Some(mut request) = request_receiver.recv().unwrap() {
+            while let Ok(Some(mut request)) = request_receiver.recv() {
                 if request.is_write {
                     disk_manager.lock().unwrap().write_page(request.page_id, &request.data);
                 } else {
                     disk_manager.lock().unwrap().read_page(request.page_id, &mut request.data);
                 }
-                request.callback.send(true).unwrap();
+                (request.callback)(request.data);
             }
         })
-    }   
+    }
 }
+
+impl Drop for DiskScheduler {
+    fn drop(&mut self) {
+        // Put a `None` in the queue to signal the worker thread to exit.
+        let _ = self.request_sender.send(None);
+        if let Some(handle) = self.background_thread.take() {
+            handle.join().unwrap();
+        }
+    }
+}
+
 unsafe impl Send for DiskRequest{}
 unsafe impl Sync for DiskRequest{}
 unsafe impl Send for DiskManager{}
-unsafe impl Sync for DiskManager{}
\ No newline at end of file
+unsafe impl Sync for DiskManager{}
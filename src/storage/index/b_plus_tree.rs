@@ -0,0 +1,908 @@
+use std::marker::PhantomData;
+use std::ops::RangeBounds;
+use std::sync::Arc;
+
+use parking_lot::{RwLock, RwLockWriteGuard};
+
+use crate::buffer::buffer_pool::BufferPool;
+use crate::storage::page::b_plus_tree_internal_page::*;
+use crate::storage::page::b_plus_tree_leaf_page::*;
+use crate::storage::page::b_plus_tree_page::BPlusTreePage;
+use crate::storage::page::header_page::*;
+use crate::storage::page::page_guard::{ReadPageGuard, WritePageGuard};
+use crate::utils::config::*;
+use crate::utils::rid::RID;
+
+use super::index_iterator::IndexIterator;
+use super::index_key::IndexKey;
+
+/// BPlusTree is a disk-backed B+ tree index mapping unique keys to `RID`s. Its nodes are
+/// `BPlusTreeLeafPage`s and `BPlusTreeInternalPage`s stored in buffer pool pages; the leaves are
+/// linked both ways, and `begin`, `range` and `range_rev` return an `IndexIterator` over them.
+///
+/// A page is split when it grows past its max size, and rebalanced with a sibling, by borrowing an
+/// entry or merging, when it shrinks below its min size. The root page id is recorded in the
+/// header page under the name of the index, so the tree is found again after a restart by
+/// creating it with the same name.
+///
+/// Threads descend the tree with latch crabbing on page guards:
+///
+/// - Lookups read-latch a child before releasing its parent.
+/// - Inserts and removes first try an optimistic descent: read latches down to the parent of the
+///   leaf, then a write latch on the leaf only. If the leaf could split or underflow, they release
+///   it and start over pessimistically.
+/// - A pessimistic descent write-latches every page and keeps the ancestors latched until it
+///   reaches a child that is safe, i.e. cannot split or underflow; it then releases all of them.
+/// - Sibling leaves are always latched left to right.
+///
+/// The root page id has a latch of its own, taken before the root page: readers share it until
+/// they latch the root page, and pessimistic writers hold it exclusively as long as the root may
+/// change.
+pub struct BPlusTree<K: IndexKey> {
+    index_name: String,
+    bpm: Arc<dyn BufferPool>,
+    leaf_max_size: usize,
+    internal_max_size: usize,
+    /// Id of the root page, `INVALID_PAGE_ID` while the tree is empty.
+    root_page_id: RwLock<PageId>,
+    _key: PhantomData<K>,
+}
+
+/// Kind of modification a descent prepares for, which decides when a page is safe.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Operation {
+    Insert,
+    Remove,
+}
+
+/// Leaf a read-only descent looks for.
+pub(crate) enum LeafTarget<'k, K> {
+    First,
+    Last,
+    /// The leaf covering a key.
+    Key(&'k K),
+}
+
+/// Splits a sequence of entries into page-sized chunks of `target` entries. The last two chunks
+/// are evened out if the last one would fall below `min`, so that no page underflows.
+struct Chunker<T> {
+    target: usize,
+    min: usize,
+    max: usize,
+    /// The last full chunk, held back in case the tail needs to borrow from it.
+    prev: Option<Vec<T>>,
+    current: Vec<T>,
+}
+
+impl<T> Chunker<T> {
+    /// Create a chunker filling pages of `min..=max` entries to `fill_factor` of `max`.
+    fn new(fill_factor: f64, min: usize, max: usize) -> Self {
+        let target = ((max as f64 * fill_factor).round() as usize).clamp(min.max(1), max);
+        Chunker {
+            target,
+            min,
+            max,
+            prev: None,
+            current: Vec::with_capacity(target),
+        }
+    }
+
+    /// Add an entry. Return a chunk that is final, if any.
+    fn push(&mut self, entry: T) -> Option<Vec<T>> {
+        self.current.push(entry);
+        if self.current.len() < self.target {
+            return None;
+        }
+        let full = std::mem::replace(&mut self.current, Vec::with_capacity(self.target));
+        self.prev.replace(full)
+    }
+
+    /// Return the remaining chunks.
+    fn finish(self) -> Vec<Vec<T>> {
+        let Chunker {
+            min,
+            max,
+            prev,
+            current,
+            ..
+        } = self;
+        match prev {
+            Some(mut prev) if !current.is_empty() && current.len() < min => {
+                prev.extend(current);
+                if prev.len() <= max {
+                    vec![prev]
+                } else {
+                    let tail = prev.split_off(prev.len() / 2);
+                    vec![prev, tail]
+                }
+            }
+            prev => prev
+                .into_iter()
+                .chain((!current.is_empty()).then_some(current))
+                .collect(),
+        }
+    }
+}
+
+/// State of a `BPlusTree::bulk_load` in progress.
+struct BulkLoader<'a, K: IndexKey> {
+    tree: &'a BPlusTree<K>,
+    /// Every page written so far, to delete if the load fails.
+    page_ids: Vec<PageId>,
+    /// The last leaf written, still to be linked to the next one.
+    prev_leaf: Option<WritePageGuard<'a>>,
+}
+
+impl<K: IndexKey> BulkLoader<'_, K> {
+    /// Write the leaf level. Return the first key and page id of each leaf, or `None` if the keys
+    /// are not strictly ascending or the buffer pool runs out of frames.
+    fn load_leaves(
+        &mut self,
+        entries: impl Iterator<Item = (K, RID)>,
+        fill_factor: f64,
+    ) -> Option<Vec<(K, PageId)>> {
+        let max_size = self.tree.leaf_max_size;
+        let mut chunker = Chunker::new(fill_factor, max_size / 2, max_size);
+        let mut level = Vec::new();
+        let mut last_key = None;
+        for (key, value) in entries {
+            if last_key.is_some_and(|last_key| key <= last_key) {
+                return None;
+            }
+            last_key = Some(key);
+            if let Some(chunk) = chunker.push((key, value)) {
+                level.push(self.write_leaf(&chunk)?);
+            }
+        }
+        for chunk in chunker.finish() {
+            level.push(self.write_leaf(&chunk)?);
+        }
+        self.prev_leaf = None;
+        Some(level)
+    }
+
+    fn write_leaf(&mut self, entries: &[(K, RID)]) -> Option<(K, PageId)> {
+        let mut guard = self.tree.new_page()?;
+        let page_id = guard.page_id();
+        self.page_ids.push(page_id);
+        let mut leaf = BPlusTreeLeafPage::<_, K>::new(guard.get_data_mut());
+        leaf.init(page_id, self.tree.leaf_max_size);
+        leaf.set_entries(entries);
+        if let Some(mut prev_guard) = self.prev_leaf.take() {
+            leaf.set_prev_page_id(prev_guard.page_id());
+            BPlusTreeLeafPage::<_, K>::new(prev_guard.get_data_mut()).set_next_page_id(page_id);
+        }
+        self.prev_leaf = Some(guard);
+        Some((entries[0].0, page_id))
+    }
+
+    /// Write the internal pages above `children`. Return the first key and page id of each, or
+    /// `None` if the buffer pool runs out of frames.
+    fn load_internal_level(
+        &mut self,
+        children: &[(K, PageId)],
+        fill_factor: f64,
+    ) -> Option<Vec<(K, PageId)>> {
+        let max_size = self.tree.internal_max_size;
+        let mut chunker = Chunker::new(fill_factor, max_size.div_ceil(2).max(2), max_size);
+        let mut chunks = Vec::new();
+        for child in children {
+            chunks.extend(chunker.push(*child));
+        }
+        chunks.extend(chunker.finish());
+        chunks
+            .iter()
+            .map(|entries| {
+                let mut guard = self.tree.new_page()?;
+                let page_id = guard.page_id();
+                self.page_ids.push(page_id);
+                let mut page = BPlusTreeInternalPage::<_, K>::new(guard.get_data_mut());
+                page.init(page_id, max_size);
+                page.set_entries(entries);
+                Some((entries[0].0, page_id))
+            })
+            .collect()
+    }
+}
+
+/// Latches a pessimistic descent still holds above the leaf.
+struct Context<'a> {
+    /// The root page id latch, held while the root may change.
+    root_page_id: Option<RwLockWriteGuard<'a, PageId>>,
+    /// Latched ancestors from the top down, each with the index of the child taken.
+    write_set: Vec<(WritePageGuard<'a>, usize)>,
+}
+
+/// What the latched pages keep after an insert splits the leaf, worked out before any changes.
+struct Split<K> {
+    /// Entries the leaf keeps.
+    leaf_entries: Vec<(K, RID)>,
+    /// The new right sibling of the leaf.
+    right_page_id: PageId,
+    /// Entries of each latched ancestor the split reaches, from the bottom up.
+    ancestor_entries: Vec<Vec<(K, PageId)>>,
+    /// The new root, if the root itself splits.
+    new_root_page_id: Option<PageId>,
+}
+
+impl<K: IndexKey> BPlusTree<K> {
+    /// Open the B+ tree recorded in the header page under `index_name`, or create an empty one.
+    /// Leaves are split past `leaf_max_size` entries and internal pages past `internal_max_size`
+    /// children. Return `None` if the name cannot be recorded, or the header page is unavailable
+    /// or was never initialized, see `ensure_header_page`.
+    pub fn new(
+        index_name: &str,
+        bpm: Arc<dyn BufferPool>,
+        leaf_max_size: usize,
+        internal_max_size: usize,
+    ) -> Option<Self> {
+        assert!(
+            (2..leaf_page_capacity::<K>()).contains(&leaf_max_size),
+            "leaf max size must be in 2..{}",
+            leaf_page_capacity::<K>()
+        );
+        assert!(
+            (3..internal_page_capacity::<K>()).contains(&internal_max_size),
+            "internal max size must be in 3..{}",
+            internal_page_capacity::<K>()
+        );
+        let root_page_id = {
+            let mut guard = bpm.fetch_page_write(HEADER_PAGE_ID)?;
+            let header = HeaderPage::new(guard.get_data());
+            if !header.is_header_page() {
+                return None;
+            }
+            match header.get_root_id(index_name) {
+                Some(root_page_id) => root_page_id,
+                None => {
+                    if !HeaderPage::new(guard.get_data_mut())
+                        .insert_record(index_name, INVALID_PAGE_ID)
+                    {
+                        return None;
+                    }
+                    INVALID_PAGE_ID
+                }
+            }
+        };
+        Some(BPlusTree {
+            index_name: index_name.to_string(),
+            bpm,
+            leaf_max_size,
+            internal_max_size,
+            root_page_id: RwLock::new(root_page_id),
+            _key: PhantomData,
+        })
+    }
+
+    /// Return the name the tree is recorded under in the header page.
+    pub fn get_index_name(&self) -> &str {
+        &self.index_name
+    }
+
+    /// Return the id of the root page, or `INVALID_PAGE_ID` if the tree is empty.
+    pub fn get_root_page_id(&self) -> PageId {
+        *self.root_page_id.read()
+    }
+
+    /// Return true if the tree holds no key.
+    pub fn is_empty(&self) -> bool {
+        self.get_root_page_id() == INVALID_PAGE_ID
+    }
+
+    /// Return the RID stored under `key`, if any. Return `None` as well if the buffer pool has no
+    /// frame for a page on the way.
+    pub fn get_value(&self, key: &K) -> Option<RID> {
+        let guard = self.find_leaf_read(LeafTarget::Key(key))?;
+        let leaf = BPlusTreeLeafPage::<_, K>::new(guard.get_data());
+        leaf.lookup(key).ok().map(|index| leaf.value_at(index))
+    }
+
+    /// Build the tree bottom-up from `entries`, which must be sorted by strictly ascending key,
+    /// instead of inserting them one by one. Pages are filled to `fill_factor` (in `(0, 1]`) of
+    /// their max size, leaving room for later inserts, and never below their min size.
+    ///
+    /// Return false, leaving the tree empty, if the tree is not empty, the keys are not strictly
+    /// ascending or the buffer pool runs out of frames. Other operations on the tree wait until
+    /// the load is done.
+    pub fn bulk_load<I: IntoIterator<Item = (K, RID)>>(
+        &self,
+        entries: I,
+        fill_factor: f64,
+    ) -> bool {
+        assert!(
+            fill_factor > 0.0 && fill_factor <= 1.0,
+            "fill factor {} is not in (0, 1]",
+            fill_factor
+        );
+        let mut root_page_id = self.root_page_id.write();
+        if *root_page_id != INVALID_PAGE_ID {
+            return false;
+        }
+
+        let mut loader = BulkLoader {
+            tree: self,
+            page_ids: Vec::new(),
+            prev_leaf: None,
+        };
+        let level = loader
+            .load_leaves(entries.into_iter(), fill_factor)
+            .and_then(|mut level| {
+                while level.len() > 1 {
+                    level = loader.load_internal_level(&level, fill_factor)?;
+                }
+                Some(level)
+            });
+        let root = match level.as_deref() {
+            Some([]) => return true,
+            Some(&[(_, page_id)]) => self
+                .fetch_write(HEADER_PAGE_ID)
+                .map(|header_guard| (header_guard, page_id)),
+            _ => None,
+        };
+        let Some((header_guard, page_id)) = root else {
+            drop(loader.prev_leaf.take());
+            for page_id in loader.page_ids {
+                self.delete_page(page_id);
+            }
+            return false;
+        };
+        drop(loader);
+        self.set_root_page_id(header_guard, &mut root_page_id, page_id);
+        true
+    }
+
+    /// Return an iterator over all entries in key order.
+    pub fn begin(&self) -> IndexIterator<'_, K> {
+        self.range(..)
+    }
+
+    /// Return an iterator over the entries from `key` on, in key order.
+    pub fn begin_at(&self, key: &K) -> IndexIterator<'_, K> {
+        self.range(*key..)
+    }
+
+    /// Return an exhausted iterator, equal to any iterator that has run past its last entry.
+    pub fn end(&self) -> IndexIterator<'_, K> {
+        IndexIterator::end(self)
+    }
+
+    /// Return an iterator over the entries whose key is within `range`, in key order.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> IndexIterator<'_, K> {
+        IndexIterator::new(
+            self,
+            range.start_bound().cloned(),
+            range.end_bound().cloned(),
+            false,
+        )
+    }
+
+    /// Return an iterator over all entries in reverse key order.
+    pub fn rbegin(&self) -> IndexIterator<'_, K> {
+        self.range_rev(..)
+    }
+
+    /// Return an iterator over the entries whose key is within `range`, in reverse key order.
+    pub fn range_rev<R: RangeBounds<K>>(&self, range: R) -> IndexIterator<'_, K> {
+        IndexIterator::new(
+            self,
+            range.start_bound().cloned(),
+            range.end_bound().cloned(),
+            true,
+        )
+    }
+
+    /// Descend to a leaf with read latches, latching each child before releasing its parent.
+    /// Return `None` if the tree is empty.
+    pub(crate) fn find_leaf_read(&self, target: LeafTarget<'_, K>) -> Option<ReadPageGuard<'_>> {
+        let root_page_id = self.root_page_id.read();
+        if *root_page_id == INVALID_PAGE_ID {
+            return None;
+        }
+        let mut guard = self.fetch_read(*root_page_id)?;
+        drop(root_page_id);
+        while !BPlusTreePage::new(guard.get_data()).is_leaf() {
+            let page = BPlusTreeInternalPage::<_, K>::new(guard.get_data());
+            let index = match target {
+                LeafTarget::First => 0,
+                LeafTarget::Last => page.get_size() - 1,
+                LeafTarget::Key(key) => page.lookup_child(key),
+            };
+            let child_page_id = page.value_at(index);
+            // Assigning releases the parent once the child is latched.
+            guard = self.fetch_read(child_page_id)?;
+        }
+        Some(guard)
+    }
+
+    /// Insert `key` with `value`. Return false if the key is already present, or if the buffer
+    /// pool has no frame for a page the insert needs; the tree is then left unchanged.
+    pub fn insert(&self, key: &K, value: RID) -> bool {
+        if let Some((mut guard, _)) = self.find_leaf_optimistic(key) {
+            let mut leaf = BPlusTreeLeafPage::<_, K>::new(guard.get_data_mut());
+            match leaf.lookup(key) {
+                Ok(_) => return false,
+                Err(index) if leaf.get_size() < leaf.get_max_size() => {
+                    leaf.insert_at(index, key, value);
+                    return true;
+                }
+                // The leaf would split: start over, latching the ancestors.
+                Err(_) => {}
+            }
+        }
+        self.insert_pessimistic(key, value).unwrap_or(false)
+    }
+
+    /// Insert `key` with `value`, keeping the ancestors of the leaf latched as long as they may
+    /// split. Return whether the key was inserted, or `None` if the buffer pool ran out of frames.
+    fn insert_pessimistic(&self, key: &K, value: RID) -> Option<bool> {
+        let mut root_page_id = self.root_page_id.write();
+        if *root_page_id == INVALID_PAGE_ID {
+            let header_guard = self.fetch_write(HEADER_PAGE_ID)?;
+            let mut guard = self.new_page()?;
+            let page_id = guard.page_id();
+            let mut leaf = BPlusTreeLeafPage::<_, K>::new(guard.get_data_mut());
+            leaf.init(page_id, self.leaf_max_size);
+            leaf.insert_at(0, key, value);
+            drop(guard);
+            self.set_root_page_id(header_guard, &mut root_page_id, page_id);
+            return Some(true);
+        }
+
+        let (mut ctx, mut guard) =
+            self.find_leaf_pessimistic(root_page_id, key, Operation::Insert)?;
+        let leaf_page_id = guard.page_id();
+        let mut leaf = BPlusTreeLeafPage::<_, K>::new(guard.get_data_mut());
+        let Err(index) = leaf.lookup(key) else {
+            return Some(false);
+        };
+        if leaf.get_size() < leaf.get_max_size() {
+            leaf.insert_at(index, key, value);
+            return Some(true);
+        }
+
+        // Split the leaf, and in turn every latched ancestor the separator overflows. The upper
+        // halves are written to new pages first, and the latched pages only change once all the
+        // pages needed are at hand, so that running out of frames leaves the tree untouched.
+        let mut entries = leaf.get_entries();
+        entries.insert(index, (*key, value));
+        let next_page_id = leaf.get_next_page_id();
+        let mut new_page_ids = Vec::new();
+        let split = self
+            .write_split(&ctx, leaf_page_id, next_page_id, entries, &mut new_page_ids)
+            .and_then(|split| {
+                let next_guard = self.fetch_next_leaf(next_page_id)?;
+                let header_guard = match split.new_root_page_id {
+                    Some(_) => Some(self.fetch_write(HEADER_PAGE_ID)?),
+                    None => None,
+                };
+                Some((split, next_guard, header_guard))
+            });
+        let Some((split, next_guard, header_guard)) = split else {
+            for page_id in new_page_ids {
+                self.delete_page(page_id);
+            }
+            return None;
+        };
+
+        leaf.set_entries(&split.leaf_entries);
+        leaf.set_next_page_id(split.right_page_id);
+        Self::set_prev_link(next_guard, split.right_page_id);
+        drop(guard);
+        for ((mut guard, _), entries) in ctx.write_set.drain(..).rev().zip(&split.ancestor_entries)
+        {
+            BPlusTreeInternalPage::<_, K>::new(guard.get_data_mut()).set_entries(entries);
+        }
+        if let (Some(header_guard), Some(new_root_page_id)) = (header_guard, split.new_root_page_id)
+        {
+            let root_page_id = ctx.root_page_id.as_mut().expect("the root latch is held");
+            self.set_root_page_id(header_guard, root_page_id, new_root_page_id);
+        }
+        Some(true)
+    }
+
+    /// Remove `key`. Return false if the key is not present, or if the buffer pool has no frame
+    /// for a page the remove needs; the tree is then left unchanged.
+    ///
+    /// Once the key is removed, a page that cannot be rebalanced for lack of frames is left below
+    /// its min size. The tree stays searchable, and a later remove from the page rebalances it.
+    pub fn remove(&self, key: &K) -> bool {
+        self.remove_matching(key, None)
+    }
+
+    /// Remove `key` if it is stored with `value`. Return false if it is not, or if the buffer
+    /// pool has no frame for a page the remove needs, see `remove`.
+    pub fn remove_entry(&self, key: &K, value: RID) -> bool {
+        self.remove_matching(key, Some(value))
+    }
+
+    /// Remove `key`, only if it is stored with `value` if one is given.
+    fn remove_matching(&self, key: &K, value: Option<RID>) -> bool {
+        if let Some((mut guard, is_root)) = self.find_leaf_optimistic(key) {
+            let mut leaf = BPlusTreeLeafPage::<_, K>::new(guard.get_data_mut());
+            let safe = if is_root {
+                leaf.get_size() > 1
+            } else {
+                leaf.get_size() > leaf.get_min_size()
+            };
+            match Self::lookup_entry(&leaf, key, value) {
+                None => return false,
+                Some(index) if safe => {
+                    leaf.remove_at(index);
+                    return true;
+                }
+                // The leaf would underflow: start over, latching the ancestors.
+                Some(_) => {}
+            }
+        }
+        self.remove_pessimistic(key, value).unwrap_or(false)
+    }
+
+    /// Return the index of `key` in `leaf`, if it is there and stored with `value` if one is
+    /// given.
+    fn lookup_entry<D: AsRef<[u8]>>(
+        leaf: &BPlusTreeLeafPage<D, K>,
+        key: &K,
+        value: Option<RID>,
+    ) -> Option<usize> {
+        let index = leaf.lookup(key).ok()?;
+        value
+            .is_none_or(|value| leaf.value_at(index) == value)
+            .then_some(index)
+    }
+
+    /// Remove `key`, keeping the ancestors of the leaf latched as long as they may underflow.
+    /// Return whether the key was removed, or `None` if the buffer pool ran out of frames before.
+    fn remove_pessimistic(&self, key: &K, value: Option<RID>) -> Option<bool> {
+        let root_page_id = self.root_page_id.write();
+        if *root_page_id == INVALID_PAGE_ID {
+            return Some(false);
+        }
+        let (mut ctx, mut guard) =
+            self.find_leaf_pessimistic(root_page_id, key, Operation::Remove)?;
+        let leaf_page_id = guard.page_id();
+        let mut leaf = BPlusTreeLeafPage::<_, K>::new(guard.get_data_mut());
+        let Some(index) = Self::lookup_entry(&leaf, key, value) else {
+            return Some(false);
+        };
+
+        if ctx.write_set.is_empty() {
+            // The leaf is the root, or was safe. The root leaf may hold any number of entries,
+            // but an empty tree has no root.
+            if leaf.get_size() == 1 {
+                let header_guard = self.fetch_write(HEADER_PAGE_ID)?;
+                drop(guard);
+                self.delete_page(leaf_page_id);
+                let root_page_id = ctx.root_page_id.as_mut().expect("the root latch is held");
+                self.set_root_page_id(header_guard, root_page_id, INVALID_PAGE_ID);
+            } else {
+                leaf.remove_at(index);
+            }
+        } else {
+            leaf.remove_at(index);
+            if leaf.get_size() < leaf.get_min_size() {
+                self.rebalance(&mut ctx, guard);
+            }
+        }
+        Some(true)
+    }
+
+    /// Descend to the leaf that covers `key` with read latches, and write-latch the leaf only.
+    /// Return the leaf and whether it is the root, or `None` if the tree is empty or the buffer
+    /// pool has no frame for a page on the way.
+    fn find_leaf_optimistic(&self, key: &K) -> Option<(WritePageGuard<'_>, bool)> {
+        let root_page_id = self.root_page_id.read();
+        if *root_page_id == INVALID_PAGE_ID {
+            return None;
+        }
+        let mut parent = self.fetch_read(*root_page_id)?;
+        if BPlusTreePage::new(parent.get_data()).is_leaf() {
+            // The root cannot change while its latch is held.
+            drop(parent);
+            return Some((self.fetch_write(*root_page_id)?, true));
+        }
+        drop(root_page_id);
+        loop {
+            let page = BPlusTreeInternalPage::<_, K>::new(parent.get_data());
+            let child_page_id = page.value_at(page.lookup_child(key));
+            let child = self.fetch_read(child_page_id)?;
+            if BPlusTreePage::new(child.get_data()).is_leaf() {
+                // Keep the parent latched so that the leaf cannot be merged away in between.
+                drop(child);
+                return Some((self.fetch_write(child_page_id)?, false));
+            }
+            parent = child;
+        }
+    }
+
+    /// Descend to the leaf that covers `key` with write latches, releasing the ancestors of every
+    /// page that is safe for `operation`. Return the latches still held above the leaf, and the
+    /// leaf, or `None` if the buffer pool has no frame for a page on the way.
+    fn find_leaf_pessimistic<'a>(
+        &'a self,
+        root_page_id: RwLockWriteGuard<'a, PageId>,
+        key: &K,
+        operation: Operation,
+    ) -> Option<(Context<'a>, WritePageGuard<'a>)> {
+        let mut page_id = *root_page_id;
+        let mut is_root = true;
+        let mut ctx = Context {
+            root_page_id: Some(root_page_id),
+            write_set: Vec::new(),
+        };
+        loop {
+            let guard = self.fetch_write(page_id)?;
+            if Self::is_safe(guard.get_data(), operation, is_root) {
+                ctx.root_page_id = None;
+                ctx.write_set.clear();
+            }
+            let is_leaf = BPlusTreePage::new(guard.get_data()).is_leaf();
+            if is_leaf {
+                return Some((ctx, guard));
+            }
+            let page = BPlusTreeInternalPage::<_, K>::new(guard.get_data());
+            let index = page.lookup_child(key);
+            page_id = page.value_at(index);
+            is_root = false;
+            ctx.write_set.push((guard, index));
+        }
+    }
+
+    /// Return true if `operation` on a descendant of page `data` cannot propagate above it: an
+    /// insert cannot split it and a remove cannot make it underflow, or make the root collapse.
+    fn is_safe(data: &[u8], operation: Operation, is_root: bool) -> bool {
+        let page = BPlusTreePage::new(data);
+        match operation {
+            Operation::Insert => page.get_size() < page.get_max_size(),
+            Operation::Remove if is_root && page.is_leaf() => page.get_size() > 1,
+            Operation::Remove if is_root => page.get_size() > 2,
+            Operation::Remove => page.get_size() > page.get_min_size(),
+        }
+    }
+
+    /// Write the upper halves of the pages an insert splits to new pages, leaving the latched
+    /// pages unchanged: the leaf `leaf_page_id`, which now holds `entries`, then each latched
+    /// ancestor in `ctx` that the separator of its split child overflows, and a new root if the
+    /// root itself splits. Push the ids of the new pages to `new_page_ids`. Return what the
+    /// latched pages keep, or `None` if the buffer pool runs out of frames.
+    fn write_split(
+        &self,
+        ctx: &Context<'_>,
+        leaf_page_id: PageId,
+        next_page_id: PageId,
+        entries: Vec<(K, RID)>,
+        new_page_ids: &mut Vec<PageId>,
+    ) -> Option<Split<K>> {
+        let (left_entries, right_entries) = entries.split_at(entries.len() / 2);
+        let right_page_id = self.write_new_page(new_page_ids, |page_id, data| {
+            let mut right = BPlusTreeLeafPage::<_, K>::new(data);
+            right.init(page_id, self.leaf_max_size);
+            right.set_entries(right_entries);
+            right.set_prev_page_id(leaf_page_id);
+            right.set_next_page_id(next_page_id);
+        })?;
+        let mut split = Split {
+            leaf_entries: left_entries.to_vec(),
+            right_page_id,
+            ancestor_entries: Vec::new(),
+            new_root_page_id: None,
+        };
+
+        let (mut left_page_id, mut right_page_id) = (leaf_page_id, right_page_id);
+        let mut separator = right_entries[0].0;
+        for (guard, index) in ctx.write_set.iter().rev() {
+            let page = BPlusTreeInternalPage::<_, K>::new(guard.get_data());
+            let mut entries = page.get_entries();
+            entries.insert(index + 1, (separator, right_page_id));
+            if entries.len() <= page.get_max_size() {
+                split.ancestor_entries.push(entries);
+                return Some(split);
+            }
+            // The first key of the right half moves up to the grandparent.
+            let (left_entries, right_entries) = entries.split_at(entries.len().div_ceil(2));
+            left_page_id = guard.page_id();
+            right_page_id = self.write_new_page(new_page_ids, |page_id, data| {
+                let mut right = BPlusTreeInternalPage::<_, K>::new(data);
+                right.init(page_id, page.get_max_size());
+                right.set_entries(right_entries);
+            })?;
+            separator = right_entries[0].0;
+            split.ancestor_entries.push(left_entries.to_vec());
+        }
+
+        // The root itself split: grow the tree by a new root.
+        let new_root_page_id = self.write_new_page(new_page_ids, |page_id, data| {
+            let mut root = BPlusTreeInternalPage::<_, K>::new(data);
+            root.init(page_id, self.internal_max_size);
+            root.set_entries(&[(separator, left_page_id), (separator, right_page_id)]);
+        })?;
+        split.new_root_page_id = Some(new_root_page_id);
+        Some(split)
+    }
+
+    /// Fix the latched page `guard`, which is not the root and fell below its min size, by
+    /// borrowing an entry from a sibling or merging with it. A merge removes an entry from the
+    /// parent, which is rebalanced in turn; the tree shrinks by one level when the root is left
+    /// with one child. The page is left as is if the buffer pool has no frame for a page this
+    /// needs.
+    fn rebalance<'a>(&'a self, ctx: &mut Context<'a>, guard: WritePageGuard<'a>) {
+        let (mut parent_guard, index) = ctx.write_set.pop().expect("the root is never rebalanced");
+        let parent_page_id = parent_guard.page_id();
+        let mut parent = BPlusTreeInternalPage::<_, K>::new(parent_guard.get_data_mut());
+        // Pair the page with its left sibling, or with its right one if it is the first child.
+        let underflow_is_left = index == 0;
+        let right_index = index.max(1);
+        let siblings = if underflow_is_left {
+            self.fetch_write(parent.value_at(1))
+                .map(|right_guard| (guard, right_guard))
+        } else {
+            // Latch siblings left to right, like iterators scanning the leaves. No other writer
+            // can reach the page meanwhile, as the parent stays latched.
+            drop(guard);
+            let left_guard = self.fetch_write(parent.value_at(index - 1));
+            left_guard.zip(self.fetch_write(parent.value_at(index)))
+        };
+        let Some((mut left_guard, mut right_guard)) = siblings else {
+            return;
+        };
+        let left_page_id = left_guard.page_id();
+        let right_page_id = right_guard.page_id();
+        let is_leaf = BPlusTreePage::new(left_guard.get_data()).is_leaf();
+        // Merge if the sibling has no entry to spare.
+        let merge = {
+            let sibling = BPlusTreePage::new(if underflow_is_left {
+                right_guard.get_data()
+            } else {
+                left_guard.get_data()
+            });
+            sibling.get_size() <= sibling.get_min_size()
+        };
+
+        // Latch the pages a merge relinks before changing any.
+        let mut next_guard = None;
+        let mut header_guard = None;
+        if merge {
+            if is_leaf {
+                let right = BPlusTreeLeafPage::<_, K>::new(right_guard.get_data());
+                let Some(guard) = self.fetch_next_leaf(right.get_next_page_id()) else {
+                    return;
+                };
+                next_guard = guard;
+            }
+            // The parent is the root, or was safe and cannot be left with one child.
+            if ctx.write_set.is_empty() && parent.get_size() == 2 {
+                let Some(guard) = self.fetch_write(HEADER_PAGE_ID) else {
+                    return;
+                };
+                header_guard = Some(guard);
+            }
+        }
+
+        if is_leaf {
+            let mut left = BPlusTreeLeafPage::<_, K>::new(left_guard.get_data_mut());
+            let mut right = BPlusTreeLeafPage::<_, K>::new(right_guard.get_data_mut());
+            if merge {
+                let mut entries = left.get_entries();
+                entries.extend(right.get_entries());
+                left.set_entries(&entries);
+                left.set_next_page_id(right.get_next_page_id());
+                Self::set_prev_link(next_guard, left_page_id);
+            } else if underflow_is_left {
+                left.insert_at(left.get_size(), &right.key_at(0), right.value_at(0));
+                right.remove_at(0);
+                parent.set_key_at(right_index, &right.key_at(0));
+            } else {
+                let last = left.get_size() - 1;
+                right.insert_at(0, &left.key_at(last), left.value_at(last));
+                left.remove_at(last);
+                parent.set_key_at(right_index, &right.key_at(0));
+            }
+        } else {
+            let mut left = BPlusTreeInternalPage::<_, K>::new(left_guard.get_data_mut());
+            let mut right = BPlusTreeInternalPage::<_, K>::new(right_guard.get_data_mut());
+            // Entries rotate through the separator in the parent.
+            let separator = parent.key_at(right_index);
+            if merge {
+                let mut entries = left.get_entries();
+                let right_entries = right.get_entries();
+                entries.push((separator, right_entries[0].1));
+                entries.extend_from_slice(&right_entries[1..]);
+                left.set_entries(&entries);
+            } else if underflow_is_left {
+                left.insert_at(left.get_size(), &separator, right.value_at(0));
+                parent.set_key_at(right_index, &right.key_at(1));
+                right.remove_at(0);
+            } else {
+                let last = left.get_size() - 1;
+                right.set_key_at(0, &separator);
+                right.insert_at(0, &left.key_at(last), left.value_at(last));
+                parent.set_key_at(right_index, &left.key_at(last));
+                left.remove_at(last);
+            }
+        }
+        if !merge {
+            return;
+        }
+
+        // The merged page is unreachable once removed from the parent, as the left page, which
+        // linked to it, is still latched.
+        parent.remove_at(right_index);
+        drop(right_guard);
+        self.delete_page(right_page_id);
+        drop(left_guard);
+        let (size, min_size) = (parent.get_size(), parent.get_min_size());
+        if let Some(header_guard) = header_guard {
+            drop(parent_guard);
+            self.delete_page(parent_page_id);
+            let root_page_id = ctx.root_page_id.as_mut().expect("the root latch is held");
+            self.set_root_page_id(header_guard, root_page_id, left_page_id);
+        } else if !ctx.write_set.is_empty() && size < min_size {
+            self.rebalance(ctx, parent_guard);
+        }
+    }
+
+    /// Write-latch the leaf `page_id` ahead of pointing it back to a new previous leaf. Return
+    /// `Some(None)` if there is no such leaf, or `None` if the buffer pool has no frame for it.
+    fn fetch_next_leaf(&self, page_id: PageId) -> Option<Option<WritePageGuard<'_>>> {
+        if page_id == INVALID_PAGE_ID {
+            return Some(None);
+        }
+        self.fetch_write(page_id).map(Some)
+    }
+
+    /// Point the leaf latched by `guard`, if any, back to its new previous leaf.
+    fn set_prev_link(guard: Option<WritePageGuard<'_>>, prev_page_id: PageId) {
+        if let Some(mut guard) = guard {
+            BPlusTreeLeafPage::<_, K>::new(guard.get_data_mut()).set_prev_page_id(prev_page_id);
+        }
+    }
+
+    /// Change the root page id and record it in the latched header page. The record is added
+    /// back if it went missing.
+    fn set_root_page_id(
+        &self,
+        mut header_guard: WritePageGuard<'_>,
+        root_page_id: &mut PageId,
+        new_root_page_id: PageId,
+    ) {
+        *root_page_id = new_root_page_id;
+        let mut header = HeaderPage::new(header_guard.get_data_mut());
+        if !header.update_record(&self.index_name, new_root_page_id) {
+            header.insert_record(&self.index_name, new_root_page_id);
+        }
+    }
+
+    fn new_page(&self) -> Option<WritePageGuard<'_>> {
+        self.bpm
+            .new_page_guarded()
+            .map(|guard| guard.upgrade_write())
+    }
+
+    /// Create a new page, write it with `write`, and release it. Push its id to `page_ids`, and
+    /// return it, or `None` if the buffer pool has no frame for it.
+    fn write_new_page(
+        &self,
+        page_ids: &mut Vec<PageId>,
+        write: impl FnOnce(PageId, &mut [u8]),
+    ) -> Option<PageId> {
+        let mut guard = self.new_page()?;
+        let page_id = guard.page_id();
+        page_ids.push(page_id);
+        write(page_id, guard.get_data_mut());
+        Some(page_id)
+    }
+
+    pub(crate) fn fetch_read(&self, page_id: PageId) -> Option<ReadPageGuard<'_>> {
+        self.bpm.fetch_page_read(page_id)
+    }
+
+    fn fetch_write(&self, page_id: PageId) -> Option<WritePageGuard<'_>> {
+        self.bpm.fetch_page_write(page_id)
+    }
+
+    /// Delete a page that was removed from the tree. A concurrent thread may still pin it for a
+    /// moment, e.g. between releasing its latch and unpinning it; the page is then left behind,
+    /// unreachable.
+    fn delete_page(&self, page_id: PageId) {
+        let _ = self.bpm.delete_page(page_id);
+    }
+}
@@ -0,0 +1,140 @@
+use std::ops::Bound;
+use std::sync::Arc;
+
+use crate::buffer::buffer_pool::BufferPool;
+use crate::utils::rid::RID;
+
+use super::b_plus_tree::BPlusTree;
+use super::generic_key::{GenericKey, KeyColumnType, KeySchema, KeyValue};
+use super::index::{Index, IndexMetadata};
+
+/// BPlusTreeIndex is an `Index` backed by a `BPlusTree` of `GenericKey<N>`s, recorded in the
+/// header page under the name of the index.
+///
+/// The tree only holds unique keys, so a non-unique index appends the RID to every key: entries
+/// with the same key are then neighbors in the tree, ordered by RID, and found with a range scan.
+pub struct BPlusTreeIndex<const N: usize> {
+    metadata: IndexMetadata,
+    /// Schema of the keys stored in the tree: the key schema, followed by the RID for a non-unique
+    /// index.
+    stored_schema: KeySchema,
+    tree: BPlusTree<GenericKey<N>>,
+}
+
+impl<const N: usize> BPlusTreeIndex<N> {
+    /// Open or create the index described by `metadata`, see `BPlusTree::new`. Return `None` if
+    /// the stored keys do not fit in `N` bytes, or the tree cannot be opened.
+    pub fn new(
+        metadata: IndexMetadata,
+        bpm: Arc<dyn BufferPool>,
+        leaf_max_size: usize,
+        internal_max_size: usize,
+    ) -> Option<Self> {
+        let mut columns = metadata.get_key_schema().get_columns().to_vec();
+        if !metadata.is_unique() {
+            columns.extend([KeyColumnType::Integer, KeyColumnType::BigInt]);
+        }
+        let stored_schema = KeySchema::new(columns);
+        if stored_schema.get_key_size() > N {
+            return None;
+        }
+        let tree = BPlusTree::new(metadata.get_name(), bpm, leaf_max_size, internal_max_size)?;
+        Some(BPlusTreeIndex {
+            metadata,
+            stored_schema,
+            tree,
+        })
+    }
+
+    pub fn get_tree(&self) -> &BPlusTree<GenericKey<N>> {
+        &self.tree
+    }
+
+    /// Return the stored key of an entry.
+    fn entry_key(&self, key: &[KeyValue], rid: RID) -> Option<GenericKey<N>> {
+        if self.metadata.is_unique() {
+            return GenericKey::from_values(&self.stored_schema, key);
+        }
+        let mut values = key.to_vec();
+        values.extend([
+            KeyValue::Integer(rid.page_id),
+            KeyValue::BigInt(rid.slot as i64),
+        ]);
+        GenericKey::from_values(&self.stored_schema, &values)
+    }
+
+    /// Return the lowest stored key with the given key if `high` is false, or the highest one.
+    fn bound_key(&self, key: &[KeyValue], high: bool) -> Option<GenericKey<N>> {
+        if self.metadata.is_unique() {
+            return GenericKey::from_values(&self.stored_schema, key);
+        }
+        let mut values = key.to_vec();
+        values.extend(if high {
+            [KeyValue::Integer(i32::MAX), KeyValue::BigInt(i64::MAX)]
+        } else {
+            [KeyValue::Integer(i32::MIN), KeyValue::BigInt(i64::MIN)]
+        });
+        GenericKey::from_values(&self.stored_schema, &values)
+    }
+}
+
+impl<const N: usize> Index for BPlusTreeIndex<N> {
+    fn get_metadata(&self) -> &IndexMetadata {
+        &self.metadata
+    }
+
+    fn insert_entry(&self, key: &[KeyValue], rid: RID) -> bool {
+        self.entry_key(key, rid)
+            .is_some_and(|key| self.tree.insert(&key, rid))
+    }
+
+    fn delete_entry(&self, key: &[KeyValue], rid: RID) -> bool {
+        self.entry_key(key, rid)
+            .is_some_and(|key| self.tree.remove_entry(&key, rid))
+    }
+
+    fn scan_key(&self, key: &[KeyValue]) -> Vec<RID> {
+        if self.metadata.is_unique() {
+            return self
+                .entry_key(key, RID::new(0, 0))
+                .and_then(|key| self.tree.get_value(&key))
+                .into_iter()
+                .collect();
+        }
+        match (self.bound_key(key, false), self.bound_key(key, true)) {
+            (Some(low), Some(high)) => self.tree.range(low..=high).map(|(_, rid)| rid).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn scan_range(
+        &self,
+        lower: Bound<&[KeyValue]>,
+        upper: Bound<&[KeyValue]>,
+    ) -> Option<Vec<(Vec<KeyValue>, RID)>> {
+        let lower = match lower {
+            Bound::Included(key) => self.bound_key(key, false).map(Bound::Included),
+            Bound::Excluded(key) => self.bound_key(key, true).map(Bound::Excluded),
+            Bound::Unbounded => Some(Bound::Unbounded),
+        };
+        let upper = match upper {
+            Bound::Included(key) => self.bound_key(key, true).map(Bound::Included),
+            Bound::Excluded(key) => self.bound_key(key, false).map(Bound::Excluded),
+            Bound::Unbounded => Some(Bound::Unbounded),
+        };
+        let (Some(lower), Some(upper)) = (lower, upper) else {
+            return Some(Vec::new());
+        };
+        let key_columns = self.metadata.get_index_column_count();
+        Some(
+            self.tree
+                .range((lower, upper))
+                .map(|(key, rid)| {
+                    let mut values = key.to_values(&self.stored_schema);
+                    values.truncate(key_columns);
+                    (values, rid)
+                })
+                .collect(),
+        )
+    }
+}
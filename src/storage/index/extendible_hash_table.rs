@@ -0,0 +1,490 @@
+use std::sync::Arc;
+
+use crate::buffer::buffer_pool::BufferPool;
+use crate::storage::page::extendible_hash_table_bucket_page::*;
+use crate::storage::page::extendible_hash_table_directory_page::*;
+use crate::storage::page::extendible_hash_table_header_page::*;
+use crate::storage::page::header_page::*;
+use crate::storage::page::page_guard::{ReadPageGuard, WritePageGuard};
+use crate::utils::config::*;
+use crate::utils::rid::RID;
+
+use super::index_key::IndexKey;
+
+/// Hash function of an `ExtendibleHashTable`.
+pub type HashFunction<K> = fn(&K) -> u32;
+
+/// Hash a key from its serialized bytes, with FNV-1a followed by the MurmurHash3 finalizer so that
+/// both the low bits, which pick a bucket, and the high bits, which pick a directory, are mixed.
+/// The hash only depends on the bytes, so it is stable across restarts.
+pub fn hash_key<K: IndexKey>(key: &K) -> u32 {
+    let mut buf = vec![0; K::SIZE];
+    key.serialize(&mut buf);
+    let mut hash = buf.iter().fold(0x811c_9dc5u32, |hash, &byte| {
+        (hash ^ byte as u32).wrapping_mul(0x0100_0193)
+    });
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x85eb_ca6b);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(0xc2b2_ae35);
+    hash ^ (hash >> 16)
+}
+
+/// ExtendibleHashTable is a disk-backed extendible hash index mapping keys to `RID`s, with three
+/// levels of pages:
+///
+/// - An `ExtendibleHashTableHeaderPage` picks a directory by the top bits of the hash. Directories
+///   are created on first use and never removed.
+/// - An `ExtendibleHashTableDirectoryPage` picks a bucket by the low `global_depth` bits.
+/// - An `ExtendibleHashTableBucketPage` holds the entries.
+///
+/// A full bucket is split in two by one more hash bit, doubling the directory first if the bucket
+/// already uses all of its bits. A bucket that becomes empty is merged with its split image, and
+/// the directory is halved once no bucket needs all of its bits. An insert fails when the bucket
+/// is full and the directory cannot grow past its max depth.
+///
+/// Keys are unique when inserted with `insert`. Inserted with `insert_duplicate` instead, a key
+/// can be stored with several RIDs, found with `get_values`; as entries with the same key stay
+/// in the same bucket, a key has at most as many RIDs as a bucket holds.
+///
+/// The header page id is recorded in the header page under the name of the index, so the table
+/// is found again after a restart by creating it with the same name.
+///
+/// Threads latch pages with page guards, from the header down, releasing each page once the next
+/// one is latched. Lookups take read latches. Inserts and removes read-latch the directory and
+/// write-latch the bucket; only if the bucket must split or merge do they start over with the
+/// directory write-latched, so that operations on other directories, and on buckets that do not
+/// change shape, run concurrently.
+pub struct ExtendibleHashTable<K: IndexKey> {
+    index_name: String,
+    bpm: Arc<dyn BufferPool>,
+    header_page_id: PageId,
+    directory_max_depth: u32,
+    bucket_max_size: usize,
+    hash_fn: HashFunction<K>,
+}
+
+impl<K: IndexKey> ExtendibleHashTable<K> {
+    /// Open the hash table recorded in the header page under `index_name`, or create an empty one
+    /// whose header page spreads keys over up to `2^header_max_depth` directories, directories
+    /// grow up to `2^directory_max_depth` slots, and buckets hold up to `bucket_max_size` entries.
+    /// An existing table keeps the sizes it was created with. Keys are hashed with `hash_key`.
+    ///
+    /// Return `None` if the name cannot be recorded, or the header page is unavailable or was
+    /// never initialized, see `ensure_header_page`.
+    pub fn new(
+        index_name: &str,
+        bpm: Arc<dyn BufferPool>,
+        header_max_depth: u32,
+        directory_max_depth: u32,
+        bucket_max_size: usize,
+    ) -> Option<Self> {
+        Self::new_with_hash_fn(
+            index_name,
+            bpm,
+            header_max_depth,
+            directory_max_depth,
+            bucket_max_size,
+            hash_key::<K>,
+        )
+    }
+
+    /// Like `new`, hashing keys with `hash_fn`. A table must always be opened with the same hash
+    /// function.
+    pub fn new_with_hash_fn(
+        index_name: &str,
+        bpm: Arc<dyn BufferPool>,
+        header_max_depth: u32,
+        directory_max_depth: u32,
+        bucket_max_size: usize,
+        hash_fn: HashFunction<K>,
+    ) -> Option<Self> {
+        assert!(
+            header_max_depth <= HASH_TABLE_HEADER_MAX_DEPTH,
+            "header max depth must be at most {}",
+            HASH_TABLE_HEADER_MAX_DEPTH
+        );
+        assert!(
+            directory_max_depth <= HASH_TABLE_DIRECTORY_MAX_DEPTH,
+            "directory max depth must be at most {}",
+            HASH_TABLE_DIRECTORY_MAX_DEPTH
+        );
+        assert!(
+            (1..=bucket_page_capacity::<K>()).contains(&bucket_max_size),
+            "bucket max size must be in 1..={}",
+            bucket_page_capacity::<K>()
+        );
+        let header_page_id = {
+            let mut guard = bpm.fetch_page_write(HEADER_PAGE_ID)?;
+            let header = HeaderPage::new(guard.get_data());
+            if !header.is_header_page() {
+                return None;
+            }
+            match header.get_root_id(index_name) {
+                Some(header_page_id) => header_page_id,
+                None => {
+                    if !is_valid_record_name(index_name) {
+                        return None;
+                    }
+                    let mut header_guard = bpm.new_page_guarded()?.upgrade_write();
+                    let header_page_id = header_guard.page_id();
+                    ExtendibleHashTableHeaderPage::new(header_guard.get_data_mut())
+                        .init(header_page_id, header_max_depth);
+                    drop(header_guard);
+                    if !HeaderPage::new(guard.get_data_mut())
+                        .insert_record(index_name, header_page_id)
+                    {
+                        bpm.delete_page(header_page_id);
+                        return None;
+                    }
+                    header_page_id
+                }
+            }
+        };
+        Some(ExtendibleHashTable {
+            index_name: index_name.to_string(),
+            bpm,
+            header_page_id,
+            directory_max_depth,
+            bucket_max_size,
+            hash_fn,
+        })
+    }
+
+    /// Return the name the table is recorded under in the header page.
+    pub fn get_index_name(&self) -> &str {
+        &self.index_name
+    }
+
+    /// Return the id of the hash table header page.
+    pub fn get_header_page_id(&self) -> PageId {
+        self.header_page_id
+    }
+
+    /// Return the RID of `key`, if present. Return `None` as well if the buffer pool has no frame
+    /// for a page on the way.
+    pub fn get_value(&self, key: &K) -> Option<RID> {
+        self.read_bucket(key, |bucket| {
+            bucket.lookup(key).map(|index| bucket.value_at(index))
+        })?
+    }
+
+    /// Return the RIDs of every entry of `key`. Return no RID as well if the buffer pool has no
+    /// frame for a page on the way.
+    pub fn get_values(&self, key: &K) -> Vec<RID> {
+        self.read_bucket(key, |bucket| bucket.get_values(key))
+            .unwrap_or_default()
+    }
+
+    /// Read-latch the bucket `key` falls in and return what `f` makes of it, or `None` if its
+    /// directory does not exist or the buffer pool has no frame for a page on the way.
+    fn read_bucket<T>(
+        &self,
+        key: &K,
+        f: impl FnOnce(&ExtendibleHashTableBucketPage<&[u8], K>) -> T,
+    ) -> Option<T> {
+        let hash = (self.hash_fn)(key);
+        let directory_page_id = self.get_directory_page_id(hash, false)?;
+        let directory_guard = self.fetch_read(directory_page_id)?;
+        let directory = ExtendibleHashTableDirectoryPage::new(directory_guard.get_data());
+        let bucket_page_id = directory.get_bucket_page_id(directory.hash_to_bucket_index(hash));
+        let bucket_guard = self.fetch_read(bucket_page_id)?;
+        drop(directory_guard);
+        let bucket = ExtendibleHashTableBucketPage::new(bucket_guard.get_data());
+        Some(f(&bucket))
+    }
+
+    /// Insert a key. Return false if it is already present, if its bucket is full and cannot be
+    /// split any further, or if the buffer pool has no frame for a page the insert needs.
+    pub fn insert(&self, key: &K, value: RID) -> bool {
+        self.insert_entry(key, value, true).unwrap_or(false)
+    }
+
+    /// Insert a key, even if it is already present with other RIDs. Return false if it is
+    /// already present with `value`, if its bucket is full and cannot be split any further, which
+    /// is bound to happen once a key has more RIDs than a bucket holds, or if the buffer pool has
+    /// no frame for a page the insert needs.
+    pub fn insert_duplicate(&self, key: &K, value: RID) -> bool {
+        self.insert_entry(key, value, false).unwrap_or(false)
+    }
+
+    /// Insert a key, refusing a key that is already present if `unique` is set, or only an entry
+    /// that is already present otherwise. Return whether it was inserted, or `None` if the buffer
+    /// pool has no frame for a page on the way.
+    fn insert_entry(&self, key: &K, value: RID, unique: bool) -> Option<bool> {
+        let is_present = |bucket: &ExtendibleHashTableBucketPage<&[u8], K>| {
+            if unique {
+                bucket.lookup(key).is_some()
+            } else {
+                bucket.lookup_entry(key, value).is_some()
+            }
+        };
+        let hash = (self.hash_fn)(key);
+        let directory_page_id = self.get_directory_page_id(hash, true)?;
+        {
+            let directory_guard = self.fetch_read(directory_page_id)?;
+            let directory = ExtendibleHashTableDirectoryPage::new(directory_guard.get_data());
+            let bucket_page_id = directory.get_bucket_page_id(directory.hash_to_bucket_index(hash));
+            let mut bucket_guard = self.fetch_write(bucket_page_id)?;
+            drop(directory_guard);
+            let bucket = ExtendibleHashTableBucketPage::<_, K>::new(bucket_guard.get_data());
+            if is_present(&bucket) {
+                return Some(false);
+            }
+            if !bucket.is_full() {
+                let mut bucket =
+                    ExtendibleHashTableBucketPage::<_, K>::new(bucket_guard.get_data_mut());
+                return Some(bucket.insert_duplicate(key, value));
+            }
+        }
+
+        // The bucket is full: split it under the directory write latch until the key fits.
+        let mut directory_guard = self.fetch_write(directory_page_id)?;
+        loop {
+            let directory = ExtendibleHashTableDirectoryPage::new(directory_guard.get_data());
+            let bucket_index = directory.hash_to_bucket_index(hash);
+            let mut bucket_guard = self.fetch_write(directory.get_bucket_page_id(bucket_index))?;
+            let bucket = ExtendibleHashTableBucketPage::<_, K>::new(bucket_guard.get_data());
+            if is_present(&bucket) {
+                return Some(false);
+            }
+            if !bucket.is_full() {
+                let mut bucket =
+                    ExtendibleHashTableBucketPage::<_, K>::new(bucket_guard.get_data_mut());
+                return Some(bucket.insert_duplicate(key, value));
+            }
+            if !self.split_bucket(&mut directory_guard, bucket_index, &mut bucket_guard) {
+                return Some(false);
+            }
+        }
+    }
+
+    /// Remove a key. Return false if it is not present, or if the buffer pool has no frame for a
+    /// page the remove needs.
+    pub fn remove(&self, key: &K) -> bool {
+        self.remove_matching(key, None).unwrap_or(false)
+    }
+
+    /// Remove a key if it is stored with `value`. Return false if it is not, or if the buffer pool
+    /// has no frame for a page the remove needs.
+    pub fn remove_entry(&self, key: &K, value: RID) -> bool {
+        self.remove_matching(key, Some(value)).unwrap_or(false)
+    }
+
+    /// Remove a key, only if it is stored with `value` if one is given. Return whether it was
+    /// removed, or `None` if its directory does not exist or the buffer pool has no frame for a
+    /// page on the way.
+    fn remove_matching(&self, key: &K, value: Option<RID>) -> Option<bool> {
+        let hash = (self.hash_fn)(key);
+        let directory_page_id = self.get_directory_page_id(hash, false)?;
+        {
+            let directory_guard = self.fetch_read(directory_page_id)?;
+            let directory = ExtendibleHashTableDirectoryPage::new(directory_guard.get_data());
+            let bucket_page_id = directory.get_bucket_page_id(directory.hash_to_bucket_index(hash));
+            let mut bucket_guard = self.fetch_write(bucket_page_id)?;
+            drop(directory_guard);
+            let bucket = ExtendibleHashTableBucketPage::<_, K>::new(bucket_guard.get_data());
+            let index = match value {
+                Some(value) => bucket.lookup_entry(key, value),
+                None => bucket.lookup(key),
+            };
+            let Some(index) = index else {
+                return Some(false);
+            };
+            let mut bucket =
+                ExtendibleHashTableBucketPage::<_, K>::new(bucket_guard.get_data_mut());
+            bucket.remove_at(index);
+            if !bucket.is_empty() {
+                return Some(true);
+            }
+        }
+        self.merge_buckets(directory_page_id, hash);
+        Some(true)
+    }
+
+    /// Check the invariants of every directory, and that every entry is in the bucket its hash
+    /// maps to, panicking on a violation or if the buffer pool has no frame for a page. Meant for
+    /// tests, with no concurrent writers.
+    pub fn verify_integrity(&self) {
+        let header_guard = self.fetch_read(self.header_page_id).unwrap();
+        let header = ExtendibleHashTableHeaderPage::new(header_guard.get_data());
+        for directory_index in 0..header.get_max_size() {
+            let directory_page_id = header.get_directory_page_id(directory_index);
+            if directory_page_id == INVALID_PAGE_ID {
+                continue;
+            }
+            let directory_guard = self.fetch_read(directory_page_id).unwrap();
+            let directory = ExtendibleHashTableDirectoryPage::new(directory_guard.get_data());
+            directory.verify_integrity();
+            for bucket_index in 0..directory.get_size() {
+                let bucket_page_id = directory.get_bucket_page_id(bucket_index);
+                let bucket_guard = self.fetch_read(bucket_page_id).unwrap();
+                let bucket = ExtendibleHashTableBucketPage::<_, K>::new(bucket_guard.get_data());
+                assert!(bucket.get_size() <= bucket.get_max_size());
+                let mask = directory.get_local_depth_mask(bucket_index);
+                for (key, _) in bucket.get_entries() {
+                    let hash = (self.hash_fn)(&key);
+                    assert_eq!(header.hash_to_directory_index(hash), directory_index);
+                    assert_eq!(hash & mask, bucket_index as u32 & mask, "key {:?}", key);
+                }
+            }
+        }
+    }
+
+    /// Return the id of the directory a hash falls in. If it does not exist yet, create it with
+    /// an empty bucket if `create` is set, otherwise return `None`. Return `None` as well if the
+    /// buffer pool has no frame for a page this needs.
+    fn get_directory_page_id(&self, hash: u32, create: bool) -> Option<PageId> {
+        {
+            let header_guard = self.fetch_read(self.header_page_id)?;
+            let header = ExtendibleHashTableHeaderPage::new(header_guard.get_data());
+            let directory_page_id =
+                header.get_directory_page_id(header.hash_to_directory_index(hash));
+            if directory_page_id != INVALID_PAGE_ID || !create {
+                return Some(directory_page_id).filter(|&page_id| page_id != INVALID_PAGE_ID);
+            }
+        }
+
+        let mut header_guard = self.fetch_write(self.header_page_id)?;
+        let header = ExtendibleHashTableHeaderPage::new(header_guard.get_data());
+        let directory_index = header.hash_to_directory_index(hash);
+        let directory_page_id = header.get_directory_page_id(directory_index);
+        if directory_page_id != INVALID_PAGE_ID {
+            // Another thread created it in the meantime.
+            return Some(directory_page_id);
+        }
+        let mut bucket_guard = self.new_page()?;
+        let bucket_page_id = bucket_guard.page_id();
+        ExtendibleHashTableBucketPage::<_, K>::new(bucket_guard.get_data_mut())
+            .init(bucket_page_id, self.bucket_max_size);
+        let Some(mut directory_guard) = self.new_page() else {
+            drop(bucket_guard);
+            self.delete_page(bucket_page_id);
+            return None;
+        };
+        let directory_page_id = directory_guard.page_id();
+        ExtendibleHashTableDirectoryPage::new(directory_guard.get_data_mut()).init(
+            directory_page_id,
+            self.directory_max_depth,
+            bucket_page_id,
+        );
+        ExtendibleHashTableHeaderPage::new(header_guard.get_data_mut())
+            .set_directory_page_id(directory_index, directory_page_id);
+        Some(directory_page_id)
+    }
+
+    /// Split the full bucket of `bucket_index` by its next hash bit, doubling the directory first
+    /// if the bucket already uses all of its bits. Entries with that bit set move to a new bucket.
+    /// Return false if the directory is at its max depth, or if the buffer pool has no frame for
+    /// the new bucket.
+    fn split_bucket(
+        &self,
+        directory_guard: &mut WritePageGuard<'_>,
+        bucket_index: usize,
+        bucket_guard: &mut WritePageGuard<'_>,
+    ) -> bool {
+        let mut directory = ExtendibleHashTableDirectoryPage::new(directory_guard.get_data_mut());
+        let local_depth = directory.get_local_depth(bucket_index);
+        let grow = local_depth == directory.get_global_depth();
+        if grow && directory.get_size() == directory.get_max_size() {
+            return false;
+        }
+        let Some(mut image_guard) = self.new_page() else {
+            return false;
+        };
+        if grow {
+            directory.incr_global_depth();
+        }
+
+        let bucket_page_id = bucket_guard.page_id();
+        let mut bucket = ExtendibleHashTableBucketPage::<_, K>::new(bucket_guard.get_data_mut());
+        let image_page_id = image_guard.page_id();
+        let mut image = ExtendibleHashTableBucketPage::<_, K>::new(image_guard.get_data_mut());
+        image.init(image_page_id, bucket.get_max_size());
+
+        let high_bit = 1 << local_depth;
+        let (moved, kept): (Vec<_>, Vec<_>) = bucket
+            .get_entries()
+            .into_iter()
+            .partition(|(key, _)| (self.hash_fn)(key) & high_bit != 0);
+        bucket.set_entries(&kept);
+        image.set_entries(&moved);
+
+        for index in 0..directory.get_size() {
+            if directory.get_bucket_page_id(index) == bucket_page_id {
+                directory.set_local_depth(index, local_depth + 1);
+                if index as u32 & high_bit != 0 {
+                    directory.set_bucket_page_id(index, image_page_id);
+                }
+            }
+        }
+        true
+    }
+
+    /// Merge the bucket a hash falls in with its split image for as long as either of them is
+    /// empty and both have the same local depth, then halve the directory while it can shrink.
+    /// Buckets the buffer pool has no frame for are left as they are.
+    fn merge_buckets(&self, directory_page_id: PageId, hash: u32) {
+        let Some(mut directory_guard) = self.fetch_write(directory_page_id) else {
+            return;
+        };
+        let mut directory = ExtendibleHashTableDirectoryPage::new(directory_guard.get_data_mut());
+        loop {
+            let bucket_index = directory.hash_to_bucket_index(hash);
+            let local_depth = directory.get_local_depth(bucket_index);
+            if local_depth == 0 {
+                break;
+            }
+            let image_index = directory.get_split_image_index(bucket_index);
+            if directory.get_local_depth(image_index) != local_depth {
+                break;
+            }
+            let bucket_page_id = directory.get_bucket_page_id(bucket_index);
+            let image_page_id = directory.get_bucket_page_id(image_index);
+            let is_empty = |page_id| {
+                self.fetch_read(page_id).is_some_and(|guard| {
+                    ExtendibleHashTableBucketPage::<_, K>::new(guard.get_data()).is_empty()
+                })
+            };
+            let (kept_page_id, removed_page_id) = if is_empty(bucket_page_id) {
+                (image_page_id, bucket_page_id)
+            } else if is_empty(image_page_id) {
+                (bucket_page_id, image_page_id)
+            } else {
+                break;
+            };
+
+            for index in 0..directory.get_size() {
+                let page_id = directory.get_bucket_page_id(index);
+                if page_id == bucket_page_id || page_id == image_page_id {
+                    directory.set_bucket_page_id(index, kept_page_id);
+                    directory.set_local_depth(index, local_depth - 1);
+                }
+            }
+            self.delete_page(removed_page_id);
+        }
+        while directory.can_shrink() {
+            directory.decr_global_depth();
+        }
+    }
+
+    fn new_page(&self) -> Option<WritePageGuard<'_>> {
+        self.bpm
+            .new_page_guarded()
+            .map(|guard| guard.upgrade_write())
+    }
+
+    fn fetch_read(&self, page_id: PageId) -> Option<ReadPageGuard<'_>> {
+        self.bpm.fetch_page_read(page_id)
+    }
+
+    fn fetch_write(&self, page_id: PageId) -> Option<WritePageGuard<'_>> {
+        self.bpm.fetch_page_write(page_id)
+    }
+
+    /// Delete a bucket that was merged away. A concurrent thread may still pin it for a moment, in
+    /// which case the page is left to be evicted.
+    fn delete_page(&self, page_id: PageId) {
+        let _ = self.bpm.delete_page(page_id);
+    }
+}
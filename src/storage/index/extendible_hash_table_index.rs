@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use crate::buffer::buffer_pool::BufferPool;
+use crate::utils::rid::RID;
+
+use super::extendible_hash_table::ExtendibleHashTable;
+use super::generic_key::{GenericKey, KeyValue};
+use super::index::{Index, IndexMetadata};
+
+/// ExtendibleHashTableIndex is an `Index` backed by an `ExtendibleHashTable` of `GenericKey<N>`s,
+/// recorded in the header page under the name of the index. It answers point lookups only.
+///
+/// A non-unique index stores every entry of a key in the same bucket, so a key can have at most
+/// as many entries as a bucket holds.
+pub struct ExtendibleHashTableIndex<const N: usize> {
+    metadata: IndexMetadata,
+    table: ExtendibleHashTable<GenericKey<N>>,
+}
+
+impl<const N: usize> ExtendibleHashTableIndex<N> {
+    /// Open or create the index described by `metadata`, see `ExtendibleHashTable::new`. Return
+    /// `None` if the keys do not fit in `N` bytes, or the hash table cannot be opened.
+    pub fn new(
+        metadata: IndexMetadata,
+        bpm: Arc<dyn BufferPool>,
+        header_max_depth: u32,
+        directory_max_depth: u32,
+        bucket_max_size: usize,
+    ) -> Option<Self> {
+        if metadata.get_key_schema().get_key_size() > N {
+            return None;
+        }
+        let table = ExtendibleHashTable::new(
+            metadata.get_name(),
+            bpm,
+            header_max_depth,
+            directory_max_depth,
+            bucket_max_size,
+        )?;
+        Some(ExtendibleHashTableIndex { metadata, table })
+    }
+
+    pub fn get_hash_table(&self) -> &ExtendibleHashTable<GenericKey<N>> {
+        &self.table
+    }
+
+    fn key(&self, key: &[KeyValue]) -> Option<GenericKey<N>> {
+        GenericKey::from_values(self.metadata.get_key_schema(), key)
+    }
+}
+
+impl<const N: usize> Index for ExtendibleHashTableIndex<N> {
+    fn get_metadata(&self) -> &IndexMetadata {
+        &self.metadata
+    }
+
+    fn insert_entry(&self, key: &[KeyValue], rid: RID) -> bool {
+        self.key(key).is_some_and(|key| {
+            if self.metadata.is_unique() {
+                self.table.insert(&key, rid)
+            } else {
+                self.table.insert_duplicate(&key, rid)
+            }
+        })
+    }
+
+    fn delete_entry(&self, key: &[KeyValue], rid: RID) -> bool {
+        self.key(key)
+            .is_some_and(|key| self.table.remove_entry(&key, rid))
+    }
+
+    fn scan_key(&self, key: &[KeyValue]) -> Vec<RID> {
+        self.key(key)
+            .map(|key| self.table.get_values(&key))
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,218 @@
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+use super::index_key::IndexKey;
+
+/// Type of a key column. Every type encodes to a fixed number of bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyColumnType {
+    TinyInt,
+    SmallInt,
+    Integer,
+    BigInt,
+    /// A string of at most the given number of bytes.
+    Varchar(usize),
+}
+
+impl KeyColumnType {
+    /// Return the size of an encoded value of this type.
+    pub fn encoded_size(&self) -> usize {
+        match self {
+            KeyColumnType::TinyInt => 1,
+            KeyColumnType::SmallInt => 2,
+            KeyColumnType::Integer => 4,
+            KeyColumnType::BigInt => 8,
+            KeyColumnType::Varchar(max_len) => max_len + SIZE_VARCHAR_LEN,
+        }
+    }
+}
+
+/// Size of the length suffix of an encoded varchar.
+const SIZE_VARCHAR_LEN: usize = 2;
+
+/// Value of a key column.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum KeyValue {
+    TinyInt(i8),
+    SmallInt(i16),
+    Integer(i32),
+    BigInt(i64),
+    Varchar(String),
+}
+
+/// KeySchema lists the types of the columns of an index key, in the order they are compared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeySchema {
+    columns: Vec<KeyColumnType>,
+}
+
+impl KeySchema {
+    pub fn new(columns: Vec<KeyColumnType>) -> Self {
+        for column in &columns {
+            if let KeyColumnType::Varchar(max_len) = column {
+                assert!(
+                    *max_len <= u16::MAX as usize,
+                    "varchar length {} does not fit in a key",
+                    max_len
+                );
+            }
+        }
+        KeySchema { columns }
+    }
+
+    pub fn get_columns(&self) -> &[KeyColumnType] {
+        &self.columns
+    }
+
+    /// Return the size of an encoded key.
+    pub fn get_key_size(&self) -> usize {
+        self.columns.iter().map(KeyColumnType::encoded_size).sum()
+    }
+
+    /// Encode `values` to `buf` so that comparing the bytes of two keys compares their values
+    /// column by column:
+    ///
+    /// - Integers are big-endian with the sign bit flipped, so negative values sort first.
+    /// - Varchars are zero-padded to their max length and followed by their big-endian length,
+    ///   which orders a string before the same string with trailing zero bytes.
+    ///
+    /// Return false if the values do not match the schema, a string is too long, or `buf` is too
+    /// small. Bytes of `buf` past the key are zeroed.
+    pub fn encode(&self, values: &[KeyValue], buf: &mut [u8]) -> bool {
+        if values.len() != self.columns.len() || buf.len() < self.get_key_size() {
+            return false;
+        }
+        let mut offset = 0;
+        for (column, value) in self.columns.iter().zip(values) {
+            let size = column.encoded_size();
+            let field = &mut buf[offset..offset + size];
+            match (column, value) {
+                (KeyColumnType::TinyInt, KeyValue::TinyInt(v)) => {
+                    field.copy_from_slice(&((*v as u8) ^ 0x80).to_be_bytes())
+                }
+                (KeyColumnType::SmallInt, KeyValue::SmallInt(v)) => {
+                    field.copy_from_slice(&((*v as u16) ^ 0x8000).to_be_bytes())
+                }
+                (KeyColumnType::Integer, KeyValue::Integer(v)) => {
+                    field.copy_from_slice(&((*v as u32) ^ 0x8000_0000).to_be_bytes())
+                }
+                (KeyColumnType::BigInt, KeyValue::BigInt(v)) => {
+                    field.copy_from_slice(&((*v as u64) ^ 0x8000_0000_0000_0000).to_be_bytes())
+                }
+                (KeyColumnType::Varchar(max_len), KeyValue::Varchar(v)) => {
+                    if v.len() > *max_len {
+                        return false;
+                    }
+                    field[..v.len()].copy_from_slice(v.as_bytes());
+                    field[v.len()..*max_len].fill(0);
+                    field[*max_len..].copy_from_slice(&(v.len() as u16).to_be_bytes());
+                }
+                _ => return false,
+            }
+            offset += size;
+        }
+        buf[offset..].fill(0);
+        true
+    }
+
+    /// Decode the values of a key written by `encode`. A varchar length past the max length of
+    /// its column, which `encode` never writes, is clamped to it.
+    pub fn decode(&self, buf: &[u8]) -> Vec<KeyValue> {
+        let mut offset = 0;
+        let mut values = Vec::with_capacity(self.columns.len());
+        for column in &self.columns {
+            let size = column.encoded_size();
+            let field = &buf[offset..offset + size];
+            values.push(match column {
+                KeyColumnType::TinyInt => KeyValue::TinyInt((field[0] ^ 0x80) as i8),
+                KeyColumnType::SmallInt => KeyValue::SmallInt(
+                    (u16::from_be_bytes(field.try_into().unwrap()) ^ 0x8000) as i16,
+                ),
+                KeyColumnType::Integer => KeyValue::Integer(
+                    (u32::from_be_bytes(field.try_into().unwrap()) ^ 0x8000_0000) as i32,
+                ),
+                KeyColumnType::BigInt => KeyValue::BigInt(
+                    (u64::from_be_bytes(field.try_into().unwrap()) ^ 0x8000_0000_0000_0000) as i64,
+                ),
+                KeyColumnType::Varchar(max_len) => {
+                    let len = u16::from_be_bytes(field[*max_len..].try_into().unwrap()) as usize;
+                    let len = len.min(*max_len);
+                    KeyValue::Varchar(String::from_utf8_lossy(&field[..len]).into_owned())
+                }
+            });
+            offset += size;
+        }
+        values
+    }
+}
+
+/// GenericKey is an index key of up to `N` bytes holding the values of a `KeySchema` in their
+/// memcomparable encoding, so that keys of any schema are ordered by comparing their bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GenericKey<const N: usize> {
+    data: [u8; N],
+}
+
+impl<const N: usize> GenericKey<N> {
+    /// Encode `values` as a key of `schema`. Return `None` if they do not match the schema or
+    /// do not fit in `N` bytes.
+    pub fn from_values(schema: &KeySchema, values: &[KeyValue]) -> Option<Self> {
+        let mut data = [0; N];
+        schema
+            .encode(values, &mut data)
+            .then_some(GenericKey { data })
+    }
+
+    /// Decode the values of the key.
+    pub fn to_values(&self, schema: &KeySchema) -> Vec<KeyValue> {
+        schema.decode(&self.data)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl<const N: usize> IndexKey for GenericKey<N> {
+    const SIZE: usize = N;
+
+    fn serialize(&self, buf: &mut [u8]) {
+        buf.copy_from_slice(&self.data);
+    }
+
+    fn deserialize(buf: &[u8]) -> Self {
+        GenericKey {
+            data: buf.try_into().unwrap(),
+        }
+    }
+}
+
+/// KeyComparator orders the keys of an index.
+pub trait KeyComparator<K> {
+    fn compare(&self, lhs: &K, rhs: &K) -> Ordering;
+}
+
+/// GenericComparator orders `GenericKey`s by decoding them with a key schema and comparing their
+/// values column by column. It agrees with the byte order of the keys, which indexes rely on, and
+/// serves to check it.
+#[derive(Debug, Clone)]
+pub struct GenericComparator {
+    schema: Arc<KeySchema>,
+}
+
+impl GenericComparator {
+    pub fn new(schema: Arc<KeySchema>) -> Self {
+        GenericComparator { schema }
+    }
+
+    pub fn get_schema(&self) -> &KeySchema {
+        &self.schema
+    }
+}
+
+impl<const N: usize> KeyComparator<GenericKey<N>> for GenericComparator {
+    fn compare(&self, lhs: &GenericKey<N>, rhs: &GenericKey<N>) -> Ordering {
+        lhs.to_values(&self.schema)
+            .cmp(&rhs.to_values(&self.schema))
+    }
+}
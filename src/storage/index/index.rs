@@ -0,0 +1,103 @@
+use std::ops::Bound;
+use std::sync::Arc;
+
+use crate::utils::rid::RID;
+
+use super::generic_key::{KeySchema, KeyValue};
+
+/// IndexMetadata describes an index: its name, the table it indexes, the schema of its keys and
+/// which columns of the table they are made of.
+#[derive(Debug, Clone)]
+pub struct IndexMetadata {
+    name: String,
+    table_name: String,
+    key_schema: Arc<KeySchema>,
+    /// Position in the table schema of each key column.
+    key_attrs: Vec<usize>,
+    is_unique: bool,
+}
+
+impl IndexMetadata {
+    pub fn new(
+        name: &str,
+        table_name: &str,
+        key_schema: KeySchema,
+        key_attrs: Vec<usize>,
+        is_unique: bool,
+    ) -> Self {
+        assert_eq!(
+            key_schema.get_columns().len(),
+            key_attrs.len(),
+            "every key column needs a table column"
+        );
+        IndexMetadata {
+            name: name.to_string(),
+            table_name: table_name.to_string(),
+            key_schema: Arc::new(key_schema),
+            key_attrs,
+            is_unique,
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_table_name(&self) -> &str {
+        &self.table_name
+    }
+
+    pub fn get_key_schema(&self) -> &Arc<KeySchema> {
+        &self.key_schema
+    }
+
+    pub fn get_key_attrs(&self) -> &[usize] {
+        &self.key_attrs
+    }
+
+    /// Return the number of key columns.
+    pub fn get_index_column_count(&self) -> usize {
+        self.key_attrs.len()
+    }
+
+    /// Return true if no two entries may have the same key.
+    pub fn is_unique(&self) -> bool {
+        self.is_unique
+    }
+
+    /// Return the key of a table row, given as the values of all its columns.
+    pub fn get_key_values(&self, row: &[KeyValue]) -> Vec<KeyValue> {
+        self.key_attrs
+            .iter()
+            .map(|&attr| row[attr].clone())
+            .collect()
+    }
+}
+
+/// Index is the interface executors and the catalog use to maintain and search an index,
+/// whatever structure backs it. Keys are given as the values of the key columns, in the order of
+/// the key schema.
+pub trait Index: Send + Sync {
+    fn get_metadata(&self) -> &IndexMetadata;
+
+    /// Add an entry. Return false if the index is unique and already has the key, or if the entry
+    /// cannot be stored, e.g. the key does not match the key schema.
+    fn insert_entry(&self, key: &[KeyValue], rid: RID) -> bool;
+
+    /// Remove the entry with both the key and the RID. Return false if there is no such entry,
+    /// e.g. the key is stored with another RID.
+    fn delete_entry(&self, key: &[KeyValue], rid: RID) -> bool;
+
+    /// Return the RIDs of the entries with the key.
+    fn scan_key(&self, key: &[KeyValue]) -> Vec<RID>;
+
+    /// Return the entries with keys between `lower` and `upper`, in key order, or `None` if the
+    /// index cannot scan ranges.
+    fn scan_range(
+        &self,
+        _lower: Bound<&[KeyValue]>,
+        _upper: Bound<&[KeyValue]>,
+    ) -> Option<Vec<(Vec<KeyValue>, RID)>> {
+        None
+    }
+}
@@ -0,0 +1,221 @@
+use std::ops::Bound;
+
+use crate::storage::page::b_plus_tree_leaf_page::BPlusTreeLeafPage;
+use crate::storage::page::b_plus_tree_page::BPlusTreePage;
+use crate::storage::page::page_guard::ReadPageGuard;
+use crate::utils::config::*;
+use crate::utils::rid::RID;
+
+use super::b_plus_tree::{BPlusTree, LeafTarget};
+use super::index_key::IndexKey;
+
+/// IndexIterator walks the leaf chain of a `BPlusTree`, forward or in reverse, yielding the
+/// (key, RID) entries within a range. It holds a read latch on the current leaf only.
+///
+/// Forward iteration latches the next leaf before releasing the current one, the same left to
+/// right order writers latch sibling leaves in. Going the other way could deadlock with a writer,
+/// so reverse iteration releases the current leaf first, then checks that the previous leaf still
+/// links to it and that no entry moved between them; if a concurrent split, borrow or merge got
+/// in between, it looks up the next entry from the root again.
+///
+/// Entries inserted or removed concurrently may or may not be seen. The iterator ends early if
+/// the buffer pool has no frame for the next leaf.
+pub struct IndexIterator<'a, K: IndexKey> {
+    tree: &'a BPlusTree<K>,
+    /// The current leaf, `None` once the iterator is exhausted.
+    guard: Option<ReadPageGuard<'a>>,
+    /// Going forward, index of the next entry of the leaf; in reverse, index past it.
+    index: usize,
+    lower: Bound<K>,
+    upper: Bound<K>,
+    reverse: bool,
+}
+
+impl<'a, K: IndexKey> IndexIterator<'a, K> {
+    /// Create an iterator over the entries of `tree` between `lower` and `upper`.
+    pub(crate) fn new(
+        tree: &'a BPlusTree<K>,
+        lower: Bound<K>,
+        upper: Bound<K>,
+        reverse: bool,
+    ) -> Self {
+        let mut iter = IndexIterator {
+            tree,
+            guard: None,
+            index: 0,
+            lower,
+            upper,
+            reverse,
+        };
+        if reverse {
+            iter.seek(upper);
+        } else {
+            iter.seek(lower);
+        }
+        iter
+    }
+
+    /// Create an exhausted iterator.
+    pub(crate) fn end(tree: &'a BPlusTree<K>) -> Self {
+        IndexIterator {
+            tree,
+            guard: None,
+            index: 0,
+            lower: Bound::Unbounded,
+            upper: Bound::Unbounded,
+            reverse: false,
+        }
+    }
+
+    /// Return true once the iterator has run past its last entry, or if the tree was empty.
+    pub fn is_end(&self) -> bool {
+        self.guard.is_none()
+    }
+
+    /// Position the iterator at `bound`: on the first entry from it going forward, or on the last
+    /// entry up to it in reverse.
+    fn seek(&mut self, bound: Bound<K>) {
+        let target = match &bound {
+            Bound::Included(key) | Bound::Excluded(key) => LeafTarget::Key(key),
+            Bound::Unbounded if self.reverse => LeafTarget::Last,
+            Bound::Unbounded => LeafTarget::First,
+        };
+        self.guard = self.tree.find_leaf_read(target);
+        let Some(guard) = &self.guard else {
+            return;
+        };
+        let leaf = BPlusTreeLeafPage::<_, K>::new(guard.get_data());
+        self.index = match (bound, self.reverse) {
+            (Bound::Unbounded, false) => 0,
+            (Bound::Unbounded, true) => leaf.get_size(),
+            (Bound::Included(key), false) => leaf.lookup(&key).unwrap_or_else(|index| index),
+            (Bound::Excluded(key), true) => leaf.lookup(&key).unwrap_or_else(|index| index),
+            (Bound::Excluded(key), false) | (Bound::Included(key), true) => leaf
+                .lookup(&key)
+                .map_or_else(|index| index, |index| index + 1),
+        };
+    }
+
+    fn is_below_upper(&self, key: &K) -> bool {
+        match &self.upper {
+            Bound::Included(upper) => key <= upper,
+            Bound::Excluded(upper) => key < upper,
+            Bound::Unbounded => true,
+        }
+    }
+
+    fn is_above_lower(&self, key: &K) -> bool {
+        match &self.lower {
+            Bound::Included(lower) => key >= lower,
+            Bound::Excluded(lower) => key > lower,
+            Bound::Unbounded => true,
+        }
+    }
+
+    fn next_forward(&mut self) -> Option<(K, RID)> {
+        loop {
+            let guard = self.guard.as_ref()?;
+            let leaf = BPlusTreeLeafPage::<_, K>::new(guard.get_data());
+            if self.index < leaf.get_size() {
+                let entry = (leaf.key_at(self.index), leaf.value_at(self.index));
+                self.index += 1;
+                if !self.is_below_upper(&entry.0) {
+                    self.guard = None;
+                    return None;
+                }
+                return Some(entry);
+            }
+            let next_page_id = leaf.get_next_page_id();
+            self.guard = if next_page_id == INVALID_PAGE_ID {
+                None
+            } else {
+                // The next leaf is latched before the current one is released by the assignment.
+                self.tree.fetch_read(next_page_id)
+            };
+            self.index = 0;
+        }
+    }
+
+    fn next_reverse(&mut self) -> Option<(K, RID)> {
+        loop {
+            let guard = self.guard.as_ref()?;
+            let leaf = BPlusTreeLeafPage::<_, K>::new(guard.get_data());
+            if self.index > 0 {
+                self.index -= 1;
+                let entry = (leaf.key_at(self.index), leaf.value_at(self.index));
+                if !self.is_above_lower(&entry.0) {
+                    self.guard = None;
+                    return None;
+                }
+                return Some(entry);
+            }
+            let (page_id, prev_page_id) = (guard.page_id(), leaf.get_prev_page_id());
+            if prev_page_id == INVALID_PAGE_ID || leaf.get_size() == 0 {
+                self.guard = None;
+                return None;
+            }
+            let boundary = leaf.key_at(0);
+            self.guard = None;
+
+            let prev_guard = self.tree.fetch_read(prev_page_id)?;
+            if self.is_unchanged(&prev_guard, page_id, &boundary) {
+                let prev = BPlusTreeLeafPage::<_, K>::new(prev_guard.get_data());
+                self.index = prev.lookup(&boundary).unwrap_or_else(|index| index);
+                self.guard = Some(prev_guard);
+            } else {
+                drop(prev_guard);
+                self.seek(Bound::Excluded(boundary));
+            }
+        }
+    }
+
+    /// Return true if the latched leaf `prev_guard` and leaf `page_id` are still neighbors, and
+    /// `page_id` still starts at `boundary`, so that no entry moved between them since the
+    /// iterator left `page_id`. Both leaves are latched for the check, as either may have been
+    /// merged away and its stale image read back from disk.
+    fn is_unchanged(&self, prev_guard: &ReadPageGuard<'_>, page_id: PageId, boundary: &K) -> bool {
+        if !BPlusTreePage::new(prev_guard.get_data()).is_leaf()
+            || BPlusTreeLeafPage::<_, K>::new(prev_guard.get_data()).get_next_page_id() != page_id
+        {
+            return false;
+        }
+        let Some(guard) = self.tree.fetch_read(page_id) else {
+            return false;
+        };
+        if !BPlusTreePage::new(guard.get_data()).is_leaf() {
+            return false;
+        }
+        let leaf = BPlusTreeLeafPage::<_, K>::new(guard.get_data());
+        leaf.get_prev_page_id() == prev_guard.page_id()
+            && leaf.get_size() > 0
+            && leaf.key_at(0) == *boundary
+    }
+}
+
+impl<K: IndexKey> Iterator for IndexIterator<'_, K> {
+    type Item = (K, RID);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.reverse {
+            self.next_reverse()
+        } else {
+            self.next_forward()
+        }
+    }
+}
+
+impl<K: IndexKey> PartialEq for IndexIterator<'_, K> {
+    /// Iterators are equal when both are exhausted, or both are at the same entry of the same
+    /// leaf going the same way.
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.guard, &other.guard) {
+            (None, None) => true,
+            (Some(guard), Some(other_guard)) => {
+                guard.page_id() == other_guard.page_id()
+                    && self.index == other.index
+                    && self.reverse == other.reverse
+            }
+            _ => false,
+        }
+    }
+}
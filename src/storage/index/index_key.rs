@@ -0,0 +1,34 @@
+use std::fmt::Debug;
+
+/// IndexKey is a key type that can be stored in index pages: it serializes to a fixed number of
+/// bytes and is ordered the way the index sorts it.
+pub trait IndexKey: Copy + Ord + Debug + Send + Sync + 'static {
+    /// Size of the serialized key in bytes.
+    const SIZE: usize;
+
+    /// Write the key to `buf`, which is exactly `SIZE` bytes long.
+    fn serialize(&self, buf: &mut [u8]);
+
+    /// Read back a key written by `serialize`.
+    fn deserialize(buf: &[u8]) -> Self;
+}
+
+macro_rules! impl_index_key_for_int {
+    ($($int:ty),*) => {
+        $(
+            impl IndexKey for $int {
+                const SIZE: usize = std::mem::size_of::<$int>();
+
+                fn serialize(&self, buf: &mut [u8]) {
+                    buf.copy_from_slice(&self.to_le_bytes());
+                }
+
+                fn deserialize(buf: &[u8]) -> Self {
+                    <$int>::from_le_bytes(buf.try_into().unwrap())
+                }
+            }
+        )*
+    };
+}
+
+impl_index_key_for_int!(i32, i64, u32, u64);
@@ -0,0 +1,9 @@
+#[allow(clippy::module_inception)]
+pub mod index;
+pub mod b_plus_tree;
+pub mod b_plus_tree_index;
+pub mod extendible_hash_table;
+pub mod extendible_hash_table_index;
+pub mod generic_key;
+pub mod index_iterator;
+pub mod index_key;
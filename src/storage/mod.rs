@@ -1,3 +1,4 @@
 pub mod disk;
 pub mod index;
-pub mod page;
\ No newline at end of file
+pub mod page;
+pub mod table;
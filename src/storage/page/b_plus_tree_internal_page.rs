@@ -0,0 +1,167 @@
+use std::marker::PhantomData;
+use std::mem::size_of;
+
+use crate::storage::index::index_key::IndexKey;
+use crate::utils::config::*;
+
+use super::b_plus_tree_page::{BPlusTreePage, SIZE_B_PLUS_TREE_PAGE_HEADER};
+use super::page_header::PageType;
+
+/// Size of the internal page header, i.e. offset of the entry array.
+pub const SIZE_INTERNAL_PAGE_HEADER: usize = SIZE_B_PLUS_TREE_PAGE_HEADER;
+
+/// Return the number of children that fit in an internal page with keys of type `K`.
+pub fn internal_page_capacity<K: IndexKey>() -> usize {
+    (BUSTUB_PAGE_SIZE - SIZE_INTERNAL_PAGE_HEADER) / (K::SIZE + size_of::<PageId>())
+}
+
+/// BPlusTreeInternalPage is the layout of B+ tree internal pages, viewed over the data of a
+/// `Page`: the `BPlusTreePage` header followed by (key, child page id) entries sorted by key.
+///
+/// ```text
+///  ---------------------------------------------------------------------
+/// | HEADER | KEY(0) + CHILD(0) | KEY(1) + CHILD(1) | ... | KEY(n) + CHILD(n) |
+///  ---------------------------------------------------------------------
+/// ```
+///
+/// The first key is unused: child `i` holds the keys `k` with `KEY(i) <= k < KEY(i + 1)`, and
+/// child 0 every key below `KEY(1)`.
+#[derive(Debug)]
+pub struct BPlusTreeInternalPage<D, K> {
+    data: D,
+    _key: PhantomData<K>,
+}
+
+impl<D: AsRef<[u8]>, K: IndexKey> BPlusTreeInternalPage<D, K> {
+    /// View page data as an internal page.
+    pub fn new(data: D) -> Self {
+        BPlusTreeInternalPage {
+            data,
+            _key: PhantomData,
+        }
+    }
+
+    /// Return the id of the page.
+    pub fn get_page_id(&self) -> PageId {
+        self.header().get_page_id()
+    }
+
+    /// Return the number of children.
+    pub fn get_size(&self) -> usize {
+        self.header().get_size()
+    }
+
+    /// Return the number of children above which the page is split.
+    pub fn get_max_size(&self) -> usize {
+        self.header().get_max_size()
+    }
+
+    /// Return the number of children below which the page is rebalanced, unless it is the root.
+    pub fn get_min_size(&self) -> usize {
+        self.header().get_min_size()
+    }
+
+    /// Return the key of an entry. The key of entry 0 is meaningless.
+    pub fn key_at(&self, index: usize) -> K {
+        let offset = Self::entry_offset(index);
+        K::deserialize(&self.data.as_ref()[offset..offset + K::SIZE])
+    }
+
+    /// Return the child page id of an entry.
+    pub fn value_at(&self, index: usize) -> PageId {
+        let offset = Self::entry_offset(index) + K::SIZE;
+        PageId::from_le_bytes(self.data.as_ref()[offset..offset + 4].try_into().unwrap())
+    }
+
+    /// Return the index of the child whose subtree holds `key`.
+    pub fn lookup_child(&self, key: &K) -> usize {
+        // Find the first key greater than `key`; the child before it covers `key`.
+        let (mut lo, mut hi) = (1, self.get_size());
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if self.key_at(mid) <= *key {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        lo - 1
+    }
+
+    /// Return every entry, in key order. The first key is meaningless.
+    pub fn get_entries(&self) -> Vec<(K, PageId)> {
+        (0..self.get_size())
+            .map(|index| (self.key_at(index), self.value_at(index)))
+            .collect()
+    }
+
+    fn header(&self) -> BPlusTreePage<&[u8]> {
+        BPlusTreePage::new(self.data.as_ref())
+    }
+
+    fn entry_offset(index: usize) -> usize {
+        SIZE_INTERNAL_PAGE_HEADER + index * (K::SIZE + size_of::<PageId>())
+    }
+}
+
+impl<D: AsRef<[u8]> + AsMut<[u8]>, K: IndexKey> BPlusTreeInternalPage<D, K> {
+    /// Initialize an empty internal page.
+    pub fn init(&mut self, page_id: PageId, max_size: usize) {
+        assert!(
+            max_size < internal_page_capacity::<K>(),
+            "internal max size {} leaves no room to split",
+            max_size
+        );
+        BPlusTreePage::new(self.data.as_mut()).init(page_id, PageType::BPlusTreeInternal, max_size);
+    }
+
+    /// Overwrite the key of an entry.
+    pub fn set_key_at(&mut self, index: usize, key: &K) {
+        let offset = Self::entry_offset(index);
+        key.serialize(&mut self.data.as_mut()[offset..offset + K::SIZE]);
+    }
+
+    /// Insert an entry at `index`, shifting the following entries right. The page may hold one
+    /// child more than its max size until it is split.
+    pub fn insert_at(&mut self, index: usize, key: &K, child_page_id: PageId) {
+        let size = self.get_size();
+        assert!(index <= size && size < internal_page_capacity::<K>());
+        self.data.as_mut().copy_within(
+            Self::entry_offset(index)..Self::entry_offset(size),
+            Self::entry_offset(index + 1),
+        );
+        self.write_entry(index, key, child_page_id);
+        self.set_size(size + 1);
+    }
+
+    /// Remove the entry at `index`, shifting the following entries left.
+    pub fn remove_at(&mut self, index: usize) {
+        let size = self.get_size();
+        assert!(index < size);
+        self.data.as_mut().copy_within(
+            Self::entry_offset(index + 1)..Self::entry_offset(size),
+            Self::entry_offset(index),
+        );
+        self.set_size(size - 1);
+    }
+
+    /// Replace all entries.
+    pub fn set_entries(&mut self, entries: &[(K, PageId)]) {
+        assert!(entries.len() < internal_page_capacity::<K>());
+        for (index, (key, child_page_id)) in entries.iter().enumerate() {
+            self.write_entry(index, key, *child_page_id);
+        }
+        self.set_size(entries.len());
+    }
+
+    fn write_entry(&mut self, index: usize, key: &K, child_page_id: PageId) {
+        let offset = Self::entry_offset(index);
+        let data = self.data.as_mut();
+        key.serialize(&mut data[offset..offset + K::SIZE]);
+        data[offset + K::SIZE..offset + K::SIZE + 4].copy_from_slice(&child_page_id.to_le_bytes());
+    }
+
+    fn set_size(&mut self, size: usize) {
+        BPlusTreePage::new(self.data.as_mut()).set_size(size);
+    }
+}
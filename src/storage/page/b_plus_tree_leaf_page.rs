@@ -0,0 +1,198 @@
+use std::marker::PhantomData;
+use std::mem::size_of;
+
+use crate::storage::index::index_key::IndexKey;
+use crate::utils::config::*;
+use crate::utils::rid::RID;
+
+use super::b_plus_tree_page::{BPlusTreePage, SIZE_B_PLUS_TREE_PAGE_HEADER};
+use super::page_header::PageType;
+
+const OFFSET_PREV_PAGE_ID: usize = SIZE_B_PLUS_TREE_PAGE_HEADER;
+const OFFSET_NEXT_PAGE_ID: usize = OFFSET_PREV_PAGE_ID + size_of::<PageId>();
+
+/// Size of the leaf page header, i.e. offset of the entry array.
+pub const SIZE_LEAF_PAGE_HEADER: usize = OFFSET_NEXT_PAGE_ID + size_of::<PageId>();
+/// Size of a serialized `RID`.
+const SIZE_RID: usize = size_of::<PageId>() + size_of::<u32>();
+
+/// Return the number of entries that fit in a leaf page with keys of type `K`.
+pub fn leaf_page_capacity<K: IndexKey>() -> usize {
+    (BUSTUB_PAGE_SIZE - SIZE_LEAF_PAGE_HEADER) / (K::SIZE + SIZE_RID)
+}
+
+/// BPlusTreeLeafPage is the layout of B+ tree leaves, viewed over the data of a `Page`: the
+/// `BPlusTreePage` header and the ids of the previous and next leaves, followed by (key, RID)
+/// entries sorted by key.
+///
+/// ```text
+///  -----------------------------------------------------------------------------------
+/// | HEADER | PREV PAGE ID | NEXT PAGE ID | KEY(1) + RID(1) | ... | KEY(n) + RID(n) |
+///  -----------------------------------------------------------------------------------
+/// ```
+#[derive(Debug)]
+pub struct BPlusTreeLeafPage<D, K> {
+    data: D,
+    _key: PhantomData<K>,
+}
+
+impl<D: AsRef<[u8]>, K: IndexKey> BPlusTreeLeafPage<D, K> {
+    /// View page data as a leaf page.
+    pub fn new(data: D) -> Self {
+        BPlusTreeLeafPage {
+            data,
+            _key: PhantomData,
+        }
+    }
+
+    /// Return the id of the page.
+    pub fn get_page_id(&self) -> PageId {
+        self.header().get_page_id()
+    }
+
+    /// Return the number of entries.
+    pub fn get_size(&self) -> usize {
+        self.header().get_size()
+    }
+
+    /// Return the number of entries above which the leaf is split.
+    pub fn get_max_size(&self) -> usize {
+        self.header().get_max_size()
+    }
+
+    /// Return the number of entries below which the leaf is rebalanced, unless it is the root.
+    pub fn get_min_size(&self) -> usize {
+        self.header().get_min_size()
+    }
+
+    /// Return the id of the previous leaf, or `INVALID_PAGE_ID` for the first one.
+    pub fn get_prev_page_id(&self) -> PageId {
+        PageId::from_le_bytes(self.read::<4>(OFFSET_PREV_PAGE_ID))
+    }
+
+    /// Return the id of the next leaf, or `INVALID_PAGE_ID` for the last one.
+    pub fn get_next_page_id(&self) -> PageId {
+        PageId::from_le_bytes(self.read::<4>(OFFSET_NEXT_PAGE_ID))
+    }
+
+    /// Return the key of an entry.
+    pub fn key_at(&self, index: usize) -> K {
+        let offset = Self::entry_offset(index);
+        K::deserialize(&self.data.as_ref()[offset..offset + K::SIZE])
+    }
+
+    /// Return the RID of an entry.
+    pub fn value_at(&self, index: usize) -> RID {
+        let offset = Self::entry_offset(index) + K::SIZE;
+        RID::new(
+            PageId::from_le_bytes(self.read::<4>(offset)),
+            u32::from_le_bytes(self.read::<4>(offset + size_of::<PageId>())) as SlotOffset,
+        )
+    }
+
+    /// Search the key: return `Ok` with its index if present, or `Err` with the index it would
+    /// be inserted at.
+    pub fn lookup(&self, key: &K) -> Result<usize, usize> {
+        let (mut lo, mut hi) = (0, self.get_size());
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            match self.key_at(mid).cmp(key) {
+                std::cmp::Ordering::Less => lo = mid + 1,
+                std::cmp::Ordering::Greater => hi = mid,
+                std::cmp::Ordering::Equal => return Ok(mid),
+            }
+        }
+        Err(lo)
+    }
+
+    /// Return every entry, in key order.
+    pub fn get_entries(&self) -> Vec<(K, RID)> {
+        (0..self.get_size())
+            .map(|index| (self.key_at(index), self.value_at(index)))
+            .collect()
+    }
+
+    fn header(&self) -> BPlusTreePage<&[u8]> {
+        BPlusTreePage::new(self.data.as_ref())
+    }
+
+    fn entry_offset(index: usize) -> usize {
+        SIZE_LEAF_PAGE_HEADER + index * (K::SIZE + SIZE_RID)
+    }
+
+    fn read<const N: usize>(&self, offset: usize) -> [u8; N] {
+        self.data.as_ref()[offset..offset + N].try_into().unwrap()
+    }
+}
+
+impl<D: AsRef<[u8]> + AsMut<[u8]>, K: IndexKey> BPlusTreeLeafPage<D, K> {
+    /// Initialize an empty leaf.
+    pub fn init(&mut self, page_id: PageId, max_size: usize) {
+        assert!(
+            max_size < leaf_page_capacity::<K>(),
+            "leaf max size {} leaves no room to split",
+            max_size
+        );
+        BPlusTreePage::new(self.data.as_mut()).init(page_id, PageType::BPlusTreeLeaf, max_size);
+        self.set_prev_page_id(INVALID_PAGE_ID);
+        self.set_next_page_id(INVALID_PAGE_ID);
+    }
+
+    /// Set the id of the previous leaf.
+    pub fn set_prev_page_id(&mut self, prev_page_id: PageId) {
+        self.data.as_mut()[OFFSET_PREV_PAGE_ID..OFFSET_PREV_PAGE_ID + 4]
+            .copy_from_slice(&prev_page_id.to_le_bytes());
+    }
+
+    /// Set the id of the next leaf.
+    pub fn set_next_page_id(&mut self, next_page_id: PageId) {
+        self.data.as_mut()[OFFSET_NEXT_PAGE_ID..OFFSET_NEXT_PAGE_ID + 4]
+            .copy_from_slice(&next_page_id.to_le_bytes());
+    }
+
+    /// Insert an entry at `index`, shifting the following entries right. The page may hold one
+    /// entry more than its max size until it is split.
+    pub fn insert_at(&mut self, index: usize, key: &K, value: RID) {
+        let size = self.get_size();
+        assert!(index <= size && size < leaf_page_capacity::<K>());
+        self.data.as_mut().copy_within(
+            Self::entry_offset(index)..Self::entry_offset(size),
+            Self::entry_offset(index + 1),
+        );
+        self.write_entry(index, key, value);
+        self.set_size(size + 1);
+    }
+
+    /// Remove the entry at `index`, shifting the following entries left.
+    pub fn remove_at(&mut self, index: usize) {
+        let size = self.get_size();
+        assert!(index < size);
+        self.data.as_mut().copy_within(
+            Self::entry_offset(index + 1)..Self::entry_offset(size),
+            Self::entry_offset(index),
+        );
+        self.set_size(size - 1);
+    }
+
+    /// Replace all entries.
+    pub fn set_entries(&mut self, entries: &[(K, RID)]) {
+        assert!(entries.len() < leaf_page_capacity::<K>());
+        for (index, (key, value)) in entries.iter().enumerate() {
+            self.write_entry(index, key, *value);
+        }
+        self.set_size(entries.len());
+    }
+
+    fn write_entry(&mut self, index: usize, key: &K, value: RID) {
+        let offset = Self::entry_offset(index);
+        let data = self.data.as_mut();
+        key.serialize(&mut data[offset..offset + K::SIZE]);
+        let offset = offset + K::SIZE;
+        data[offset..offset + 4].copy_from_slice(&value.page_id.to_le_bytes());
+        data[offset + 4..offset + 8].copy_from_slice(&(value.slot as u32).to_le_bytes());
+    }
+
+    fn set_size(&mut self, size: usize) {
+        BPlusTreePage::new(self.data.as_mut()).set_size(size);
+    }
+}
@@ -0,0 +1,80 @@
+use std::mem::size_of;
+
+use crate::utils::config::*;
+
+use super::page::SIZE_PAGE_HEADER;
+use super::page_header::{PageHeader, PageType};
+
+const OFFSET_SIZE: usize = SIZE_PAGE_HEADER;
+const OFFSET_MAX_SIZE: usize = OFFSET_SIZE + size_of::<u32>();
+
+/// Size of the header shared by B+ tree leaf and internal pages.
+pub const SIZE_B_PLUS_TREE_PAGE_HEADER: usize = OFFSET_MAX_SIZE + size_of::<u32>();
+
+/// BPlusTreePage is the header shared by `BPlusTreeLeafPage` and `BPlusTreeInternalPage`, viewed
+/// over the data of a `Page`: common page header | size | max size.
+///
+/// The page type in the common header tells leaves from internal pages. The size counts the
+/// key/value pairs of a leaf, or the children of an internal page.
+#[derive(Debug)]
+pub struct BPlusTreePage<D> {
+    data: D,
+}
+
+impl<D: AsRef<[u8]>> BPlusTreePage<D> {
+    /// View page data as a B+ tree page.
+    pub fn new(data: D) -> Self {
+        BPlusTreePage { data }
+    }
+
+    /// Return the id of the page.
+    pub fn get_page_id(&self) -> PageId {
+        PageHeader::new(self.data.as_ref()).get_page_id()
+    }
+
+    /// Return true if the page is a leaf.
+    pub fn is_leaf(&self) -> bool {
+        PageHeader::new(self.data.as_ref()).get_page_type() == Some(PageType::BPlusTreeLeaf)
+    }
+
+    /// Return the number of entries of the page.
+    pub fn get_size(&self) -> usize {
+        self.read_u32(OFFSET_SIZE) as usize
+    }
+
+    /// Return the number of entries above which the page is split.
+    pub fn get_max_size(&self) -> usize {
+        self.read_u32(OFFSET_MAX_SIZE) as usize
+    }
+
+    /// Return the number of entries below which a page other than the root is rebalanced.
+    pub fn get_min_size(&self) -> usize {
+        if self.is_leaf() {
+            self.get_max_size() / 2
+        } else {
+            self.get_max_size().div_ceil(2)
+        }
+    }
+
+    fn read_u32(&self, offset: usize) -> u32 {
+        u32::from_le_bytes(self.data.as_ref()[offset..offset + 4].try_into().unwrap())
+    }
+}
+
+impl<D: AsRef<[u8]> + AsMut<[u8]>> BPlusTreePage<D> {
+    /// Format the header of a new B+ tree page.
+    pub fn init(&mut self, page_id: PageId, page_type: PageType, max_size: usize) {
+        PageHeader::new(self.data.as_mut()).init(page_id, page_type);
+        self.set_size(0);
+        self.write_u32(OFFSET_MAX_SIZE, max_size as u32);
+    }
+
+    /// Set the number of entries of the page.
+    pub fn set_size(&mut self, size: usize) {
+        self.write_u32(OFFSET_SIZE, size as u32);
+    }
+
+    fn write_u32(&mut self, offset: usize, value: u32) {
+        self.data.as_mut()[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+}
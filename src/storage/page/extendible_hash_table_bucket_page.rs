@@ -0,0 +1,202 @@
+use std::marker::PhantomData;
+use std::mem::size_of;
+
+use crate::storage::index::index_key::IndexKey;
+use crate::utils::config::*;
+use crate::utils::rid::RID;
+
+use super::page::SIZE_PAGE_HEADER;
+use super::page_header::{PageHeader, PageType};
+
+const OFFSET_SIZE: usize = SIZE_PAGE_HEADER;
+const OFFSET_MAX_SIZE: usize = OFFSET_SIZE + size_of::<u32>();
+
+/// Size of the bucket page header, i.e. offset of the entry array.
+pub const SIZE_BUCKET_PAGE_HEADER: usize = OFFSET_MAX_SIZE + size_of::<u32>();
+/// Size of a serialized `RID`.
+const SIZE_RID: usize = size_of::<PageId>() + size_of::<u32>();
+
+/// Return the number of entries a bucket page with keys of type `K` can hold: `BUCKET_SIZE`,
+/// or fewer if the keys are too large for that many to fit in a page.
+pub fn bucket_page_capacity<K: IndexKey>() -> usize {
+    BUCKET_SIZE.min((BUSTUB_PAGE_SIZE - SIZE_BUCKET_PAGE_HEADER) / (K::SIZE + SIZE_RID))
+}
+
+/// ExtendibleHashTableBucketPage is the layout of the buckets of an `ExtendibleHashTable`, viewed
+/// over the data of a `Page`: up to `max_size` (key, RID) entries, in no particular order. Keys
+/// are unique, unless entries are added with `insert_duplicate`.
+///
+/// ```text
+///  -------------------------------------------------------------------
+/// | HEADER | SIZE | MAX SIZE | KEY(1) + RID(1) | ... | KEY(n) + RID(n) |
+///  -------------------------------------------------------------------
+/// ```
+#[derive(Debug)]
+pub struct ExtendibleHashTableBucketPage<D, K> {
+    data: D,
+    _key: PhantomData<K>,
+}
+
+impl<D: AsRef<[u8]>, K: IndexKey> ExtendibleHashTableBucketPage<D, K> {
+    /// View page data as a hash table bucket page.
+    pub fn new(data: D) -> Self {
+        ExtendibleHashTableBucketPage {
+            data,
+            _key: PhantomData,
+        }
+    }
+
+    /// Return the number of entries.
+    pub fn get_size(&self) -> usize {
+        self.read_u32(OFFSET_SIZE) as usize
+    }
+
+    /// Return the number of entries the bucket holds when full.
+    pub fn get_max_size(&self) -> usize {
+        self.read_u32(OFFSET_MAX_SIZE) as usize
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.get_size() == self.get_max_size()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.get_size() == 0
+    }
+
+    /// Return the key of an entry.
+    pub fn key_at(&self, index: usize) -> K {
+        let offset = Self::entry_offset(index);
+        K::deserialize(&self.data.as_ref()[offset..offset + K::SIZE])
+    }
+
+    /// Return the RID of an entry.
+    pub fn value_at(&self, index: usize) -> RID {
+        let offset = Self::entry_offset(index) + K::SIZE;
+        RID::new(
+            PageId::from_le_bytes(self.read::<4>(offset)),
+            self.read_u32(offset + size_of::<PageId>()) as SlotOffset,
+        )
+    }
+
+    /// Return the index of the entry of `key`, if present.
+    pub fn lookup(&self, key: &K) -> Option<usize> {
+        (0..self.get_size()).find(|&index| self.key_at(index) == *key)
+    }
+
+    /// Return the index of the entry of `key` with RID `value`, if present.
+    pub fn lookup_entry(&self, key: &K, value: RID) -> Option<usize> {
+        (0..self.get_size())
+            .find(|&index| self.key_at(index) == *key && self.value_at(index) == value)
+    }
+
+    /// Return the RIDs of every entry of `key`.
+    pub fn get_values(&self, key: &K) -> Vec<RID> {
+        (0..self.get_size())
+            .filter(|&index| self.key_at(index) == *key)
+            .map(|index| self.value_at(index))
+            .collect()
+    }
+
+    /// Return every entry.
+    pub fn get_entries(&self) -> Vec<(K, RID)> {
+        (0..self.get_size())
+            .map(|index| (self.key_at(index), self.value_at(index)))
+            .collect()
+    }
+
+    fn entry_offset(index: usize) -> usize {
+        SIZE_BUCKET_PAGE_HEADER + index * (K::SIZE + SIZE_RID)
+    }
+
+    fn read_u32(&self, offset: usize) -> u32 {
+        u32::from_le_bytes(self.read::<4>(offset))
+    }
+
+    fn read<const N: usize>(&self, offset: usize) -> [u8; N] {
+        self.data.as_ref()[offset..offset + N].try_into().unwrap()
+    }
+}
+
+impl<D: AsRef<[u8]> + AsMut<[u8]>, K: IndexKey> ExtendibleHashTableBucketPage<D, K> {
+    /// Initialize an empty bucket.
+    pub fn init(&mut self, page_id: PageId, max_size: usize) {
+        assert!(
+            (1..=bucket_page_capacity::<K>()).contains(&max_size),
+            "bucket max size must be in 1..={}",
+            bucket_page_capacity::<K>()
+        );
+        PageHeader::new(self.data.as_mut()).init(page_id, PageType::HashBucket);
+        self.write_u32(OFFSET_SIZE, 0);
+        self.write_u32(OFFSET_MAX_SIZE, max_size as u32);
+    }
+
+    /// Add an entry. Return false if the key is present or the bucket is full.
+    pub fn insert(&mut self, key: &K, value: RID) -> bool {
+        if self.is_full() || self.lookup(key).is_some() {
+            return false;
+        }
+        self.push_entry(key, value);
+        true
+    }
+
+    /// Add an entry, even if the key is present with other RIDs. Return false if the key is
+    /// present with `value` or the bucket is full.
+    pub fn insert_duplicate(&mut self, key: &K, value: RID) -> bool {
+        if self.is_full() || self.lookup_entry(key, value).is_some() {
+            return false;
+        }
+        self.push_entry(key, value);
+        true
+    }
+
+    /// Remove the entry of `key`. Return false if it is not present.
+    pub fn remove(&mut self, key: &K) -> bool {
+        match self.lookup(key) {
+            Some(index) => {
+                self.remove_at(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Remove the entry at `index`, moving the last entry in its place.
+    pub fn remove_at(&mut self, index: usize) {
+        let size = self.get_size();
+        assert!(index < size);
+        self.data.as_mut().copy_within(
+            Self::entry_offset(size - 1)..Self::entry_offset(size),
+            Self::entry_offset(index),
+        );
+        self.write_u32(OFFSET_SIZE, size as u32 - 1);
+    }
+
+    /// Replace all entries.
+    pub fn set_entries(&mut self, entries: &[(K, RID)]) {
+        assert!(entries.len() <= self.get_max_size());
+        for (index, (key, value)) in entries.iter().enumerate() {
+            self.write_entry(index, key, *value);
+        }
+        self.write_u32(OFFSET_SIZE, entries.len() as u32);
+    }
+
+    fn push_entry(&mut self, key: &K, value: RID) {
+        let size = self.get_size();
+        self.write_entry(size, key, value);
+        self.write_u32(OFFSET_SIZE, size as u32 + 1);
+    }
+
+    fn write_entry(&mut self, index: usize, key: &K, value: RID) {
+        let offset = Self::entry_offset(index);
+        let data = self.data.as_mut();
+        key.serialize(&mut data[offset..offset + K::SIZE]);
+        let offset = offset + K::SIZE;
+        data[offset..offset + 4].copy_from_slice(&value.page_id.to_le_bytes());
+        data[offset + 4..offset + 8].copy_from_slice(&(value.slot as u32).to_le_bytes());
+    }
+
+    fn write_u32(&mut self, offset: usize, value: u32) {
+        self.data.as_mut()[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+}
@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+use std::mem::size_of;
+
+use crate::utils::config::*;
+
+use super::page::SIZE_PAGE_HEADER;
+use super::page_header::{PageHeader, PageType};
+
+const OFFSET_MAX_DEPTH: usize = SIZE_PAGE_HEADER;
+const OFFSET_GLOBAL_DEPTH: usize = OFFSET_MAX_DEPTH + size_of::<u32>();
+const OFFSET_LOCAL_DEPTHS: usize = OFFSET_GLOBAL_DEPTH + size_of::<u32>();
+const OFFSET_BUCKET_PAGE_IDS: usize = OFFSET_LOCAL_DEPTHS + HASH_TABLE_DIRECTORY_ARRAY_SIZE;
+
+/// Largest max depth of a hash table directory page, so that its slots fit in a page.
+pub const HASH_TABLE_DIRECTORY_MAX_DEPTH: u32 = 9;
+/// Number of slots a directory page has room for.
+const HASH_TABLE_DIRECTORY_ARRAY_SIZE: usize = 1 << HASH_TABLE_DIRECTORY_MAX_DEPTH;
+
+const _: () = assert!(
+    OFFSET_BUCKET_PAGE_IDS + HASH_TABLE_DIRECTORY_ARRAY_SIZE * size_of::<PageId>()
+        <= BUSTUB_PAGE_SIZE
+);
+
+/// ExtendibleHashTableDirectoryPage is the layout of the directories of an `ExtendibleHashTable`,
+/// viewed over the data of a `Page`. A directory maps the low `global_depth` bits of a hash to a
+/// bucket page; its `2^global_depth` slots grow up to `2^max_depth`.
+///
+/// Several slots point to the same bucket when the bucket's local depth is below the global depth:
+/// a bucket of local depth `d` holds the keys whose low `d` hash bits match, and is pointed to by
+/// the `2^(global_depth - d)` slots sharing those bits.
+///
+/// ```text
+///  --------------------------------------------------------------------------------
+/// | HEADER | MAX DEPTH | GLOBAL DEPTH | LOCAL DEPTH(0) ... (511) | BUCKET PAGE ID(0) ... (511) |
+///  --------------------------------------------------------------------------------
+/// ```
+#[derive(Debug)]
+pub struct ExtendibleHashTableDirectoryPage<D> {
+    data: D,
+}
+
+impl<D: AsRef<[u8]>> ExtendibleHashTableDirectoryPage<D> {
+    /// View page data as a hash table directory page.
+    pub fn new(data: D) -> Self {
+        ExtendibleHashTableDirectoryPage { data }
+    }
+
+    /// Return the global depth the directory can grow to.
+    pub fn get_max_depth(&self) -> u32 {
+        u32::from_le_bytes(self.read::<4>(OFFSET_MAX_DEPTH))
+    }
+
+    /// Return the number of hash bits used to pick a slot.
+    pub fn get_global_depth(&self) -> u32 {
+        u32::from_le_bytes(self.read::<4>(OFFSET_GLOBAL_DEPTH))
+    }
+
+    /// Return a mask of the low `global_depth` bits.
+    pub fn get_global_depth_mask(&self) -> u32 {
+        (1 << self.get_global_depth()) - 1
+    }
+
+    /// Return the number of slots in use.
+    pub fn get_size(&self) -> usize {
+        1 << self.get_global_depth()
+    }
+
+    /// Return the number of slots the directory can grow to.
+    pub fn get_max_size(&self) -> usize {
+        1 << self.get_max_depth()
+    }
+
+    /// Return the slot a hash falls in.
+    pub fn hash_to_bucket_index(&self, hash: u32) -> usize {
+        (hash & self.get_global_depth_mask()) as usize
+    }
+
+    /// Return the id of the bucket page of a slot.
+    pub fn get_bucket_page_id(&self, bucket_index: usize) -> PageId {
+        assert!(bucket_index < self.get_size());
+        PageId::from_le_bytes(self.read::<4>(Self::bucket_page_id_offset(bucket_index)))
+    }
+
+    /// Return the local depth of the bucket of a slot.
+    pub fn get_local_depth(&self, bucket_index: usize) -> u32 {
+        assert!(bucket_index < self.get_size());
+        self.data.as_ref()[OFFSET_LOCAL_DEPTHS + bucket_index] as u32
+    }
+
+    /// Return a mask of the low `local_depth` bits of the bucket of a slot.
+    pub fn get_local_depth_mask(&self, bucket_index: usize) -> u32 {
+        (1 << self.get_local_depth(bucket_index)) - 1
+    }
+
+    /// Return the slot of the bucket that the bucket of `bucket_index` was split from, or would
+    /// merge with: the one differing in the highest of its local depth bits. The local depth must
+    /// not be 0.
+    pub fn get_split_image_index(&self, bucket_index: usize) -> usize {
+        let local_depth = self.get_local_depth(bucket_index);
+        assert!(
+            local_depth > 0,
+            "a bucket of local depth 0 has no split image"
+        );
+        bucket_index ^ (1 << (local_depth - 1))
+    }
+
+    /// Return true if every local depth is below the global depth, so that the directory can be
+    /// halved.
+    pub fn can_shrink(&self) -> bool {
+        let global_depth = self.get_global_depth();
+        global_depth > 0
+            && (0..self.get_size()).all(|index| self.get_local_depth(index) < global_depth)
+    }
+
+    /// Check the directory invariants, panicking on a violation:
+    ///
+    /// - Every local depth is at most the global depth.
+    /// - The slots of a bucket all have its local depth, agree on its low `local_depth` bits, and
+    ///   number `2^(global_depth - local_depth)`.
+    pub fn verify_integrity(&self) {
+        let global_depth = self.get_global_depth();
+        assert!(global_depth <= self.get_max_depth());
+        let mut buckets: HashMap<PageId, Vec<usize>> = HashMap::new();
+        for index in 0..self.get_size() {
+            assert!(self.get_local_depth(index) <= global_depth);
+            buckets
+                .entry(self.get_bucket_page_id(index))
+                .or_default()
+                .push(index);
+        }
+        for (page_id, indexes) in buckets {
+            let local_depth = self.get_local_depth(indexes[0]);
+            let mask = self.get_local_depth_mask(indexes[0]) as usize;
+            assert_eq!(
+                indexes.len(),
+                1 << (global_depth - local_depth),
+                "bucket page {} has {} slots at local depth {}",
+                page_id,
+                indexes.len(),
+                local_depth
+            );
+            for &index in &indexes {
+                assert_eq!(self.get_local_depth(index), local_depth);
+                assert_eq!(index & mask, indexes[0] & mask);
+            }
+        }
+    }
+
+    fn bucket_page_id_offset(bucket_index: usize) -> usize {
+        OFFSET_BUCKET_PAGE_IDS + bucket_index * size_of::<PageId>()
+    }
+
+    fn read<const N: usize>(&self, offset: usize) -> [u8; N] {
+        self.data.as_ref()[offset..offset + N].try_into().unwrap()
+    }
+}
+
+impl<D: AsRef<[u8]> + AsMut<[u8]>> ExtendibleHashTableDirectoryPage<D> {
+    /// Initialize a directory of global depth 0 whose only slot points to `bucket_page_id`.
+    pub fn init(&mut self, page_id: PageId, max_depth: u32, bucket_page_id: PageId) {
+        assert!(
+            max_depth <= HASH_TABLE_DIRECTORY_MAX_DEPTH,
+            "directory max depth {} is above {}",
+            max_depth,
+            HASH_TABLE_DIRECTORY_MAX_DEPTH
+        );
+        PageHeader::new(self.data.as_mut()).init(page_id, PageType::HashDirectory);
+        self.write_u32(OFFSET_MAX_DEPTH, max_depth);
+        self.write_u32(OFFSET_GLOBAL_DEPTH, 0);
+        self.set_bucket_page_id(0, bucket_page_id);
+        self.set_local_depth(0, 0);
+    }
+
+    /// Set the id of the bucket page of a slot.
+    pub fn set_bucket_page_id(&mut self, bucket_index: usize, page_id: PageId) {
+        assert!(bucket_index < self.get_size());
+        let offset = Self::bucket_page_id_offset(bucket_index);
+        self.data.as_mut()[offset..offset + 4].copy_from_slice(&page_id.to_le_bytes());
+    }
+
+    /// Set the local depth of the bucket of a slot.
+    pub fn set_local_depth(&mut self, bucket_index: usize, local_depth: u32) {
+        assert!(bucket_index < self.get_size() && local_depth <= self.get_global_depth());
+        self.data.as_mut()[OFFSET_LOCAL_DEPTHS + bucket_index] = local_depth as u8;
+    }
+
+    /// Double the directory: the new upper half of the slots mirrors the lower half. Return false
+    /// if the directory is at its max depth.
+    pub fn incr_global_depth(&mut self) -> bool {
+        let size = self.get_size();
+        if size == self.get_max_size() {
+            return false;
+        }
+        let data = self.data.as_mut();
+        data.copy_within(
+            OFFSET_LOCAL_DEPTHS..OFFSET_LOCAL_DEPTHS + size,
+            OFFSET_LOCAL_DEPTHS + size,
+        );
+        data.copy_within(
+            Self::bucket_page_id_offset(0)..Self::bucket_page_id_offset(size),
+            Self::bucket_page_id_offset(size),
+        );
+        let global_depth = self.get_global_depth();
+        self.write_u32(OFFSET_GLOBAL_DEPTH, global_depth + 1);
+        true
+    }
+
+    /// Halve the directory, dropping the upper half of the slots. The directory must be able to
+    /// shrink.
+    pub fn decr_global_depth(&mut self) {
+        assert!(self.can_shrink());
+        let global_depth = self.get_global_depth();
+        self.write_u32(OFFSET_GLOBAL_DEPTH, global_depth - 1);
+    }
+
+    fn write_u32(&mut self, offset: usize, value: u32) {
+        self.data.as_mut()[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+}
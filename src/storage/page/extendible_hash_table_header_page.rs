@@ -0,0 +1,95 @@
+use std::mem::size_of;
+
+use crate::utils::config::*;
+
+use super::page::SIZE_PAGE_HEADER;
+use super::page_header::{PageHeader, PageType};
+
+const OFFSET_MAX_DEPTH: usize = SIZE_PAGE_HEADER;
+const OFFSET_DIRECTORY_PAGE_IDS: usize = OFFSET_MAX_DEPTH + size_of::<u32>();
+
+/// Largest max depth of a hash table header page, so that its directory page ids fit in a page.
+pub const HASH_TABLE_HEADER_MAX_DEPTH: u32 = 9;
+
+const _: () = assert!(
+    OFFSET_DIRECTORY_PAGE_IDS + (size_of::<PageId>() << HASH_TABLE_HEADER_MAX_DEPTH)
+        <= BUSTUB_PAGE_SIZE
+);
+
+/// ExtendibleHashTableHeaderPage is the root page of an `ExtendibleHashTable`, viewed over the
+/// data of a `Page`. It spreads keys over up to `2^max_depth` directory pages by the top
+/// `max_depth` bits of their hash.
+///
+/// ```text
+///  -----------------------------------------------------------------
+/// | HEADER | MAX DEPTH | DIRECTORY PAGE ID(0) | ... | DIRECTORY PAGE ID(2^max_depth - 1) |
+///  -----------------------------------------------------------------
+/// ```
+#[derive(Debug)]
+pub struct ExtendibleHashTableHeaderPage<D> {
+    data: D,
+}
+
+impl<D: AsRef<[u8]>> ExtendibleHashTableHeaderPage<D> {
+    /// View page data as a hash table header page.
+    pub fn new(data: D) -> Self {
+        ExtendibleHashTableHeaderPage { data }
+    }
+
+    /// Return the number of hash bits used to pick a directory.
+    pub fn get_max_depth(&self) -> u32 {
+        u32::from_le_bytes(self.read::<4>(OFFSET_MAX_DEPTH))
+    }
+
+    /// Return the number of directory slots.
+    pub fn get_max_size(&self) -> usize {
+        1 << self.get_max_depth()
+    }
+
+    /// Return the index of the directory slot a hash falls in.
+    pub fn hash_to_directory_index(&self, hash: u32) -> usize {
+        match self.get_max_depth() {
+            0 => 0,
+            depth => (hash >> (u32::BITS - depth)) as usize,
+        }
+    }
+
+    /// Return the id of a directory page, or `INVALID_PAGE_ID` if it was not created yet.
+    pub fn get_directory_page_id(&self, directory_index: usize) -> PageId {
+        assert!(directory_index < self.get_max_size());
+        PageId::from_le_bytes(self.read::<4>(Self::directory_page_id_offset(directory_index)))
+    }
+
+    fn directory_page_id_offset(directory_index: usize) -> usize {
+        OFFSET_DIRECTORY_PAGE_IDS + directory_index * size_of::<PageId>()
+    }
+
+    fn read<const N: usize>(&self, offset: usize) -> [u8; N] {
+        self.data.as_ref()[offset..offset + N].try_into().unwrap()
+    }
+}
+
+impl<D: AsRef<[u8]> + AsMut<[u8]>> ExtendibleHashTableHeaderPage<D> {
+    /// Initialize a header page without directories.
+    pub fn init(&mut self, page_id: PageId, max_depth: u32) {
+        assert!(
+            max_depth <= HASH_TABLE_HEADER_MAX_DEPTH,
+            "header max depth {} is above {}",
+            max_depth,
+            HASH_TABLE_HEADER_MAX_DEPTH
+        );
+        PageHeader::new(self.data.as_mut()).init(page_id, PageType::HashHeader);
+        self.data.as_mut()[OFFSET_MAX_DEPTH..OFFSET_MAX_DEPTH + 4]
+            .copy_from_slice(&max_depth.to_le_bytes());
+        for directory_index in 0..self.get_max_size() {
+            self.set_directory_page_id(directory_index, INVALID_PAGE_ID);
+        }
+    }
+
+    /// Set the id of a directory page.
+    pub fn set_directory_page_id(&mut self, directory_index: usize, page_id: PageId) {
+        assert!(directory_index < self.get_max_size());
+        let offset = Self::directory_page_id_offset(directory_index);
+        self.data.as_mut()[offset..offset + 4].copy_from_slice(&page_id.to_le_bytes());
+    }
+}
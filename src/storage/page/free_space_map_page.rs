@@ -0,0 +1,178 @@
+use std::mem::size_of;
+
+use crate::utils::config::*;
+
+use super::page::SIZE_PAGE_HEADER;
+use super::page_header::{PageHeader, PageType};
+
+const OFFSET_ENTRY_COUNT: usize = SIZE_PAGE_HEADER;
+const OFFSET_MAX_SIZE: usize = OFFSET_ENTRY_COUNT + size_of::<u32>();
+const OFFSET_LEVEL: usize = OFFSET_MAX_SIZE + size_of::<u32>();
+
+/// Size of the free space map page header, i.e. offset of the entry array.
+pub const SIZE_FREE_SPACE_MAP_PAGE_HEADER: usize = OFFSET_LEVEL + size_of::<u32>();
+/// Size of an entry: page id and free space category.
+pub const SIZE_FREE_SPACE_MAP_ENTRY: usize = size_of::<PageId>() + size_of::<u8>();
+/// Number of entries that fit in a free space map page.
+pub const FREE_SPACE_MAP_PAGE_CAPACITY: usize =
+    (BUSTUB_PAGE_SIZE - SIZE_FREE_SPACE_MAP_PAGE_HEADER) / SIZE_FREE_SPACE_MAP_ENTRY;
+
+/// Bytes of free space represented by one step of a category.
+pub const FREE_SPACE_CATEGORY_STEP: usize = BUSTUB_PAGE_SIZE / 256;
+
+/// Return the category recording `free_bytes` of free space, rounding down so that a page in
+/// category `c` is guaranteed to have at least `c * FREE_SPACE_CATEGORY_STEP` free bytes.
+pub fn free_space_category(free_bytes: usize) -> u8 {
+    (free_bytes / FREE_SPACE_CATEGORY_STEP).min(u8::MAX as usize) as u8
+}
+
+/// Return the smallest category whose pages are guaranteed to have `needed_bytes` free.
+pub fn required_category(needed_bytes: usize) -> usize {
+    needed_bytes.div_ceil(FREE_SPACE_CATEGORY_STEP)
+}
+
+/// FreeSpaceMapPage is the layout of the pages of a free space map, viewed over the data of a
+/// `Page`: the entry count, the max number of entries, the level of the page, and an array of
+/// (page id, category) entries.
+///
+/// In a leaf page (level 0) every entry records the approximate free space of a heap page. In an
+/// inner page every entry is a child page one level below along with the largest category it
+/// holds.
+#[derive(Debug)]
+pub struct FreeSpaceMapPage<D> {
+    data: D,
+}
+
+impl<D: AsRef<[u8]>> FreeSpaceMapPage<D> {
+    /// View page data as a free space map page.
+    pub fn new(data: D) -> Self {
+        FreeSpaceMapPage { data }
+    }
+
+    /// Return the number of entries.
+    pub fn get_entry_count(&self) -> usize {
+        u32::from_le_bytes(self.read::<4>(OFFSET_ENTRY_COUNT)) as usize
+    }
+
+    /// Return the max number of entries of the page.
+    pub fn get_max_size(&self) -> usize {
+        u32::from_le_bytes(self.read::<4>(OFFSET_MAX_SIZE)) as usize
+    }
+
+    /// Return the level of the page: 0 for a leaf, one more than its children otherwise.
+    pub fn get_level(&self) -> u32 {
+        u32::from_le_bytes(self.read::<4>(OFFSET_LEVEL))
+    }
+
+    /// Return true if the entries are heap pages rather than child pages.
+    pub fn is_leaf(&self) -> bool {
+        self.get_level() == 0
+    }
+
+    /// Return true if no more entries fit in the page.
+    pub fn is_full(&self) -> bool {
+        self.get_entry_count() >= self.get_max_size()
+    }
+
+    /// Return the page id and category of an entry.
+    pub fn get_entry(&self, index: usize) -> (PageId, u8) {
+        assert!(
+            index < self.get_entry_count(),
+            "invalid entry index {}",
+            index
+        );
+        let offset = Self::entry_offset(index);
+        (
+            PageId::from_le_bytes(self.read::<4>(offset)),
+            self.data.as_ref()[offset + size_of::<PageId>()],
+        )
+    }
+
+    /// Return all entries, in order.
+    pub fn get_entries(&self) -> Vec<(PageId, u8)> {
+        (0..self.get_entry_count())
+            .map(|index| self.get_entry(index))
+            .collect()
+    }
+
+    /// Return the index of the first entry with at least the given category.
+    pub fn find_entry(&self, category: usize) -> Option<usize> {
+        (0..self.get_entry_count()).find(|&index| self.get_entry(index).1 as usize >= category)
+    }
+
+    /// Return the largest category of all entries.
+    pub fn get_max_category(&self) -> u8 {
+        (0..self.get_entry_count())
+            .map(|index| self.get_entry(index).1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn entry_offset(index: usize) -> usize {
+        SIZE_FREE_SPACE_MAP_PAGE_HEADER + index * SIZE_FREE_SPACE_MAP_ENTRY
+    }
+
+    fn read<const N: usize>(&self, offset: usize) -> [u8; N] {
+        self.data.as_ref()[offset..offset + N].try_into().unwrap()
+    }
+}
+
+impl<D: AsRef<[u8]> + AsMut<[u8]>> FreeSpaceMapPage<D> {
+    /// Initialize an empty free space map page holding up to `max_size` entries at `level`.
+    pub fn init(&mut self, page_id: PageId, max_size: usize, level: u32) {
+        assert!(
+            (1..=FREE_SPACE_MAP_PAGE_CAPACITY).contains(&max_size),
+            "free space map page max size must be in 1..={}",
+            FREE_SPACE_MAP_PAGE_CAPACITY
+        );
+        PageHeader::new(self.data.as_mut()).init(page_id, PageType::FreeSpaceMap);
+        self.set_entry_count(0);
+        self.data.as_mut()[OFFSET_MAX_SIZE..OFFSET_MAX_SIZE + 4]
+            .copy_from_slice(&(max_size as u32).to_le_bytes());
+        self.set_level(level);
+    }
+
+    /// Set the level of the page.
+    pub fn set_level(&mut self, level: u32) {
+        self.data.as_mut()[OFFSET_LEVEL..OFFSET_LEVEL + 4].copy_from_slice(&level.to_le_bytes());
+    }
+
+    /// Replace all entries. They must fit in the page.
+    pub fn set_entries(&mut self, entries: &[(PageId, u8)]) {
+        assert!(
+            entries.len() <= self.get_max_size(),
+            "too many free space map entries"
+        );
+        self.set_entry_count(entries.len());
+        for (index, &(page_id, category)) in entries.iter().enumerate() {
+            self.set_entry(index, page_id, category);
+        }
+    }
+
+    /// Append an entry and return its index. The page must not be full.
+    pub fn push_entry(&mut self, page_id: PageId, category: u8) -> usize {
+        assert!(!self.is_full(), "free space map page is full");
+        let index = self.get_entry_count();
+        self.set_entry_count(index + 1);
+        self.set_entry(index, page_id, category);
+        index
+    }
+
+    /// Overwrite the category of an entry.
+    pub fn set_category(&mut self, index: usize, category: u8) {
+        let (page_id, _) = self.get_entry(index);
+        self.set_entry(index, page_id, category);
+    }
+
+    fn set_entry(&mut self, index: usize, page_id: PageId, category: u8) {
+        let offset = Self::entry_offset(index);
+        let data = self.data.as_mut();
+        data[offset..offset + size_of::<PageId>()].copy_from_slice(&page_id.to_le_bytes());
+        data[offset + size_of::<PageId>()] = category;
+    }
+
+    fn set_entry_count(&mut self, count: usize) {
+        self.data.as_mut()[OFFSET_ENTRY_COUNT..OFFSET_ENTRY_COUNT + 4]
+            .copy_from_slice(&(count as u32).to_le_bytes());
+    }
+}
@@ -0,0 +1,184 @@
+use std::mem::size_of;
+
+use crate::buffer::buffer_pool::BufferPool;
+use crate::utils::config::*;
+
+use super::page::SIZE_PAGE_HEADER;
+use super::page_header::{PageHeader, PageType};
+
+const OFFSET_RECORD_COUNT: usize = SIZE_PAGE_HEADER;
+
+/// Size of the header page header, i.e. offset of the record array.
+pub const SIZE_HEADER_PAGE_HEADER: usize = OFFSET_RECORD_COUNT + size_of::<u32>();
+/// Longest name of a record, in bytes.
+pub const MAX_RECORD_NAME_SIZE: usize = 32;
+/// Size of a record: name and root page id.
+pub const SIZE_HEADER_RECORD: usize = MAX_RECORD_NAME_SIZE + size_of::<PageId>();
+/// Number of records that fit in the header page.
+pub const HEADER_PAGE_CAPACITY: usize =
+    (BUSTUB_PAGE_SIZE - SIZE_HEADER_PAGE_HEADER) / SIZE_HEADER_RECORD;
+
+/// HeaderPage is the layout of the directory page stored at `HEADER_PAGE_ID`, viewed over the
+/// data of a `Page`. It records the root page id of every persistent structure (table heaps,
+/// index roots, the catalog, ...) by name, so that they can be found again after reopening the
+/// database.
+///
+/// Header: common page header | record count, followed by the records. Every record stores a
+/// name of at most `MAX_RECORD_NAME_SIZE` bytes, padded with zeros, and a root page id.
+#[derive(Debug)]
+pub struct HeaderPage<D> {
+    data: D,
+}
+
+impl<D: AsRef<[u8]>> HeaderPage<D> {
+    /// View page data as the header page.
+    pub fn new(data: D) -> Self {
+        HeaderPage { data }
+    }
+
+    /// Return true if the page has been initialized as the header page. Structures recorded in
+    /// it must not trust a page that is not.
+    pub fn is_header_page(&self) -> bool {
+        let header = PageHeader::new(self.data.as_ref());
+        header.is_formatted()
+            && header.get_page_type() == Some(PageType::Header)
+            && header.get_page_id() == HEADER_PAGE_ID
+    }
+
+    /// Return the number of records.
+    pub fn get_record_count(&self) -> usize {
+        u32::from_le_bytes(self.read::<4>(OFFSET_RECORD_COUNT)) as usize
+    }
+
+    /// Return the root page id recorded under `name`.
+    pub fn get_root_id(&self, name: &str) -> Option<PageId> {
+        self.find_record(name)
+            .map(|index| PageId::from_le_bytes(self.read::<4>(Self::root_id_offset(index))))
+    }
+
+    /// Return every record as (name, root page id), in insertion order.
+    pub fn get_records(&self) -> Vec<(String, PageId)> {
+        (0..self.get_record_count())
+            .map(|index| {
+                let name = String::from_utf8_lossy(self.get_name(index)).into_owned();
+                let root_id = PageId::from_le_bytes(self.read::<4>(Self::root_id_offset(index)));
+                (name, root_id)
+            })
+            .collect()
+    }
+
+    fn find_record(&self, name: &str) -> Option<usize> {
+        (0..self.get_record_count()).find(|&index| self.get_name(index) == name.as_bytes())
+    }
+
+    fn get_name(&self, index: usize) -> &[u8] {
+        let offset = Self::record_offset(index);
+        let name = &self.data.as_ref()[offset..offset + MAX_RECORD_NAME_SIZE];
+        let len = name
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(MAX_RECORD_NAME_SIZE);
+        &name[..len]
+    }
+
+    fn record_offset(index: usize) -> usize {
+        SIZE_HEADER_PAGE_HEADER + index * SIZE_HEADER_RECORD
+    }
+
+    fn root_id_offset(index: usize) -> usize {
+        Self::record_offset(index) + MAX_RECORD_NAME_SIZE
+    }
+
+    fn read<const N: usize>(&self, offset: usize) -> [u8; N] {
+        self.data.as_ref()[offset..offset + N].try_into().unwrap()
+    }
+}
+
+impl<D: AsRef<[u8]> + AsMut<[u8]>> HeaderPage<D> {
+    /// Initialize an empty header page.
+    pub fn init(&mut self) {
+        PageHeader::new(self.data.as_mut()).init(HEADER_PAGE_ID, PageType::Header);
+        self.set_record_count(0);
+    }
+
+    /// Record `root_id` under `name`. Return false if the name is empty, too long or contains a
+    /// zero byte, is already recorded, or the page is full.
+    pub fn insert_record(&mut self, name: &str, root_id: PageId) -> bool {
+        let index = self.get_record_count();
+        if !is_valid_record_name(name)
+            || self.find_record(name).is_some()
+            || index == HEADER_PAGE_CAPACITY
+        {
+            return false;
+        }
+        let offset = Self::record_offset(index);
+        let data = self.data.as_mut();
+        data[offset..offset + MAX_RECORD_NAME_SIZE].fill(0);
+        data[offset..offset + name.len()].copy_from_slice(name.as_bytes());
+        self.set_root_id(index, root_id);
+        self.set_record_count(index + 1);
+        true
+    }
+
+    /// Change the root page id recorded under `name`, e.g. after a B+ tree root split. Return
+    /// false if there is no such record.
+    pub fn update_record(&mut self, name: &str, root_id: PageId) -> bool {
+        let Some(index) = self.find_record(name) else {
+            return false;
+        };
+        self.set_root_id(index, root_id);
+        true
+    }
+
+    /// Remove the record of `name`. Return false if there is no such record.
+    pub fn delete_record(&mut self, name: &str) -> bool {
+        let Some(index) = self.find_record(name) else {
+            return false;
+        };
+        // Later records move up to keep the array dense.
+        let count = self.get_record_count();
+        self.data.as_mut().copy_within(
+            Self::record_offset(index + 1)..Self::record_offset(count),
+            Self::record_offset(index),
+        );
+        self.set_record_count(count - 1);
+        true
+    }
+
+    fn set_root_id(&mut self, index: usize, root_id: PageId) {
+        let offset = Self::root_id_offset(index);
+        self.data.as_mut()[offset..offset + 4].copy_from_slice(&root_id.to_le_bytes());
+    }
+
+    fn set_record_count(&mut self, count: usize) {
+        self.data.as_mut()[OFFSET_RECORD_COUNT..OFFSET_RECORD_COUNT + 4]
+            .copy_from_slice(&(count as u32).to_le_bytes());
+    }
+}
+
+/// Return true if `name` can be recorded in the header page: it is not empty, has at most
+/// `MAX_RECORD_NAME_SIZE` bytes and no zero byte.
+pub fn is_valid_record_name(name: &str) -> bool {
+    !name.is_empty() && name.len() <= MAX_RECORD_NAME_SIZE && !name.as_bytes().contains(&0)
+}
+
+/// Make sure the database has its header page: allocate and initialize it if the database is new,
+/// in which case it must happen before any other page is allocated so that the header page gets
+/// `HEADER_PAGE_ID`. Return false if no frame is available, or if the new page got another id
+/// because other pages were allocated first; that page is deleted again.
+pub fn ensure_header_page(bpm: &dyn BufferPool) -> bool {
+    if bpm.is_page_allocated(HEADER_PAGE_ID) {
+        return true;
+    }
+    let Some(guard) = bpm.new_page_guarded() else {
+        return false;
+    };
+    let page_id = guard.page_id();
+    if page_id != HEADER_PAGE_ID {
+        drop(guard);
+        bpm.delete_page(page_id);
+        return false;
+    }
+    HeaderPage::new(guard.upgrade_write().get_data_mut()).init();
+    true
+}
@@ -1,2 +1,14 @@
+#[allow(clippy::module_inception)]
 pub mod page;
-pub mod page_guard;
\ No newline at end of file
+pub mod b_plus_tree_internal_page;
+pub mod b_plus_tree_leaf_page;
+pub mod b_plus_tree_page;
+pub mod extendible_hash_table_bucket_page;
+pub mod extendible_hash_table_directory_page;
+pub mod extendible_hash_table_header_page;
+pub mod free_space_map_page;
+pub mod header_page;
+pub mod overflow_page;
+pub mod page_guard;
+pub mod page_header;
+pub mod table_page;
@@ -0,0 +1,68 @@
+use std::mem::size_of;
+
+use crate::utils::config::*;
+
+use super::page::SIZE_PAGE_HEADER;
+use super::page_header::{PageHeader, PageType};
+
+const OFFSET_NEXT_PAGE_ID: usize = SIZE_PAGE_HEADER;
+const OFFSET_DATA_SIZE: usize = OFFSET_NEXT_PAGE_ID + size_of::<PageId>();
+
+/// Size of the overflow page header, i.e. offset of the data.
+pub const SIZE_OVERFLOW_PAGE_HEADER: usize = OFFSET_DATA_SIZE + size_of::<u32>();
+/// Number of bytes of a value stored in one overflow page.
+pub const OVERFLOW_PAGE_CAPACITY: usize = BUSTUB_PAGE_SIZE - SIZE_OVERFLOW_PAGE_HEADER;
+
+/// OverflowPage is the layout of the pages holding values stored out of line, viewed over the
+/// data of a `Page`. A value is split into chunks kept in a singly linked chain of overflow pages.
+///
+/// Header: common page header | next page id | data size, followed by the chunk.
+#[derive(Debug)]
+pub struct OverflowPage<D> {
+    data: D,
+}
+
+impl<D: AsRef<[u8]>> OverflowPage<D> {
+    /// View page data as an overflow page.
+    pub fn new(data: D) -> Self {
+        OverflowPage { data }
+    }
+
+    /// Return the id of the next page of the chain, or `INVALID_PAGE_ID` for the last one.
+    pub fn get_next_page_id(&self) -> PageId {
+        PageId::from_le_bytes(self.read::<4>(OFFSET_NEXT_PAGE_ID))
+    }
+
+    /// Return the chunk stored in the page.
+    pub fn get_chunk(&self) -> &[u8] {
+        let size = u32::from_le_bytes(self.read::<4>(OFFSET_DATA_SIZE)) as usize;
+        &self.data.as_ref()[SIZE_OVERFLOW_PAGE_HEADER..SIZE_OVERFLOW_PAGE_HEADER + size]
+    }
+
+    fn read<const N: usize>(&self, offset: usize) -> [u8; N] {
+        self.data.as_ref()[offset..offset + N].try_into().unwrap()
+    }
+}
+
+impl<D: AsRef<[u8]> + AsMut<[u8]>> OverflowPage<D> {
+    /// Initialize the page with a chunk of at most `OVERFLOW_PAGE_CAPACITY` bytes.
+    pub fn init(&mut self, page_id: PageId, next_page_id: PageId, chunk: &[u8]) {
+        assert!(
+            chunk.len() <= OVERFLOW_PAGE_CAPACITY,
+            "chunk does not fit in an overflow page"
+        );
+        PageHeader::new(self.data.as_mut()).init(page_id, PageType::Overflow);
+        self.set_next_page_id(next_page_id);
+        let data = self.data.as_mut();
+        data[OFFSET_DATA_SIZE..OFFSET_DATA_SIZE + 4]
+            .copy_from_slice(&(chunk.len() as u32).to_le_bytes());
+        data[SIZE_OVERFLOW_PAGE_HEADER..SIZE_OVERFLOW_PAGE_HEADER + chunk.len()]
+            .copy_from_slice(chunk);
+    }
+
+    /// Set the id of the next page of the chain.
+    pub fn set_next_page_id(&mut self, next_page_id: PageId) {
+        self.data.as_mut()[OFFSET_NEXT_PAGE_ID..OFFSET_NEXT_PAGE_ID + 4]
+            .copy_from_slice(&next_page_id.to_le_bytes());
+    }
+}
@@ -1,11 +1,16 @@
 use crate::utils::config::*;
-use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
-use std::sync::Arc;
+use parking_lot::lock_api::RawRwLock as _;
+use parking_lot::RawRwLock;
+use std::cell::UnsafeCell;
+use std::fmt;
 use std::mem::size_of;
+use std::sync::atomic::{fence, AtomicBool, AtomicI32, AtomicU64, AtomicUsize, Ordering};
+use tokio::sync::Notify;
 
 // pub const BUSTUB_PAGE_SIZE: usize = 4096;
 
-pub const SIZE_PAGE_HEADER: usize = 8;
+/// Size of the common header at the start of every on-disk page, see `PageHeader`.
+pub const SIZE_PAGE_HEADER: usize = 16;
 
 pub const OFFSET_LSN: usize = 4;
 
@@ -13,88 +18,305 @@ pub const OFFSET_LSN: usize = 4;
 /// Page is the basic unit of storage within the database system. Page provides a wrapper for actual data pages being
 /// held in main memory. Page also contains book-keeping information that is used by the buffer pool manager, e.g.
 /// pin count, dirty flag, page id, etc.
-#[derive(Debug)]
+///
+/// Like its C++ counterpart, the page data is only protected by the page latch: callers must hold `r_latch` /
+/// `w_latch` (or a page guard) while touching `get_data` / `get_data_mut`.
+///
+/// Readers may instead read optimistically without the latch, in the style of a seqlock: the page carries a
+/// version counter that is odd while a writer holds the write latch and is bumped again on release. A reader
+/// records an even version before reading and checks it is unchanged afterwards; if not, a writer interfered and
+/// the read must be retried.
+///
+/// Optimistic readers never look at the data itself, which latched writers modify with plain writes. They read a
+/// copy of it in atomic words instead, which the writer stores back with atomic stores before it releases the
+/// write latch, so the two never race.
 pub struct Page {
     /// Store the actual data of the page.
-    data: Vec<u8>,
+    data: UnsafeCell<Box<[u8]>>,
+    /// Copy of the data for optimistic readers, as of the last release of the write latch.
+    published: Box<[AtomicU64]>,
+    /// True if the data may have been modified since it was last copied to `published`.
+    unpublished: AtomicBool,
     /// The page id of the page.
-    page_id: PageId,
+    page_id: AtomicI32,
     /// The pin count of the page.
-    pin_count: i32,
+    pin_count: AtomicI32,
     /// True if the page is dirty, i.e. it is different from its corresponding page on disk.
-    is_dirty: bool,
+    is_dirty: AtomicBool,
+    /// Recovery LSN of the page while it is dirty: its LSN right before it was first modified
+    /// since it was last written back.
+    rec_lsn: AtomicI32,
     /// Page latch.
-    rwlatch: Arc<RwLock<()>>,
+    rwlatch: RawRwLock,
+    /// Woken up when the latch is released while async tasks are waiting to take it.
+    latch_released: Notify,
+    /// Number of async tasks waiting to take the latch.
+    latch_waiters: AtomicUsize,
+    /// Version counter for optimistic reads, odd while the write latch is held.
+    version: AtomicU64,
 }
 
+// The data is only accessed under the page latch, or by the owner of the frame.
+unsafe impl Sync for Page {}
+
 impl Page {
-    /// Constructor. 
+    /// Constructor.
     pub fn new() -> Self {
-        let mut page = Page {
-            data: vec![0; BUSTUB_PAGE_SIZE],
-            page_id: INVALID_PAGE_ID,
-            pin_count: 0,
-            is_dirty: false,
-            rwlatch: Arc::new(RwLock::new(())),
+        let page = Page {
+            data: UnsafeCell::new(vec![0; BUSTUB_PAGE_SIZE].into_boxed_slice()),
+            published: (0..BUSTUB_PAGE_SIZE / size_of::<u64>())
+                .map(|_| AtomicU64::new(0))
+                .collect(),
+            unpublished: AtomicBool::new(false),
+            page_id: AtomicI32::new(INVALID_PAGE_ID),
+            pin_count: AtomicI32::new(0),
+            is_dirty: AtomicBool::new(false),
+            rec_lsn: AtomicI32::new(INVALID_LSN),
+            rwlatch: RawRwLock::INIT,
+            latch_released: Notify::new(),
+            latch_waiters: AtomicUsize::new(0),
+            version: AtomicU64::new(0),
         };
         page.reset_memory();
         page
     }
 
-    /// Return the data of the page.
+    /// Return the data of the page. The caller must hold the read or write latch.
     pub fn get_data(&self) -> &[u8] {
-        &self.data
+        unsafe { &*self.data.get() }
+    }
+
+    /// Return the data of the page for modification. The caller must hold the write latch.
+    #[allow(clippy::mut_from_ref)]
+    pub fn get_data_mut(&self) -> &mut [u8] {
+        self.unpublished.store(true, Ordering::Relaxed);
+        unsafe { &mut *self.data.get() }
+    }
+
+    /// Copy the data of the page into `buf` without taking the latch. A concurrent writer may
+    /// leave the copy torn, so it must only be trusted once `validate_version` confirms that no
+    /// writer interfered.
+    pub(crate) fn read_data_optimistic(&self, buf: &mut [u8; BUSTUB_PAGE_SIZE]) {
+        for (word, chunk) in self.published.iter().zip(buf.chunks_exact_mut(size_of::<u64>())) {
+            chunk.copy_from_slice(&word.load(Ordering::Relaxed).to_ne_bytes());
+        }
+    }
+
+    /// Copy the data to the words optimistic readers read, if it may have been modified since
+    /// the last copy. The caller must hold the write latch, or own the frame.
+    pub(crate) fn publish_data(&self) {
+        if !self.unpublished.swap(false, Ordering::Relaxed) {
+            return;
+        }
+        // Pairs with the acquire fence of `validate_version`: a reader that loads any of the
+        // words stored below also sees the odd version the write latch left, and retries.
+        fence(Ordering::Release);
+        let data = self.get_data();
+        for (word, chunk) in self.published.iter().zip(data.chunks_exact(size_of::<u64>())) {
+            word.store(u64::from_ne_bytes(chunk.try_into().unwrap()), Ordering::Relaxed);
+        }
     }
 
     /// Return the id of the page.
     pub fn get_page_id(&self) -> PageId {
-        self.page_id
+        self.page_id.load(Ordering::SeqCst)
     }
 
     /// Return the pin count of the page.
     pub fn get_pin_count(&self) -> i32 {
-        self.pin_count
+        self.pin_count.load(Ordering::SeqCst)
     }
 
     /// Return true if the page is dirty.
     pub fn is_dirty(&self) -> bool {
-        self.is_dirty
+        self.is_dirty.load(Ordering::SeqCst)
     }
 
     /// Acquire the write latch of the page.
-    pub fn w_latch(&self) -> RwLockWriteGuard<()> {
-        self.rwlatch.write().unwrap()
+    pub fn w_latch(&self) {
+        self.rwlatch.lock_exclusive();
+        self.version.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Try to acquire the write latch of the page without blocking.
+    pub fn try_w_latch(&self) -> bool {
+        if !self.rwlatch.try_lock_exclusive() {
+            return false;
+        }
+        self.version.fetch_add(1, Ordering::SeqCst);
+        true
     }
 
     /// Release the write latch of the page.
     pub fn w_unlatch(&self) {
-        drop(self.w_latch());
+        self.publish_data();
+        self.version.fetch_add(1, Ordering::SeqCst);
+        unsafe { self.rwlatch.unlock_exclusive() };
+        self.notify_latch_released();
     }
 
     /// Acquire the read latch of the page.
-    pub fn r_latch(&self) -> RwLockReadGuard<()> {
-        self.rwlatch.read().unwrap()
+    pub fn r_latch(&self) {
+        self.rwlatch.lock_shared();
+    }
+
+    /// Try to acquire the read latch of the page without blocking.
+    pub fn try_r_latch(&self) -> bool {
+        self.rwlatch.try_lock_shared()
     }
 
     /// Release the read latch of the page.
     pub fn r_unlatch(&self) {
-        drop(self.r_latch());
+        unsafe { self.rwlatch.unlock_shared() };
+        self.notify_latch_released();
+    }
+
+    /// Wake up the async tasks waiting for the latch, if there are any.
+    fn notify_latch_released(&self) {
+        // Pairs with the fence of `LatchWaiter::new`: either the waiter is counted here, or its
+        // next attempt sees the latch released.
+        fence(Ordering::SeqCst);
+        if self.latch_waiters.load(Ordering::Relaxed) > 0 {
+            self.latch_released.notify_waiters();
+        }
+    }
+
+    /// Acquire the read latch of the page from an async task, yielding while a writer holds it.
+    pub(crate) async fn r_latch_async(&self) {
+        self.latch_async(Self::try_r_latch).await;
+    }
+
+    /// Acquire the write latch of the page from an async task, yielding while it is held.
+    pub(crate) async fn w_latch_async(&self) {
+        self.latch_async(Self::try_w_latch).await;
+    }
+
+    async fn latch_async(&self, try_latch: fn(&Self) -> bool) {
+        let _waiter = LatchWaiter::new(&self.latch_waiters);
+        loop {
+            let released = self.latch_released.notified();
+            tokio::pin!(released);
+            // Register for the wake-up before trying, so that a release in between is not missed.
+            released.as_mut().enable();
+            if try_latch(self) {
+                return;
+            }
+            released.await;
+        }
+    }
+
+    /// Return the current version of the page. An odd version means a writer holds the latch.
+    pub fn get_version(&self) -> u64 {
+        self.version.load(Ordering::SeqCst)
+    }
+
+    /// Return a version to start an optimistic read from, waiting for the current writer (if
+    /// any) to release the latch.
+    pub fn read_version(&self) -> u64 {
+        let version = self.get_version();
+        if version.is_multiple_of(2) {
+            return version;
+        }
+        self.r_latch();
+        let version = self.get_version();
+        self.r_unlatch();
+        version
+    }
+
+    /// Return true if no writer latched the page since `read_version` returned `version`, i.e.
+    /// everything read from the page in between is consistent.
+    pub fn validate_version(&self, version: u64) -> bool {
+        // Orders the relaxed loads of the read before the version check, see `publish_data`.
+        fence(Ordering::Acquire);
+        self.get_version() == version
     }
 
     /// Return the log sequence number (LSN) of the page.
     pub fn get_lsn(&self) -> Lsn {
-        let lsn_bytes = &self.data[OFFSET_LSN..OFFSET_LSN + size_of::<Lsn>()];
+        let lsn_bytes = &self.get_data()[OFFSET_LSN..OFFSET_LSN + size_of::<Lsn>()];
         Lsn::from_le_bytes(lsn_bytes.try_into().unwrap())
     }
 
     /// Set the log sequence number (LSN) of the page.
-    pub fn set_lsn(&mut self, lsn: Lsn) {
+    pub fn set_lsn(&self, lsn: Lsn) {
         let lsn_bytes = lsn.to_le_bytes();
-        self.data[OFFSET_LSN..OFFSET_LSN + size_of::<Lsn>()].copy_from_slice(&lsn_bytes);
+        self.get_data_mut()[OFFSET_LSN..OFFSET_LSN + size_of::<Lsn>()].copy_from_slice(&lsn_bytes);
     }
 
-    /// Reset the memory of the page.
-    fn reset_memory(&mut self) {
-        self.data.fill(0);
+    /// Set the id of the page. Only the buffer pool manager reassigns frames.
+    pub(crate) fn set_page_id(&self, page_id: PageId) {
+        self.page_id.store(page_id, Ordering::SeqCst);
+    }
+
+    /// Set the dirty flag of the page.
+    pub(crate) fn set_dirty(&self, is_dirty: bool) {
+        self.is_dirty.store(is_dirty, Ordering::SeqCst);
+    }
+
+    /// Return the recovery LSN of the page, only meaningful while the page is dirty.
+    pub(crate) fn get_rec_lsn(&self) -> Lsn {
+        self.rec_lsn.load(Ordering::SeqCst)
+    }
+
+    /// Mark the page dirty. If it was clean, its current LSN becomes its recovery LSN: every log
+    /// record of a change from now on has a larger LSN. The caller must hold a latch on the page
+    /// or own the frame, and call this before modifying the data.
+    pub(crate) fn mark_dirty(&self) {
+        if !self.is_dirty() {
+            self.rec_lsn.store(self.get_lsn(), Ordering::SeqCst);
+            self.set_dirty(true);
+        }
+    }
+
+    /// Increment the pin count, returning the new value.
+    pub(crate) fn pin(&self) -> i32 {
+        self.pin_count.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    /// Decrement the pin count, returning the new value.
+    pub(crate) fn unpin(&self) -> i32 {
+        self.pin_count.fetch_sub(1, Ordering::SeqCst) - 1
+    }
+
+    /// Reset the memory of the page. The caller must own the frame.
+    pub(crate) fn reset_memory(&self) {
+        self.get_data_mut().fill(0);
+        self.publish_data();
+    }
+}
+
+/// Counts an async task as waiting for the latch of a page for as long as it lives, including
+/// when the task is dropped while waiting.
+struct LatchWaiter<'a> {
+    latch_waiters: &'a AtomicUsize,
+}
+
+impl<'a> LatchWaiter<'a> {
+    fn new(latch_waiters: &'a AtomicUsize) -> Self {
+        latch_waiters.fetch_add(1, Ordering::Relaxed);
+        fence(Ordering::SeqCst);
+        LatchWaiter { latch_waiters }
+    }
+}
+
+impl Drop for LatchWaiter<'_> {
+    fn drop(&mut self) {
+        self.latch_waiters.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Default for Page {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Page {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Page")
+            .field("page_id", &self.get_page_id())
+            .field("pin_count", &self.get_pin_count())
+            .field("is_dirty", &self.is_dirty())
+            .finish()
     }
 }
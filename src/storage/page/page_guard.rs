@@ -0,0 +1,253 @@
+use std::sync::Arc;
+
+use crate::buffer::buffer_pool::BufferPool;
+use crate::utils::config::*;
+
+use super::page::Page;
+use super::page_header::PageHeader;
+
+/// BasicPageGuard keeps a page pinned for as long as it lives and unpins it when dropped. It
+/// gives no access to the page data: that takes the latch, through `upgrade_read` or
+/// `upgrade_write`.
+pub struct BasicPageGuard<'a> {
+    bpm: &'a dyn BufferPool,
+    page: Option<Arc<Page>>,
+}
+
+impl<'a> BasicPageGuard<'a> {
+    pub fn new(bpm: &'a dyn BufferPool, page: Arc<Page>) -> Self {
+        BasicPageGuard {
+            bpm,
+            page: Some(page),
+        }
+    }
+
+    /// Return the id of the guarded page.
+    pub fn page_id(&self) -> PageId {
+        self.page().get_page_id()
+    }
+
+    /// Return the guarded page.
+    pub fn page(&self) -> &Arc<Page> {
+        self.page.as_ref().expect("page guard already released")
+    }
+
+    /// Take the read latch of the page and turn this guard into a `ReadPageGuard`.
+    pub fn upgrade_read(self) -> ReadPageGuard<'a> {
+        self.page().r_latch();
+        ReadPageGuard { guard: self }
+    }
+
+    /// Take the write latch of the page and turn this guard into a `WritePageGuard`.
+    pub fn upgrade_write(self) -> WritePageGuard<'a> {
+        self.page().w_latch();
+        WritePageGuard { guard: self }
+    }
+
+    /// Take the read latch of the page and turn this guard into a `ReadPageGuard`, waiting
+    /// asynchronously instead of blocking while a writer holds the latch. If the future is
+    /// dropped while waiting, the guard is dropped with it and the page is unpinned.
+    pub async fn upgrade_read_async(self) -> ReadPageGuard<'a> {
+        self.page().r_latch_async().await;
+        ReadPageGuard { guard: self }
+    }
+
+    /// Take the write latch of the page and turn this guard into a `WritePageGuard`, waiting
+    /// asynchronously instead of blocking while the latch is held. See `upgrade_read_async`.
+    pub async fn upgrade_write_async(self) -> WritePageGuard<'a> {
+        self.page().w_latch_async().await;
+        WritePageGuard { guard: self }
+    }
+
+    /// Turn this guard into an `OptimisticPageGuard`, which reads the page without latching it.
+    pub fn upgrade_optimistic(self) -> OptimisticPageGuard<'a> {
+        let version = self.page().read_version();
+        OptimisticPageGuard {
+            guard: self,
+            version,
+        }
+    }
+
+    /// Unpin the page now instead of waiting for the guard to go out of scope.
+    pub fn drop_guard(&mut self) {
+        if let Some(page) = self.page.take() {
+            self.bpm.unpin_page(page.get_page_id(), false);
+        }
+    }
+}
+
+impl Drop for BasicPageGuard<'_> {
+    fn drop(&mut self) {
+        self.drop_guard();
+    }
+}
+
+/// ReadPageGuard holds the read latch and a pin on a page.
+pub struct ReadPageGuard<'a> {
+    guard: BasicPageGuard<'a>,
+}
+
+impl ReadPageGuard<'_> {
+    /// Return the id of the guarded page.
+    pub fn page_id(&self) -> PageId {
+        self.guard.page_id()
+    }
+
+    /// Return the guarded page.
+    pub fn page(&self) -> &Arc<Page> {
+        self.guard.page()
+    }
+
+    /// Return the data of the guarded page.
+    pub fn get_data(&self) -> &[u8] {
+        self.page().get_data()
+    }
+
+    /// Release the latch and unpin the page now.
+    pub fn drop_guard(&mut self) {
+        if let Some(page) = &self.guard.page {
+            page.r_unlatch();
+        }
+        self.guard.drop_guard();
+    }
+}
+
+impl Drop for ReadPageGuard<'_> {
+    fn drop(&mut self) {
+        self.drop_guard();
+    }
+}
+
+/// WritePageGuard holds the write latch and a pin on a page.
+pub struct WritePageGuard<'a> {
+    guard: BasicPageGuard<'a>,
+}
+
+impl WritePageGuard<'_> {
+    /// Return the id of the guarded page.
+    pub fn page_id(&self) -> PageId {
+        self.guard.page_id()
+    }
+
+    /// Return the guarded page.
+    pub fn page(&self) -> &Arc<Page> {
+        self.guard.page()
+    }
+
+    /// Return the data of the guarded page.
+    pub fn get_data(&self) -> &[u8] {
+        self.page().get_data()
+    }
+
+    /// Return the data of the guarded page for modification and mark the page dirty.
+    pub fn get_data_mut(&mut self) -> &mut [u8] {
+        self.mark_dirty();
+        self.page().get_data_mut()
+    }
+
+    /// Set the log sequence number (LSN) of the page and mark the page dirty.
+    pub fn set_lsn(&mut self, lsn: Lsn) {
+        PageHeader::new(self.get_data_mut()).set_lsn(lsn);
+    }
+
+    /// Mark the page dirty without touching its data. The page enters the dirty page table
+    /// right away, with the LSN it has before any change made through this guard.
+    pub fn mark_dirty(&mut self) {
+        self.page().mark_dirty();
+    }
+
+    /// Release the latch and unpin the page now.
+    pub fn drop_guard(&mut self) {
+        if let Some(page) = &self.guard.page {
+            page.w_unlatch();
+        }
+        self.guard.drop_guard();
+    }
+}
+
+impl Drop for WritePageGuard<'_> {
+    fn drop(&mut self) {
+        self.drop_guard();
+    }
+}
+
+/// OptimisticPageGuard keeps a page pinned and reads it without taking the latch, in the style of
+/// LeanStore / Umbra optimistic latching.
+///
+/// The guard remembers the page version it started from. `read` copies the page with atomic loads,
+/// which a concurrent writer may leave torn, and only hands the copy to its closure once
+/// `validate` confirms that no writer interfered; `read_with_retry` retries until a consistent
+/// read succeeds. Closures therefore only ever see a consistent version of the page.
+pub struct OptimisticPageGuard<'a> {
+    guard: BasicPageGuard<'a>,
+    version: u64,
+}
+
+impl<'a> OptimisticPageGuard<'a> {
+    /// Return the id of the guarded page.
+    pub fn page_id(&self) -> PageId {
+        self.guard.page_id()
+    }
+
+    /// Return the guarded page.
+    pub fn page(&self) -> &Arc<Page> {
+        self.guard.page()
+    }
+
+    /// Return the version the current optimistic read started from.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Return true if the page has not been latched for writing since the read started.
+    pub fn validate(&self) -> bool {
+        self.page().validate_version(self.version)
+    }
+
+    /// Start a new optimistic read from the current version of the page.
+    pub fn restart(&mut self) {
+        self.version = self.page().read_version();
+    }
+
+    /// Run `f` on a copy of the page data if the copy is consistent, and return its result, or
+    /// `None` if a writer interfered, in which case the caller should `restart`.
+    pub fn read<R>(&self, f: impl FnOnce(&[u8]) -> R) -> Option<R> {
+        let mut data = [0; BUSTUB_PAGE_SIZE];
+        self.page().read_data_optimistic(&mut data);
+        self.validate().then(|| f(&data))
+    }
+
+    /// Run `f` on the page data until it sees a consistent version of the page.
+    pub fn read_with_retry<R>(&mut self, mut f: impl FnMut(&[u8]) -> R) -> R {
+        loop {
+            if let Some(result) = self.read(&mut f) {
+                return result;
+            }
+            self.restart();
+        }
+    }
+
+    /// Take the read latch, but only if the page is still at the version the optimistic read
+    /// started from. Otherwise the pin is released and `None` is returned.
+    pub fn upgrade_read(self) -> Option<ReadPageGuard<'a>> {
+        self.page().r_latch();
+        if !self.validate() {
+            self.page().r_unlatch();
+            return None;
+        }
+        Some(ReadPageGuard { guard: self.guard })
+    }
+
+    /// Take the write latch, but only if the page is still at the version the optimistic read
+    /// started from. Otherwise the pin is released and `None` is returned.
+    pub fn upgrade_write(self) -> Option<WritePageGuard<'a>> {
+        let page = self.page().clone();
+        page.w_latch();
+        // Taking the write latch bumped the version once.
+        if page.get_version() != self.version + 1 {
+            page.w_unlatch();
+            return None;
+        }
+        Some(WritePageGuard { guard: self.guard })
+    }
+}
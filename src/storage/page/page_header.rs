@@ -0,0 +1,247 @@
+use std::fmt;
+use std::mem::size_of;
+
+use crate::utils::config::*;
+
+use super::page::{OFFSET_LSN, SIZE_PAGE_HEADER};
+
+const OFFSET_PAGE_ID: usize = 0;
+const OFFSET_MAGIC: usize = OFFSET_LSN + size_of::<Lsn>();
+const OFFSET_PAGE_TYPE: usize = OFFSET_MAGIC + size_of::<u16>();
+const OFFSET_FORMAT_VERSION: usize = OFFSET_PAGE_TYPE + size_of::<u8>();
+const OFFSET_CHECKSUM: usize = OFFSET_FORMAT_VERSION + size_of::<u8>();
+
+const _: () = assert!(OFFSET_CHECKSUM + size_of::<u32>() == SIZE_PAGE_HEADER);
+
+/// Magic number marking a page formatted with a `PageHeader`.
+pub const PAGE_MAGIC: u16 = 0xB57B;
+/// Version of the on-disk page formats, bumped whenever a layout changes incompatibly.
+pub const PAGE_FORMAT_VERSION: u8 = 1;
+
+/// Kind of structure a page belongs to, recorded in its header.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PageType {
+    Header = 1,
+    Table = 2,
+    BPlusTreeLeaf = 3,
+    BPlusTreeInternal = 4,
+    HashDirectory = 5,
+    HashBucket = 6,
+    Overflow = 7,
+    FreeSpaceMap = 8,
+    HashHeader = 9,
+    /// A deallocated page, waiting to be reused by the buffer pool.
+    Free = 10,
+}
+
+impl PageType {
+    /// Decode a page type tag, or return `None` for an unknown one.
+    pub fn from_u8(tag: u8) -> Option<Self> {
+        Some(match tag {
+            1 => PageType::Header,
+            2 => PageType::Table,
+            3 => PageType::BPlusTreeLeaf,
+            4 => PageType::BPlusTreeInternal,
+            5 => PageType::HashDirectory,
+            6 => PageType::HashBucket,
+            7 => PageType::Overflow,
+            8 => PageType::FreeSpaceMap,
+            9 => PageType::HashHeader,
+            10 => PageType::Free,
+            _ => return None,
+        })
+    }
+}
+
+/// Reason a page image failed `PageHeader::validate`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PageHeaderError {
+    /// The page does not start with `PAGE_MAGIC`, e.g. it was never formatted.
+    BadMagic(u16),
+    UnknownPageType(u8),
+    UnsupportedVersion(u8),
+    PageIdMismatch {
+        expected: PageId,
+        found: PageId,
+    },
+    ChecksumMismatch {
+        expected: u32,
+        found: u32,
+    },
+}
+
+impl fmt::Display for PageHeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PageHeaderError::BadMagic(magic) => write!(f, "bad page magic {:#06x}", magic),
+            PageHeaderError::UnknownPageType(tag) => write!(f, "unknown page type {}", tag),
+            PageHeaderError::UnsupportedVersion(version) => {
+                write!(f, "unsupported page format version {}", version)
+            }
+            PageHeaderError::PageIdMismatch { expected, found } => {
+                write!(f, "expected page {} but found page {}", expected, found)
+            }
+            PageHeaderError::ChecksumMismatch { expected, found } => write!(
+                f,
+                "page checksum {:#010x} does not match computed {:#010x}",
+                found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PageHeaderError {}
+
+/// PageHeader is the common header at the start of every on-disk page, viewed over the data of a
+/// `Page`, so that tooling and recovery can interpret any page without context.
+///
+/// ```text
+///  --------------------------------------------------------------------------
+/// | page id (4) | LSN (4) | magic (2) | page type (1) | version (1) | checksum (4) |
+///  --------------------------------------------------------------------------
+/// ```
+///
+/// The checksum is a CRC-32 of the whole page with the checksum field taken as zero. It is stamped
+/// by the buffer pool manager whenever a formatted page is written to disk, so it is only
+/// meaningful for page images read back from disk.
+#[derive(Debug)]
+pub struct PageHeader<D> {
+    data: D,
+}
+
+impl<D: AsRef<[u8]>> PageHeader<D> {
+    /// View page data as a page header.
+    pub fn new(data: D) -> Self {
+        PageHeader { data }
+    }
+
+    /// Return the id of the page.
+    pub fn get_page_id(&self) -> PageId {
+        PageId::from_le_bytes(self.read::<4>(OFFSET_PAGE_ID))
+    }
+
+    /// Return the log sequence number (LSN) of the page.
+    pub fn get_lsn(&self) -> Lsn {
+        Lsn::from_le_bytes(self.read::<4>(OFFSET_LSN))
+    }
+
+    /// Return the magic number, `PAGE_MAGIC` for a formatted page.
+    pub fn get_magic(&self) -> u16 {
+        u16::from_le_bytes(self.read::<2>(OFFSET_MAGIC))
+    }
+
+    /// Return true if the page has been formatted with a header.
+    pub fn is_formatted(&self) -> bool {
+        self.get_magic() == PAGE_MAGIC
+    }
+
+    /// Return the type of the page, or `None` if the tag is unknown.
+    pub fn get_page_type(&self) -> Option<PageType> {
+        PageType::from_u8(self.data.as_ref()[OFFSET_PAGE_TYPE])
+    }
+
+    /// Return the format version the page was written with.
+    pub fn get_format_version(&self) -> u8 {
+        self.data.as_ref()[OFFSET_FORMAT_VERSION]
+    }
+
+    /// Return the checksum stored in the page.
+    pub fn get_checksum(&self) -> u32 {
+        u32::from_le_bytes(self.read::<4>(OFFSET_CHECKSUM))
+    }
+
+    /// Compute the checksum of the page as it is now.
+    pub fn compute_checksum(&self) -> u32 {
+        let data = self.data.as_ref();
+        let crc = crc32_update(!0, &data[..OFFSET_CHECKSUM]);
+        let crc = crc32_update(crc, &[0; size_of::<u32>()]);
+        !crc32_update(crc, &data[OFFSET_CHECKSUM + size_of::<u32>()..])
+    }
+
+    /// Check a page image read from disk: magic, page type, format version, checksum and, if
+    /// given, the page id it is expected to hold. Return the page type if everything matches.
+    pub fn validate(&self, expected_page_id: Option<PageId>) -> Result<PageType, PageHeaderError> {
+        if !self.is_formatted() {
+            return Err(PageHeaderError::BadMagic(self.get_magic()));
+        }
+        let tag = self.data.as_ref()[OFFSET_PAGE_TYPE];
+        let page_type = PageType::from_u8(tag).ok_or(PageHeaderError::UnknownPageType(tag))?;
+        if self.get_format_version() != PAGE_FORMAT_VERSION {
+            return Err(PageHeaderError::UnsupportedVersion(
+                self.get_format_version(),
+            ));
+        }
+        if let Some(expected) = expected_page_id {
+            if self.get_page_id() != expected {
+                return Err(PageHeaderError::PageIdMismatch {
+                    expected,
+                    found: self.get_page_id(),
+                });
+            }
+        }
+        let expected = self.compute_checksum();
+        if self.get_checksum() != expected {
+            return Err(PageHeaderError::ChecksumMismatch {
+                expected,
+                found: self.get_checksum(),
+            });
+        }
+        Ok(page_type)
+    }
+
+    fn read<const N: usize>(&self, offset: usize) -> [u8; N] {
+        self.data.as_ref()[offset..offset + N].try_into().unwrap()
+    }
+}
+
+impl<D: AsRef<[u8]> + AsMut<[u8]>> PageHeader<D> {
+    /// Format the header of a new page of the given type. The LSN is left as it is.
+    pub fn init(&mut self, page_id: PageId, page_type: PageType) {
+        let data = self.data.as_mut();
+        data[OFFSET_PAGE_ID..OFFSET_PAGE_ID + 4].copy_from_slice(&page_id.to_le_bytes());
+        data[OFFSET_MAGIC..OFFSET_MAGIC + 2].copy_from_slice(&PAGE_MAGIC.to_le_bytes());
+        data[OFFSET_PAGE_TYPE] = page_type as u8;
+        data[OFFSET_FORMAT_VERSION] = PAGE_FORMAT_VERSION;
+        data[OFFSET_CHECKSUM..OFFSET_CHECKSUM + 4].fill(0);
+    }
+
+    /// Set the log sequence number (LSN) of the page.
+    pub fn set_lsn(&mut self, lsn: Lsn) {
+        self.data.as_mut()[OFFSET_LSN..OFFSET_LSN + 4].copy_from_slice(&lsn.to_le_bytes());
+    }
+
+    /// Store the checksum of the page as it is now.
+    pub fn update_checksum(&mut self) {
+        let checksum = self.compute_checksum();
+        self.data.as_mut()[OFFSET_CHECKSUM..OFFSET_CHECKSUM + 4]
+            .copy_from_slice(&checksum.to_le_bytes());
+    }
+}
+
+/// Lookup table of the reflected CRC-32 (IEEE) polynomial.
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+fn crc32_update(crc: u32, bytes: &[u8]) -> u32 {
+    bytes.iter().fold(crc, |crc, &byte| {
+        CRC32_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
@@ -0,0 +1,338 @@
+use std::mem::size_of;
+
+use crate::utils::config::*;
+
+use super::page::SIZE_PAGE_HEADER;
+use super::page_header::{PageHeader, PageType};
+
+const OFFSET_PREV_PAGE_ID: usize = SIZE_PAGE_HEADER;
+const OFFSET_NEXT_PAGE_ID: usize = OFFSET_PREV_PAGE_ID + size_of::<PageId>();
+const OFFSET_FREE_SPACE_POINTER: usize = OFFSET_NEXT_PAGE_ID + size_of::<PageId>();
+const OFFSET_TUPLE_COUNT: usize = OFFSET_FREE_SPACE_POINTER + size_of::<u32>();
+
+/// Size of the table page header, i.e. offset of the slot array.
+pub const SIZE_TABLE_PAGE_HEADER: usize = OFFSET_TUPLE_COUNT + size_of::<u32>();
+/// Size of a slot: tuple offset, tuple size and flags.
+pub const SIZE_SLOT: usize = 3 * size_of::<u32>();
+
+/// Slot flag: the tuple was marked deleted and can still be rolled back.
+pub const TUPLE_DELETED: u32 = 1;
+/// Slot flag: the tuple is an `OverflowPointer` to a value stored in overflow pages.
+pub const TUPLE_OVERFLOW: u32 = 2;
+
+/// TablePage is the slotted page layout of table heap pages, viewed over the data of a `Page`.
+///
+/// ```text
+///  ---------------------------------------------------------------------
+/// | HEADER | SLOT 0 | SLOT 1 | ... | FREE SPACE | ... | TUPLE 1 | TUPLE 0 |
+///  ---------------------------------------------------------------------
+///                                  ^ end of slot array ^ free space pointer
+/// ```
+///
+/// Header: common `PageHeader` | prev page id | next page id |
+/// free space pointer | tuple count, followed by the slot array growing from the front while
+/// tuple data grows from the back. Every slot stores (offset, size, flags) of its tuple; a slot
+/// of size 0 is free and is reused by the next insert. Slot numbers stay stable as long as the
+/// tuple lives, so they can be used in record ids.
+///
+/// Read operations only need `D: AsRef<[u8]>` (e.g. the data of a read guard), modifications need
+/// `D: AsMut<[u8]>` (e.g. the data of a write guard).
+#[derive(Debug)]
+pub struct TablePage<D> {
+    data: D,
+}
+
+impl<D: AsRef<[u8]>> TablePage<D> {
+    /// View page data as a table page.
+    pub fn new(data: D) -> Self {
+        TablePage { data }
+    }
+
+    /// Return the id of the page.
+    pub fn get_page_id(&self) -> PageId {
+        PageHeader::new(self.data.as_ref()).get_page_id()
+    }
+
+    /// Return the id of the previous page of the table heap.
+    pub fn get_prev_page_id(&self) -> PageId {
+        self.read_i32(OFFSET_PREV_PAGE_ID)
+    }
+
+    /// Return the id of the next page of the table heap.
+    pub fn get_next_page_id(&self) -> PageId {
+        self.read_i32(OFFSET_NEXT_PAGE_ID)
+    }
+
+    /// Return the number of slots, including free ones.
+    pub fn get_tuple_count(&self) -> usize {
+        self.read_u32(OFFSET_TUPLE_COUNT) as usize
+    }
+
+    /// Return the number of contiguous free bytes between the slot array and the tuple data.
+    pub fn get_free_space_remaining(&self) -> usize {
+        self.get_free_space_pointer() - self.slot_array_end()
+    }
+
+    /// Return the bytes taken by holes left in the tuple data by deletes and shrinking updates,
+    /// which `compact` can reclaim.
+    pub fn get_fragmented_space(&self) -> usize {
+        let live: usize = (0..self.get_tuple_count())
+            .map(|slot_num| self.read_slot(slot_num).1)
+            .sum();
+        BUSTUB_PAGE_SIZE - self.get_free_space_pointer() - live
+    }
+
+    /// Return the total number of bytes an insert can use, counting space `compact` reclaims.
+    pub fn get_free_space(&self) -> usize {
+        self.get_free_space_remaining() + self.get_fragmented_space()
+    }
+
+    /// Return the tuple stored in the slot, or `None` if the slot does not exist, is free, or
+    /// its tuple has been marked deleted.
+    pub fn get_tuple(&self, slot_num: SlotOffset) -> Option<&[u8]> {
+        let (offset, size, flags) = self.get_slot(slot_num)?;
+        if flags & TUPLE_DELETED != 0 {
+            return None;
+        }
+        Some(&self.data.as_ref()[offset..offset + size])
+    }
+
+    /// Return the bytes stored in the slot, even if its tuple has been marked deleted, or `None`
+    /// if the slot does not exist or is free.
+    pub fn get_raw_tuple(&self, slot_num: SlotOffset) -> Option<&[u8]> {
+        let (offset, size, _) = self.get_slot(slot_num)?;
+        Some(&self.data.as_ref()[offset..offset + size])
+    }
+
+    /// Return true if the tuple in the slot has been marked deleted.
+    pub fn is_deleted(&self, slot_num: SlotOffset) -> bool {
+        self.get_slot(slot_num)
+            .is_some_and(|(_, _, flags)| flags & TUPLE_DELETED != 0)
+    }
+
+    /// Return true if the tuple in the slot is stored out of line, i.e. `get_tuple` returns an
+    /// `OverflowPointer` to it.
+    pub fn is_overflow(&self, slot_num: SlotOffset) -> bool {
+        self.get_slot(slot_num)
+            .is_some_and(|(_, _, flags)| flags & TUPLE_OVERFLOW != 0)
+    }
+
+    /// Return the slot numbers of every tuple that is not free nor marked deleted, in order.
+    pub fn get_live_slots(&self) -> Vec<SlotOffset> {
+        (0..self.get_tuple_count())
+            .filter(|&slot_num| self.get_tuple(slot_num).is_some())
+            .collect()
+    }
+
+    /// Return (offset, size, flags) of a slot in use.
+    fn get_slot(&self, slot_num: SlotOffset) -> Option<(usize, usize, u32)> {
+        if slot_num >= self.get_tuple_count() {
+            return None;
+        }
+        let slot = self.read_slot(slot_num);
+        (slot.1 > 0).then_some(slot)
+    }
+
+    fn read_slot(&self, slot_num: SlotOffset) -> (usize, usize, u32) {
+        let slot = SIZE_TABLE_PAGE_HEADER + slot_num * SIZE_SLOT;
+        (
+            self.read_u32(slot) as usize,
+            self.read_u32(slot + size_of::<u32>()) as usize,
+            self.read_u32(slot + 2 * size_of::<u32>()),
+        )
+    }
+
+    fn get_free_space_pointer(&self) -> usize {
+        self.read_u32(OFFSET_FREE_SPACE_POINTER) as usize
+    }
+
+    fn slot_array_end(&self) -> usize {
+        SIZE_TABLE_PAGE_HEADER + self.get_tuple_count() * SIZE_SLOT
+    }
+
+    fn read_u32(&self, offset: usize) -> u32 {
+        u32::from_le_bytes(self.data.as_ref()[offset..offset + 4].try_into().unwrap())
+    }
+
+    fn read_i32(&self, offset: usize) -> i32 {
+        i32::from_le_bytes(self.data.as_ref()[offset..offset + 4].try_into().unwrap())
+    }
+}
+
+impl<D: AsRef<[u8]> + AsMut<[u8]>> TablePage<D> {
+    /// Initialize an empty table page.
+    pub fn init(&mut self, page_id: PageId, prev_page_id: PageId, next_page_id: PageId) {
+        PageHeader::new(self.data.as_mut()).init(page_id, PageType::Table);
+        self.set_prev_page_id(prev_page_id);
+        self.set_next_page_id(next_page_id);
+        self.write_u32(OFFSET_FREE_SPACE_POINTER, BUSTUB_PAGE_SIZE as u32);
+        self.write_u32(OFFSET_TUPLE_COUNT, 0);
+    }
+
+    /// Set the id of the previous page of the table heap.
+    pub fn set_prev_page_id(&mut self, prev_page_id: PageId) {
+        self.write_u32(OFFSET_PREV_PAGE_ID, prev_page_id as u32);
+    }
+
+    /// Set the id of the next page of the table heap.
+    pub fn set_next_page_id(&mut self, next_page_id: PageId) {
+        self.write_u32(OFFSET_NEXT_PAGE_ID, next_page_id as u32);
+    }
+
+    /// Insert a tuple and return its slot number, or `None` if the page does not have enough
+    /// space left even after compaction. Free slots are reused before the slot array grows.
+    pub fn insert_tuple(&mut self, tuple: &[u8]) -> Option<SlotOffset> {
+        assert!(!tuple.is_empty(), "cannot insert an empty tuple");
+        let free_slot = (0..self.get_tuple_count()).find(|&slot_num| self.read_slot(slot_num).1 == 0);
+        let needed = tuple.len() + if free_slot.is_some() { 0 } else { SIZE_SLOT };
+        if !self.reserve(needed) {
+            return None;
+        }
+        let slot_num = free_slot.unwrap_or_else(|| {
+            let slot_num = self.get_tuple_count();
+            self.write_u32(OFFSET_TUPLE_COUNT, slot_num as u32 + 1);
+            slot_num
+        });
+        let offset = self.append_data(tuple);
+        self.write_slot(slot_num, offset, tuple.len(), 0);
+        Some(slot_num)
+    }
+
+    /// Mark the tuple deleted. It is hidden from `get_tuple` but keeps its space until
+    /// `apply_delete`, so the delete can still be rolled back. Return false if there is no live
+    /// tuple in the slot.
+    pub fn mark_delete(&mut self, slot_num: SlotOffset) -> bool {
+        match self.get_slot(slot_num) {
+            Some((offset, size, flags)) if flags & TUPLE_DELETED == 0 => {
+                self.write_slot(slot_num, offset, size, flags | TUPLE_DELETED);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Undo `mark_delete`. Return false if the tuple in the slot is not marked deleted.
+    pub fn rollback_delete(&mut self, slot_num: SlotOffset) -> bool {
+        match self.get_slot(slot_num) {
+            Some((offset, size, flags)) if flags & TUPLE_DELETED != 0 => {
+                self.write_slot(slot_num, offset, size, flags & !TUPLE_DELETED);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Set or clear the overflow flag of the tuple in the slot. Return false if the slot is not in
+    /// use.
+    pub fn set_overflow(&mut self, slot_num: SlotOffset, overflow: bool) -> bool {
+        let Some((offset, size, flags)) = self.get_slot(slot_num) else {
+            return false;
+        };
+        let flags = if overflow {
+            flags | TUPLE_OVERFLOW
+        } else {
+            flags & !TUPLE_OVERFLOW
+        };
+        self.write_slot(slot_num, offset, size, flags);
+        true
+    }
+
+    /// Remove the tuple for good and free its slot. The space it took becomes fragmented until
+    /// the page is compacted. Return false if the slot is not in use.
+    pub fn apply_delete(&mut self, slot_num: SlotOffset) -> bool {
+        if self.get_slot(slot_num).is_none() {
+            return false;
+        }
+        self.write_slot(slot_num, 0, 0, 0);
+        // Trailing free slots are given back to the free space.
+        let mut tuple_count = self.get_tuple_count();
+        while tuple_count > 0 && self.read_slot(tuple_count - 1).1 == 0 {
+            tuple_count -= 1;
+        }
+        self.write_u32(OFFSET_TUPLE_COUNT, tuple_count as u32);
+        true
+    }
+
+    /// Replace the live tuple in the slot, keeping its slot number. A tuple that is not larger is
+    /// written in place; a larger one is moved to the free space. Return false if the slot has no
+    /// live tuple or the new tuple does not fit in the page, in which case nothing changes.
+    pub fn update_tuple(&mut self, slot_num: SlotOffset, tuple: &[u8]) -> bool {
+        assert!(!tuple.is_empty(), "cannot update to an empty tuple");
+        let Some((offset, size, flags)) = self.get_slot(slot_num) else {
+            return false;
+        };
+        if flags & TUPLE_DELETED != 0 {
+            return false;
+        }
+        if tuple.len() <= size {
+            self.data.as_mut()[offset..offset + tuple.len()].copy_from_slice(tuple);
+            self.write_slot(slot_num, offset, tuple.len(), flags);
+            return true;
+        }
+        // The old tuple's space counts as reclaimable once the slot is detached from it.
+        self.write_slot(slot_num, 0, 0, 0);
+        if !self.reserve(tuple.len()) {
+            self.write_slot(slot_num, offset, size, flags);
+            return false;
+        }
+        let new_offset = self.append_data(tuple);
+        self.write_slot(slot_num, new_offset, tuple.len(), flags);
+        true
+    }
+
+    /// Move every tuple to the back of the page so that all fragmented space becomes contiguous
+    /// free space. Slot numbers do not change.
+    pub fn compact(&mut self) {
+        let mut slots: Vec<(SlotOffset, usize, usize, u32)> = (0..self.get_tuple_count())
+            .map(|slot_num| {
+                let (offset, size, flags) = self.read_slot(slot_num);
+                (slot_num, offset, size, flags)
+            })
+            .filter(|&(_, _, size, _)| size > 0)
+            .collect();
+        // Tuples closest to the end of the page move first, so nothing is overwritten before it
+        // has been moved.
+        slots.sort_by_key(|&(_, offset, _, _)| std::cmp::Reverse(offset));
+        let mut free_space_pointer = BUSTUB_PAGE_SIZE;
+        for (slot_num, offset, size, flags) in slots {
+            free_space_pointer -= size;
+            self.data
+                .as_mut()
+                .copy_within(offset..offset + size, free_space_pointer);
+            self.write_slot(slot_num, free_space_pointer, size, flags);
+        }
+        self.write_u32(OFFSET_FREE_SPACE_POINTER, free_space_pointer as u32);
+    }
+
+    /// Make sure `needed` contiguous free bytes are available, compacting the page if that is
+    /// enough to get them.
+    fn reserve(&mut self, needed: usize) -> bool {
+        if self.get_free_space_remaining() >= needed {
+            return true;
+        }
+        if self.get_free_space_remaining() + self.get_fragmented_space() < needed {
+            return false;
+        }
+        self.compact();
+        true
+    }
+
+    /// Copy tuple data right below the free space pointer and return its offset.
+    fn append_data(&mut self, tuple: &[u8]) -> usize {
+        let offset = self.get_free_space_pointer() - tuple.len();
+        self.data.as_mut()[offset..offset + tuple.len()].copy_from_slice(tuple);
+        self.write_u32(OFFSET_FREE_SPACE_POINTER, offset as u32);
+        offset
+    }
+
+    fn write_slot(&mut self, slot_num: SlotOffset, offset: usize, size: usize, flags: u32) {
+        let slot = SIZE_TABLE_PAGE_HEADER + slot_num * SIZE_SLOT;
+        self.write_u32(slot, offset as u32);
+        self.write_u32(slot + size_of::<u32>(), size as u32);
+        self.write_u32(slot + 2 * size_of::<u32>(), flags);
+    }
+
+    fn write_u32(&mut self, offset: usize, value: u32) {
+        self.data.as_mut()[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+}
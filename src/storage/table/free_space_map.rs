@@ -0,0 +1,288 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use crate::buffer::buffer_pool::BufferPool;
+use crate::storage::page::free_space_map_page::*;
+use crate::utils::config::*;
+
+/// FreeSpaceMap records the approximate free space of every page of a table heap in dedicated
+/// `FreeSpaceMapPage`s, so that an insert finds a page with room by reading one page per level
+/// instead of walking the whole heap.
+///
+/// The map is a tree: leaf pages hold one free space category per heap page, and inner pages hold
+/// their children along with the largest category found below each of them. Heap pages are added
+/// to the rightmost leaf, and new pages are added along the rightmost path as pages fill up. Once
+/// every page on that path is full, the root moves its entries to a new child and goes up one
+/// level, so the map grows without bound while its root page id stays the same.
+///
+/// Categories round free space down, so a page found by `find_page` normally has the room asked
+/// for; callers report the actual free space back with `update` whenever it changes.
+pub struct FreeSpaceMap {
+    bpm: Arc<dyn BufferPool>,
+    root_page_id: PageId,
+    /// Path of every heap page in the map: the index of the entry to follow in each page, from
+    /// the root down to the entry of the heap page in its leaf. Also serializes changes to the
+    /// map pages, so that inner pages always reflect their children.
+    paths: Mutex<HashMap<PageId, Vec<usize>>>,
+}
+
+impl FreeSpaceMap {
+    /// Create a new, empty free space map. Return `None` if the buffer pool has no frame
+    /// available for its root page.
+    pub fn new(bpm: Arc<dyn BufferPool>) -> Option<Self> {
+        Self::new_with_max_size(bpm, FREE_SPACE_MAP_PAGE_CAPACITY)
+    }
+
+    /// Like `new`, with map pages holding up to `max_size` entries instead of as many as fit.
+    pub fn new_with_max_size(bpm: Arc<dyn BufferPool>, max_size: usize) -> Option<Self> {
+        assert!(
+            (2..=FREE_SPACE_MAP_PAGE_CAPACITY).contains(&max_size),
+            "free space map page max size must be in 2..={}",
+            FREE_SPACE_MAP_PAGE_CAPACITY
+        );
+        let root_page_id = {
+            let mut guard = bpm.new_page_guarded()?.upgrade_write();
+            let page_id = guard.page_id();
+            FreeSpaceMapPage::new(guard.get_data_mut()).init(page_id, max_size, 0);
+            page_id
+        };
+        Some(FreeSpaceMap {
+            bpm,
+            root_page_id,
+            paths: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Open an existing free space map with its root at `root_page_id`, reading every map page
+    /// once to locate the heap pages. Return `None` if no frame is available.
+    pub fn open(bpm: Arc<dyn BufferPool>, root_page_id: PageId) -> Option<Self> {
+        let mut paths = HashMap::new();
+        let mut pending = vec![(root_page_id, Vec::new())];
+        while let Some((page_id, path)) = pending.pop() {
+            let guard = bpm.fetch_page_read(page_id)?;
+            let page = FreeSpaceMapPage::new(guard.get_data());
+            for (index, (entry_page_id, _)) in page.get_entries().into_iter().enumerate() {
+                let mut entry_path = path.clone();
+                entry_path.push(index);
+                if page.is_leaf() {
+                    paths.insert(entry_page_id, entry_path);
+                } else {
+                    pending.push((entry_page_id, entry_path));
+                }
+            }
+        }
+        Some(FreeSpaceMap {
+            bpm,
+            root_page_id,
+            paths: Mutex::new(paths),
+        })
+    }
+
+    /// Return the id of the root page of the map.
+    pub fn get_root_page_id(&self) -> PageId {
+        self.root_page_id
+    }
+
+    /// Return the number of heap pages tracked by the map.
+    pub fn get_num_pages(&self) -> usize {
+        self.paths.lock().len()
+    }
+
+    /// Return the number of levels of map pages, 1 while the root is the only leaf. Return
+    /// `None` if no frame is available.
+    pub fn get_height(&self) -> Option<usize> {
+        let _paths = self.paths.lock();
+        let guard = self.bpm.fetch_page_read(self.root_page_id)?;
+        Some(FreeSpaceMapPage::new(guard.get_data()).get_level() as usize + 1)
+    }
+
+    /// Return the heap page added to the map last, if any.
+    pub fn get_last_page_id(&self) -> Option<PageId> {
+        let _paths = self.paths.lock();
+        let mut page_id = self.root_page_id;
+        loop {
+            let guard = self.bpm.fetch_page_read(page_id)?;
+            let page = FreeSpaceMapPage::new(guard.get_data());
+            let entry_count = page.get_entry_count();
+            if entry_count == 0 {
+                return None;
+            }
+            page_id = page.get_entry(entry_count - 1).0;
+            if page.is_leaf() {
+                return Some(page_id);
+            }
+        }
+    }
+
+    /// Return the recorded free space category of a heap page, or `None` if it is not tracked.
+    pub fn get_category(&self, page_id: PageId) -> Option<u8> {
+        let paths = self.paths.lock();
+        let path = paths.get(&page_id)?;
+        let leaf_page_id = *self.get_path_page_ids(path)?.last().unwrap();
+        let leaf_guard = self.bpm.fetch_page_read(leaf_page_id)?;
+        Some(
+            FreeSpaceMapPage::new(leaf_guard.get_data())
+                .get_entry(*path.last().unwrap())
+                .1,
+        )
+    }
+
+    /// Return a heap page recorded with at least `needed_bytes` of free space, or `None` if there
+    /// is none (or no frame is available to read the map).
+    pub fn find_page(&self, needed_bytes: usize) -> Option<PageId> {
+        let category = required_category(needed_bytes);
+        if category > u8::MAX as usize {
+            return None;
+        }
+        let _paths = self.paths.lock();
+        let mut page_id = self.root_page_id;
+        loop {
+            let guard = self.bpm.fetch_page_read(page_id)?;
+            let page = FreeSpaceMapPage::new(guard.get_data());
+            page_id = page.get_entry(page.find_entry(category)?).0;
+            if page.is_leaf() {
+                return Some(page_id);
+            }
+        }
+    }
+
+    /// Record that a heap page has `free_bytes` of free space, adding the page to the map if it
+    /// is not tracked yet. Return false if no frame is available to update the map.
+    pub fn update(&self, page_id: PageId, free_bytes: usize) -> bool {
+        let category = free_space_category(free_bytes);
+        let mut paths = self.paths.lock();
+        let path = match paths.get(&page_id) {
+            Some(path) => path.clone(),
+            None => {
+                let Some(path) = self.append(&mut paths, page_id) else {
+                    return false;
+                };
+                paths.insert(page_id, path.clone());
+                path
+            }
+        };
+        self.set_category(&path, category)
+    }
+
+    /// Add an entry with category 0 for a heap page after the last one, adding map pages below
+    /// the lowest page of the rightmost path that has room, or a level if none has. Return the
+    /// path of the entry, or `None` if no frame is available.
+    fn append(
+        &self,
+        paths: &mut HashMap<PageId, Vec<usize>>,
+        page_id: PageId,
+    ) -> Option<Vec<usize>> {
+        // The rightmost path, from the root down to the last leaf: page id, entry count and
+        // whether the page is full.
+        let mut rightmost = Vec::new();
+        let mut current_page_id = self.root_page_id;
+        let max_size = loop {
+            let guard = self.bpm.fetch_page_read(current_page_id)?;
+            let page = FreeSpaceMapPage::new(guard.get_data());
+            let entry_count = page.get_entry_count();
+            rightmost.push((current_page_id, entry_count, page.is_full()));
+            // Only the root can be empty, and only while it is a leaf.
+            if page.is_leaf() {
+                break page.get_max_size();
+            }
+            current_page_id = page.get_entry(entry_count - 1).0;
+        };
+        let Some(depth) = rightmost.iter().rposition(|&(_, _, is_full)| !is_full) else {
+            self.grow_root(paths)?;
+            return self.append(paths, page_id);
+        };
+
+        // Build the pages between the page with room and the new entry bottom up, so that they
+        // are only linked in once they are complete.
+        let level = rightmost.len() - 1 - depth;
+        let mut created = Vec::with_capacity(level);
+        let mut child_page_id = page_id;
+        for child_level in 0..level {
+            let Some(guard) = self.bpm.new_page_guarded() else {
+                self.delete_pages(&created);
+                return None;
+            };
+            let mut guard = guard.upgrade_write();
+            let new_page_id = guard.page_id();
+            let mut page = FreeSpaceMapPage::new(guard.get_data_mut());
+            page.init(new_page_id, max_size, child_level as u32);
+            page.push_entry(child_page_id, 0);
+            created.push(new_page_id);
+            child_page_id = new_page_id;
+        }
+        let Some(mut guard) = self.bpm.fetch_page_write(rightmost[depth].0) else {
+            self.delete_pages(&created);
+            return None;
+        };
+        let index = FreeSpaceMapPage::new(guard.get_data_mut()).push_entry(child_page_id, 0);
+
+        let mut path: Vec<usize> = rightmost[..depth]
+            .iter()
+            .map(|&(_, entry_count, _)| entry_count - 1)
+            .collect();
+        path.push(index);
+        path.resize(rightmost.len(), 0);
+        Some(path)
+    }
+
+    /// Move the entries of the full root to a new child page and make that child the only entry
+    /// of the root, one level up. Every path gets one more step.
+    fn grow_root(&self, paths: &mut HashMap<PageId, Vec<usize>>) -> Option<()> {
+        let mut root_guard = self.bpm.fetch_page_write(self.root_page_id)?;
+        let mut child_guard = self.bpm.new_page_guarded()?.upgrade_write();
+        let child_page_id = child_guard.page_id();
+        let mut root = FreeSpaceMapPage::new(root_guard.get_data_mut());
+        let mut child = FreeSpaceMapPage::new(child_guard.get_data_mut());
+        child.init(child_page_id, root.get_max_size(), root.get_level());
+        child.set_entries(&root.get_entries());
+        let max_category = root.get_max_category();
+        root.set_level(root.get_level() + 1);
+        root.set_entries(&[(child_page_id, max_category)]);
+        for path in paths.values_mut() {
+            path.insert(0, 0);
+        }
+        Some(())
+    }
+
+    /// Set the category of the entry at `path`, then the category of every ancestor entry to the
+    /// largest category below it. Return false if no frame is available.
+    fn set_category(&self, path: &[usize], category: u8) -> bool {
+        let Some(page_ids) = self.get_path_page_ids(path) else {
+            return false;
+        };
+        let mut category = category;
+        for (&page_id, &index) in page_ids.iter().zip(path).rev() {
+            let Some(mut guard) = self.bpm.fetch_page_write(page_id) else {
+                return false;
+            };
+            // Pages are only dirtied when their entry changes; unchanged, the ancestors are
+            // up to date too.
+            if FreeSpaceMapPage::new(guard.get_data()).get_entry(index).1 == category {
+                return true;
+            }
+            let mut page = FreeSpaceMapPage::new(guard.get_data_mut());
+            page.set_category(index, category);
+            category = page.get_max_category();
+        }
+        true
+    }
+
+    /// Return the ids of the map pages along `path`, from the root down to the leaf.
+    fn get_path_page_ids(&self, path: &[usize]) -> Option<Vec<PageId>> {
+        let mut page_ids = vec![self.root_page_id];
+        for &index in &path[..path.len() - 1] {
+            let guard = self.bpm.fetch_page_read(*page_ids.last().unwrap())?;
+            page_ids.push(FreeSpaceMapPage::new(guard.get_data()).get_entry(index).0);
+        }
+        Some(page_ids)
+    }
+
+    /// Delete map pages that were never linked into the map.
+    fn delete_pages(&self, page_ids: &[PageId]) {
+        for &page_id in page_ids {
+            self.bpm.delete_page(page_id);
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use std::mem::size_of;
+
+use crate::buffer::buffer_pool::BufferPool;
+use crate::storage::page::overflow_page::{OverflowPage, OVERFLOW_PAGE_CAPACITY};
+use crate::utils::config::*;
+
+/// Size of a serialized `OverflowPointer`.
+pub const SIZE_OVERFLOW_POINTER: usize = size_of::<PageId>() + size_of::<u32>();
+
+/// OverflowPointer locates a value stored out of line, TOAST style, in a chain of
+/// `OverflowPage`s. The pointer is what gets stored in place of the value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OverflowPointer {
+    /// First page of the chain.
+    pub first_page_id: PageId,
+    /// Length of the value.
+    pub len: usize,
+}
+
+impl OverflowPointer {
+    /// Store `value` in a new chain of overflow pages and return a pointer to it. Return `None`
+    /// if the buffer pool runs out of frames, in which case nothing is left allocated.
+    pub fn write(bpm: &dyn BufferPool, value: &[u8]) -> Option<Self> {
+        // Chunks are written back to front so that every page is complete when it is written.
+        let mut next_page_id = INVALID_PAGE_ID;
+        for chunk in value.chunks(OVERFLOW_PAGE_CAPACITY).rev() {
+            let Some(guard) = bpm.new_page_guarded() else {
+                OverflowPointer::delete_chain(bpm, next_page_id);
+                return None;
+            };
+            let mut guard = guard.upgrade_write();
+            let page_id = guard.page_id();
+            OverflowPage::new(guard.get_data_mut()).init(page_id, next_page_id, chunk);
+            next_page_id = page_id;
+        }
+        Some(OverflowPointer {
+            first_page_id: next_page_id,
+            len: value.len(),
+        })
+    }
+
+    /// Read the value back from its chain. Return `None` if no frame is available.
+    pub fn read(&self, bpm: &dyn BufferPool) -> Option<Vec<u8>> {
+        let mut value = Vec::with_capacity(self.len);
+        let mut page_id = self.first_page_id;
+        while page_id != INVALID_PAGE_ID {
+            let guard = bpm.fetch_page_read(page_id)?;
+            let page = OverflowPage::new(guard.get_data());
+            value.extend_from_slice(page.get_chunk());
+            page_id = page.get_next_page_id();
+        }
+        debug_assert_eq!(value.len(), self.len);
+        Some(value)
+    }
+
+    /// Delete every page of the chain. Pages still pinned elsewhere (e.g. by a concurrent
+    /// reader) cannot be deleted and are left behind.
+    pub fn delete(&self, bpm: &dyn BufferPool) {
+        OverflowPointer::delete_chain(bpm, self.first_page_id);
+    }
+
+    /// Serialize the pointer.
+    pub fn to_bytes(&self) -> [u8; SIZE_OVERFLOW_POINTER] {
+        let mut bytes = [0; SIZE_OVERFLOW_POINTER];
+        bytes[..4].copy_from_slice(&self.first_page_id.to_le_bytes());
+        bytes[4..].copy_from_slice(&(self.len as u32).to_le_bytes());
+        bytes
+    }
+
+    /// Deserialize a pointer written by `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        assert_eq!(
+            bytes.len(),
+            SIZE_OVERFLOW_POINTER,
+            "invalid overflow pointer"
+        );
+        OverflowPointer {
+            first_page_id: PageId::from_le_bytes(bytes[..4].try_into().unwrap()),
+            len: u32::from_le_bytes(bytes[4..].try_into().unwrap()) as usize,
+        }
+    }
+
+    fn delete_chain(bpm: &dyn BufferPool, mut page_id: PageId) {
+        while page_id != INVALID_PAGE_ID {
+            let Some(guard) = bpm.fetch_page_read(page_id) else {
+                return;
+            };
+            let next_page_id = OverflowPage::new(guard.get_data()).get_next_page_id();
+            drop(guard);
+            bpm.delete_page(page_id);
+            page_id = next_page_id;
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use crate::buffer::buffer_pool::BufferPool;
+use crate::storage::page::header_page::*;
+use crate::storage::page::table_page::{TablePage, SIZE_SLOT, SIZE_TABLE_PAGE_HEADER};
+use crate::utils::config::*;
+use crate::utils::rid::RID;
+
+use super::free_space_map::FreeSpaceMap;
+use super::overflow::OverflowPointer;
+use super::table_iterator::TableIterator;
+
+/// Largest tuple that fits in an empty table page.
+pub const MAX_TUPLE_SIZE: usize = BUSTUB_PAGE_SIZE - SIZE_TABLE_PAGE_HEADER - SIZE_SLOT;
+/// Tuples larger than this are stored out of line in overflow pages, leaving only an
+/// `OverflowPointer` in the table page, so that a page still holds several tuples.
+pub const OVERFLOW_THRESHOLD: usize = BUSTUB_PAGE_SIZE / 4;
+/// Suffix of the header page record holding the free space map root of a named table heap.
+pub const FREE_SPACE_MAP_RECORD_SUFFIX: &str = ".fsm";
+
+/// TableHeap stores the tuples of a table in a doubly linked list of `TablePage`s allocated from
+/// the buffer pool. Tuples are addressed by their `RID`.
+///
+/// Tuples larger than `OVERFLOW_THRESHOLD` are moved to a chain of overflow pages (TOAST style);
+/// reads, updates and deletes follow and free the chain transparently.
+///
+/// Inserts find a page with room through a `FreeSpaceMap` instead of walking the list; a new page
+/// is appended only when no page has enough space.
+///
+/// The heap is found again from its first page id and the root page id of its free space map:
+/// keep both (e.g. in the catalog) and reopen the heap with `TableHeap::open` after a restart, or
+/// let `create_named` record them in the header page and reopen the heap with `open_named`.
+pub struct TableHeap {
+    bpm: Arc<dyn BufferPool>,
+    first_page_id: PageId,
+    free_space_map: FreeSpaceMap,
+    /// Last page of the list. Also serializes appending pages.
+    last_page_id: Mutex<PageId>,
+}
+
+impl TableHeap {
+    /// Create a new, empty table heap. Return `None` if the buffer pool has no frame available
+    /// for its first page.
+    pub fn new(bpm: Arc<dyn BufferPool>) -> Option<Self> {
+        let (first_page_id, free_space) = {
+            let mut guard = bpm.new_page_guarded()?.upgrade_write();
+            let page_id = guard.page_id();
+            let mut page = TablePage::new(guard.get_data_mut());
+            page.init(page_id, INVALID_PAGE_ID, INVALID_PAGE_ID);
+            (page_id, page.get_free_space())
+        };
+        let free_space_map = FreeSpaceMap::new(bpm.clone())?;
+        if !free_space_map.update(first_page_id, free_space) {
+            return None;
+        }
+        Some(TableHeap {
+            bpm,
+            first_page_id,
+            free_space_map,
+            last_page_id: Mutex::new(first_page_id),
+        })
+    }
+
+    /// Open an existing table heap starting at `first_page_id`, whose free space map has its root
+    /// at `free_space_map_root_page_id`. Return `None` if no frame is available to read the map.
+    pub fn open(
+        bpm: Arc<dyn BufferPool>,
+        first_page_id: PageId,
+        free_space_map_root_page_id: PageId,
+    ) -> Option<Self> {
+        let free_space_map = FreeSpaceMap::open(bpm.clone(), free_space_map_root_page_id)?;
+        // Pages are added to the map in the order they are appended to the list.
+        let last_page_id = free_space_map.get_last_page_id().unwrap_or(first_page_id);
+        Some(TableHeap {
+            bpm,
+            first_page_id,
+            free_space_map,
+            last_page_id: Mutex::new(last_page_id),
+        })
+    }
+
+    /// Create a new, empty table heap and record it in the header page under `name`. Return
+    /// `None` if the name is taken or cannot be recorded, the header page was never initialized,
+    /// or no frame is available.
+    pub fn create_named(bpm: Arc<dyn BufferPool>, name: &str) -> Option<Self> {
+        let free_space_map_name = format!("{}{}", name, FREE_SPACE_MAP_RECORD_SUFFIX);
+        let mut guard = bpm.fetch_page_write(HEADER_PAGE_ID)?;
+        let header = HeaderPage::new(guard.get_data());
+        if !header.is_header_page()
+            || !is_valid_record_name(name)
+            || !is_valid_record_name(&free_space_map_name)
+            || header.get_root_id(name).is_some()
+            || header.get_root_id(&free_space_map_name).is_some()
+            || header.get_record_count() + 2 > HEADER_PAGE_CAPACITY
+        {
+            return None;
+        }
+        // The header page stays latched so that concurrent creates cannot take the same name.
+        let heap = TableHeap::new(bpm.clone())?;
+        let mut header = HeaderPage::new(guard.get_data_mut());
+        assert!(header.insert_record(name, heap.get_first_page_id()));
+        assert!(header.insert_record(&free_space_map_name, heap.get_free_space_map_root_page_id()));
+        Some(heap)
+    }
+
+    /// Open the table heap recorded in the header page under `name` by `create_named`.
+    pub fn open_named(bpm: Arc<dyn BufferPool>, name: &str) -> Option<Self> {
+        let (first_page_id, free_space_map_root_page_id) = {
+            let guard = bpm.fetch_page_read(HEADER_PAGE_ID)?;
+            let header = HeaderPage::new(guard.get_data());
+            if !header.is_header_page() {
+                return None;
+            }
+            let free_space_map_name = format!("{}{}", name, FREE_SPACE_MAP_RECORD_SUFFIX);
+            (
+                header.get_root_id(name)?,
+                header.get_root_id(&free_space_map_name)?,
+            )
+        };
+        TableHeap::open(bpm, first_page_id, free_space_map_root_page_id)
+    }
+
+    /// Return the id of the first page of the heap.
+    pub fn get_first_page_id(&self) -> PageId {
+        self.first_page_id
+    }
+
+    /// Return the id of the root page of the heap's free space map.
+    pub fn get_free_space_map_root_page_id(&self) -> PageId {
+        self.free_space_map.get_root_page_id()
+    }
+
+    /// Return the free space map of the heap.
+    pub fn get_free_space_map(&self) -> &FreeSpaceMap {
+        &self.free_space_map
+    }
+
+    /// Return the buffer pool the heap lives in.
+    pub fn get_buffer_pool_manager(&self) -> &Arc<dyn BufferPool> {
+        &self.bpm
+    }
+
+    /// Insert a tuple into a page the free space map reports to have room, appending a new page
+    /// if none has. Return `None` if the tuple is empty or no frame is available.
+    pub fn insert_tuple(&self, tuple: &[u8]) -> Option<RID> {
+        if tuple.is_empty() {
+            return None;
+        }
+        if tuple.len() <= OVERFLOW_THRESHOLD {
+            return self.insert_stored(tuple, false);
+        }
+        let pointer = OverflowPointer::write(self.bpm.as_ref(), tuple)?;
+        let rid = self.insert_stored(&pointer.to_bytes(), true);
+        if rid.is_none() {
+            pointer.delete(self.bpm.as_ref());
+        }
+        rid
+    }
+
+    /// Return a copy of the tuple, or `None` if it does not exist or is marked deleted.
+    pub fn get_tuple(&self, rid: RID) -> Option<Vec<u8>> {
+        let guard = self.bpm.fetch_page_read(rid.page_id)?;
+        self.read_tuple(&TablePage::new(guard.get_data()), rid.slot)
+    }
+
+    /// Replace the tuple in place. Return false if it does not exist, is marked deleted, or the
+    /// new tuple does not fit in its page; the caller then has to delete and re-insert it.
+    pub fn update_tuple(&self, rid: RID, tuple: &[u8]) -> bool {
+        let pointer = if tuple.len() > OVERFLOW_THRESHOLD {
+            match OverflowPointer::write(self.bpm.as_ref(), tuple) {
+                Some(pointer) => Some(pointer),
+                None => return false,
+            }
+        } else {
+            None
+        };
+        let pointer_bytes = pointer.map(|pointer| pointer.to_bytes());
+        let stored = pointer_bytes.as_ref().map_or(tuple, |bytes| &bytes[..]);
+        let mut old_pointer = None;
+        let updated = self.with_page_mut(rid, |page| {
+            let old = Self::get_overflow_pointer(page, rid.slot);
+            if !page.update_tuple(rid.slot, stored) {
+                return false;
+            }
+            page.set_overflow(rid.slot, pointer.is_some());
+            old_pointer = old;
+            true
+        });
+        // Chains are freed only once no table page points to them.
+        match (updated, old_pointer, pointer) {
+            (true, Some(old_pointer), _) => old_pointer.delete(self.bpm.as_ref()),
+            (false, _, Some(pointer)) => pointer.delete(self.bpm.as_ref()),
+            _ => {}
+        }
+        updated
+    }
+
+    /// Mark the tuple deleted. It stays in its page until `apply_delete`.
+    pub fn mark_delete(&self, rid: RID) -> bool {
+        self.with_page_mut(rid, |page| page.mark_delete(rid.slot))
+    }
+
+    /// Undo `mark_delete`.
+    pub fn rollback_delete(&self, rid: RID) -> bool {
+        self.with_page_mut(rid, |page| page.rollback_delete(rid.slot))
+    }
+
+    /// Remove the tuple for good. Its slot and space may be reused by later inserts.
+    pub fn apply_delete(&self, rid: RID) -> bool {
+        let mut old_pointer = None;
+        let deleted = self.with_page_mut(rid, |page| {
+            old_pointer = Self::get_overflow_pointer(page, rid.slot);
+            page.apply_delete(rid.slot)
+        });
+        if let Some(old_pointer) = old_pointer {
+            old_pointer.delete(self.bpm.as_ref());
+        }
+        deleted
+    }
+
+    /// Return an iterator over the live tuples of the heap, in page then slot order.
+    pub fn iter(&self) -> TableIterator<'_> {
+        TableIterator::new(self)
+    }
+
+    /// Insert what is stored for a tuple: the tuple itself, or its overflow pointer.
+    fn insert_stored(&self, tuple: &[u8], overflow: bool) -> Option<RID> {
+        // The map rounds free space down, so it only misses when it is stale; each miss records
+        // the page's actual free space, which is below what the tuple needs.
+        while let Some(page_id) = self.free_space_map.find_page(tuple.len() + SIZE_SLOT) {
+            let mut guard = self.bpm.fetch_page_write(page_id)?;
+            let mut page = TablePage::new(guard.get_data_mut());
+            let slot = page.insert_tuple(tuple);
+            if let Some(slot) = slot {
+                page.set_overflow(slot, overflow);
+            }
+            self.free_space_map.update(page_id, page.get_free_space());
+            if let Some(slot) = slot {
+                return Some(RID::new(page_id, slot));
+            }
+        }
+
+        // No page has room: link a new one while holding the last page's write latch.
+        let mut last_page_id = self.last_page_id.lock();
+        let mut guard = self.bpm.fetch_page_write(*last_page_id)?;
+        let mut new_guard = self.bpm.new_page_guarded()?.upgrade_write();
+        let new_page_id = new_guard.page_id();
+        let mut new_page = TablePage::new(new_guard.get_data_mut());
+        new_page.init(new_page_id, *last_page_id, INVALID_PAGE_ID);
+        let slot = new_page.insert_tuple(tuple).unwrap();
+        new_page.set_overflow(slot, overflow);
+        TablePage::new(guard.get_data_mut()).set_next_page_id(new_page_id);
+        self.free_space_map
+            .update(new_page_id, new_page.get_free_space());
+        *last_page_id = new_page_id;
+        Some(RID::new(new_page_id, slot))
+    }
+
+    /// Return a copy of the live tuple in the slot, following its overflow pointer if it is
+    /// stored out of line. The caller holds the latch of the page, which keeps the chain from
+    /// being freed while it is read.
+    pub(crate) fn read_tuple<D: AsRef<[u8]>>(
+        &self,
+        page: &TablePage<D>,
+        slot: SlotOffset,
+    ) -> Option<Vec<u8>> {
+        let tuple = page.get_tuple(slot)?;
+        if page.is_overflow(slot) {
+            OverflowPointer::from_bytes(tuple).read(self.bpm.as_ref())
+        } else {
+            Some(tuple.to_vec())
+        }
+    }
+
+    fn get_overflow_pointer<D: AsRef<[u8]>>(
+        page: &TablePage<D>,
+        slot: SlotOffset,
+    ) -> Option<OverflowPointer> {
+        if !page.is_overflow(slot) {
+            return None;
+        }
+        page.get_raw_tuple(slot).map(OverflowPointer::from_bytes)
+    }
+
+    fn with_page_mut(&self, rid: RID, f: impl FnOnce(&mut TablePage<&mut [u8]>) -> bool) -> bool {
+        let Some(mut guard) = self.bpm.fetch_page_write(rid.page_id) else {
+            return false;
+        };
+        let mut page = TablePage::new(guard.get_data_mut());
+        let modified = f(&mut page);
+        if modified {
+            self.free_space_map
+                .update(rid.page_id, page.get_free_space());
+        }
+        modified
+    }
+}
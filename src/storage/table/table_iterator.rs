@@ -0,0 +1,69 @@
+use crate::storage::page::table_page::TablePage;
+use crate::utils::config::*;
+use crate::utils::rid::RID;
+
+use super::table_heap::TableHeap;
+
+/// TableIterator walks the live tuples of a `TableHeap`, in page then slot order, yielding each
+/// tuple with its `RID`.
+///
+/// Only one page is latched at a time, and only while the iterator advances; tuples inserted
+/// behind the iterator's position are not seen.
+///
+/// If no frame is available to read the next page (or an overflow chain), the iteration ends
+/// early and `is_interrupted` returns true.
+pub struct TableIterator<'a> {
+    table_heap: &'a TableHeap,
+    /// Position of the next tuple to look at.
+    rid: RID,
+    /// True if the iteration ended early because the buffer pool ran out of frames.
+    interrupted: bool,
+}
+
+impl<'a> TableIterator<'a> {
+    pub fn new(table_heap: &'a TableHeap) -> Self {
+        TableIterator {
+            table_heap,
+            rid: RID::new(table_heap.get_first_page_id(), 0),
+            interrupted: false,
+        }
+    }
+
+    /// Return true if the iteration ended before the end of the heap because no frame was
+    /// available, in which case the remaining tuples were not returned.
+    pub fn is_interrupted(&self) -> bool {
+        self.interrupted
+    }
+}
+
+impl Iterator for TableIterator<'_> {
+    type Item = (RID, Vec<u8>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bpm = self.table_heap.get_buffer_pool_manager();
+        while self.rid.page_id != INVALID_PAGE_ID && !self.interrupted {
+            let Some(guard) = bpm.fetch_page_read(self.rid.page_id) else {
+                self.interrupted = true;
+                return None;
+            };
+            let page = TablePage::new(guard.get_data());
+            while self.rid.slot < page.get_tuple_count() {
+                let rid = self.rid;
+                if page.get_tuple(rid.slot).is_none() {
+                    self.rid.slot += 1;
+                    continue;
+                }
+                // Stay on the tuple if its overflow chain cannot be read, so that it is not
+                // skipped silently.
+                let Some(tuple) = self.table_heap.read_tuple(&page, rid.slot) else {
+                    self.interrupted = true;
+                    return None;
+                };
+                self.rid.slot += 1;
+                return Some((rid, tuple));
+            }
+            self.rid = RID::new(page.get_next_page_id(), 0);
+        }
+        None
+    }
+}
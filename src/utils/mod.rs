@@ -1,2 +1,3 @@
 pub mod logutil;
-pub mod config;
\ No newline at end of file
+pub mod config;
+pub mod rid;
@@ -0,0 +1,29 @@
+use std::fmt;
+
+use crate::utils::config::*;
+
+/// RID (record identifier) addresses a tuple of a table heap: the page it lives in and its slot
+/// in that page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RID {
+    pub page_id: PageId,
+    pub slot: SlotOffset,
+}
+
+impl RID {
+    pub fn new(page_id: PageId, slot: SlotOffset) -> Self {
+        RID { page_id, slot }
+    }
+}
+
+impl Default for RID {
+    fn default() -> Self {
+        RID::new(INVALID_PAGE_ID, 0)
+    }
+}
+
+impl fmt::Display for RID {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "page_id: {} slot_num: {}", self.page_id, self.slot)
+    }
+}
@@ -0,0 +1,208 @@
+#[path = "common/mod.rs"]
+mod common;
+
+#[cfg(test)]
+mod b_plus_tree_bulk_load_test {
+    use bustub_rust::buffer::buffer_pool::*;
+    use bustub_rust::buffer::buffer_pool_manager::*;
+    use bustub_rust::storage::index::b_plus_tree::*;
+    use bustub_rust::storage::page::b_plus_tree_internal_page::*;
+    use bustub_rust::storage::page::b_plus_tree_leaf_page::*;
+    use bustub_rust::storage::page::b_plus_tree_page::*;
+    use bustub_rust::storage::table::table_heap::*;
+    use bustub_rust::utils::config::*;
+    use bustub_rust::utils::rid::RID;
+
+    use super::common::*;
+
+    fn rid(key: i64) -> RID {
+        RID::new(key as PageId, key.unsigned_abs() as SlotOffset)
+    }
+
+    /// Check the size bounds and key order of every page, and that all leaves are at the same
+    /// depth. Return the number of entries of each leaf, left to right.
+    fn leaf_sizes(bpm: &BufferPoolManager, tree: &BPlusTree<i64>) -> Vec<usize> {
+        fn visit(
+            bpm: &BufferPoolManager,
+            page_id: PageId,
+            is_root: bool,
+            depth: usize,
+            leaves: &mut Vec<(usize, usize)>,
+        ) {
+            let guard = bpm.fetch_page_read(page_id).unwrap();
+            let page = BPlusTreePage::new(guard.get_data());
+            assert!(page.get_size() <= page.get_max_size());
+            if page.is_leaf() {
+                assert!(is_root || page.get_size() >= page.get_min_size());
+                let keys: Vec<i64> = BPlusTreeLeafPage::<_, i64>::new(guard.get_data())
+                    .get_entries()
+                    .into_iter()
+                    .map(|(key, _)| key)
+                    .collect();
+                assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+                leaves.push((depth, page.get_size()));
+                return;
+            }
+            assert!(page.get_size() >= if is_root { 2 } else { page.get_min_size() });
+            let children: Vec<PageId> = BPlusTreeInternalPage::<_, i64>::new(guard.get_data())
+                .get_entries()
+                .into_iter()
+                .map(|(_, child)| child)
+                .collect();
+            drop(guard);
+            for child in children {
+                visit(bpm, child, false, depth + 1, leaves);
+            }
+        }
+
+        let mut leaves = Vec::new();
+        if !tree.is_empty() {
+            visit(bpm, tree.get_root_page_id(), true, 0, &mut leaves);
+        }
+        assert!(leaves.windows(2).all(|pair| pair[0].0 == pair[1].0));
+        leaves.into_iter().map(|(_, size)| size).collect()
+    }
+
+    #[test]
+    fn fill_factor_test() {
+        let db_name = db_file("b_plus_tree_bulk_load_fill");
+        let bpm = new_bpm(&db_name, 64);
+
+        // Leaves are filled to the fill factor, except the last ones, which are evened
+        // out rather than left below their min size.
+        for (name, fill_factor, num_keys, full) in [
+            ("full_pk", 1.0, 1000, 10),
+            ("half_pk", 0.5, 1000, 5),
+            ("tail_pk", 0.7, 1001, 7),
+            ("tiny_pk", 0.01, 100, 5),
+        ] {
+            let tree = BPlusTree::new(name, bpm.clone(), 10, 6).unwrap();
+            assert!(tree.bulk_load((0..num_keys).map(|key| (key, rid(key))), fill_factor));
+
+            let sizes = leaf_sizes(&bpm, &tree);
+            assert_eq!(sizes.iter().sum::<usize>(), num_keys as usize);
+            assert!(sizes[..sizes.len() - 2].iter().all(|&size| size == full));
+            for key in 0..num_keys {
+                assert_eq!(tree.get_value(&key), Some(rid(key)));
+            }
+            assert_eq!(tree.get_value(&num_keys), None);
+            let keys: Vec<i64> = tree.begin().map(|(key, _)| key).collect();
+            assert_eq!(keys, (0..num_keys).collect::<Vec<_>>());
+            let keys: Vec<i64> = tree.rbegin().map(|(key, _)| key).collect();
+            assert_eq!(keys, (0..num_keys).rev().collect::<Vec<_>>());
+        }
+
+        // Few keys make a single root leaf.
+        let tree = BPlusTree::new("small_pk", bpm.clone(), 10, 6).unwrap();
+        assert!(tree.bulk_load((0..3).map(|key| (key, rid(key))), 0.5));
+        assert_eq!(leaf_sizes(&bpm, &tree), vec![3]);
+
+        // Empty input leaves the tree empty.
+        let tree = BPlusTree::<i64>::new("empty_pk", bpm.clone(), 10, 6).unwrap();
+        assert!(tree.bulk_load(std::iter::empty(), 0.5));
+        assert!(tree.is_empty());
+
+        drop(tree);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn reject_test() {
+        let db_name = db_file("b_plus_tree_bulk_load_reject");
+        let bpm = new_bpm(&db_name, 64);
+        let tree = BPlusTree::new("orders_pk", bpm.clone(), 4, 4).unwrap();
+
+        // Unsorted or duplicate keys fail the load and leave the tree empty.
+        let unsorted = (0..100).chain(50..60).map(|key| (key, rid(key)));
+        assert!(!tree.bulk_load(unsorted, 1.0));
+        assert!(tree.is_empty());
+        assert!(tree.begin().is_end());
+        let duplicate = [(1, rid(1)), (1, rid(1))];
+        assert!(!tree.bulk_load(duplicate, 1.0));
+        assert!(tree.is_empty());
+
+        // A tree that is not empty is not loaded.
+        assert!(tree.insert(&7, rid(7)));
+        assert!(!tree.bulk_load([(1, rid(1))], 1.0));
+        assert_eq!(tree.get_value(&1), None);
+        assert_eq!(tree.get_value(&7), Some(rid(7)));
+
+        drop(tree);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn modify_after_load_test() {
+        let db_name = db_file("b_plus_tree_bulk_load_modify");
+        let bpm = new_bpm(&db_name, 64);
+        let tree = BPlusTree::new("orders_pk", bpm.clone(), 4, 4).unwrap();
+        assert!(tree.bulk_load((0..500).map(|key| (2 * key, rid(2 * key))), 0.75));
+
+        // The loaded tree splits and merges like one built by inserts.
+        for key in (1..1000).step_by(2) {
+            assert!(tree.insert(&key, rid(key)));
+        }
+        assert!(!tree.insert(&10, rid(10)));
+        leaf_sizes(&bpm, &tree);
+        for key in (0..1000).filter(|key| key % 3 != 0) {
+            assert!(tree.remove(&key));
+        }
+        leaf_sizes(&bpm, &tree);
+        let keys: Vec<i64> = tree.begin().map(|(key, _)| key).collect();
+        assert_eq!(keys, (0..1000).step_by(3).collect::<Vec<_>>());
+        let keys: Vec<i64> = tree.rbegin().map(|(key, _)| key).collect();
+        assert_eq!(
+            keys,
+            (0..1000)
+                .rev()
+                .filter(|key| key % 3 == 0)
+                .collect::<Vec<_>>()
+        );
+
+        drop(tree);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn create_index_test() {
+        let db_name = db_file("b_plus_tree_bulk_load_create_index");
+        let bpm = new_bpm(&db_name, 64);
+
+        // Index an existing table on a key column, as `CREATE INDEX` would: scan the
+        // heap, sort the (key, RID) pairs and load them.
+        let heap = TableHeap::new(bpm.clone()).unwrap();
+        let mut state = 88172645463325252u64;
+        let mut expected = Vec::new();
+        for i in 0..2000i64 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let key = (state % 1_000_000) as i64 * 2000 + i;
+            let mut tuple = key.to_le_bytes().to_vec();
+            tuple.extend_from_slice(format!("order-{}", i).as_bytes());
+            expected.push((key, heap.insert_tuple(&tuple).unwrap()));
+        }
+        let mut entries: Vec<(i64, RID)> = heap
+            .iter()
+            .map(|(rid, tuple)| (i64::from_le_bytes(tuple[..8].try_into().unwrap()), rid))
+            .collect();
+        entries.sort();
+
+        let tree = BPlusTree::new("orders_key_idx", bpm.clone(), 32, 32).unwrap();
+        assert!(tree.bulk_load(entries.iter().copied(), 0.9));
+        leaf_sizes(&bpm, &tree);
+        for &(key, rid) in &expected {
+            assert_eq!(tree.get_value(&key), Some(rid));
+            assert_eq!(heap.get_tuple(rid).unwrap()[..8], key.to_le_bytes());
+        }
+        assert_eq!(tree.begin().collect::<Vec<_>>(), entries);
+
+        drop(tree);
+        drop(heap);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+}
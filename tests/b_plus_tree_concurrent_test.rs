@@ -0,0 +1,191 @@
+#[path = "common/mod.rs"]
+mod common;
+
+#[cfg(test)]
+mod b_plus_tree_concurrent_test {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    use bustub_rust::buffer::buffer_pool::*;
+    use bustub_rust::buffer::buffer_pool_manager::*;
+    use bustub_rust::storage::disk::disk_manager::DiskManager;
+    use bustub_rust::storage::index::b_plus_tree::*;
+    use bustub_rust::storage::page::b_plus_tree_internal_page::*;
+    use bustub_rust::storage::page::b_plus_tree_leaf_page::*;
+    use bustub_rust::storage::page::b_plus_tree_page::*;
+    use bustub_rust::storage::page::header_page::*;
+    use bustub_rust::utils::config::*;
+    use bustub_rust::utils::rid::RID;
+
+    use super::common::*;
+
+    const NUM_THREADS: i64 = 8;
+    const POOL_SIZE: usize = 256;
+
+    fn new_tree(db_name: &str) -> (Arc<BufferPoolManager>, Arc<BPlusTree<i64>>) {
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(db_name)));
+        let bpm = Arc::new(BufferPoolManager::new(
+            POOL_SIZE,
+            disk_manager,
+            LRUK_REPLACER_K,
+        ));
+        assert!(ensure_header_page(bpm.as_ref()));
+        // Small pages make the tree deep and keep splits and merges frequent.
+        let tree = Arc::new(BPlusTree::new("stress_pk", bpm.clone(), 4, 5).unwrap());
+        (bpm, tree)
+    }
+
+    fn rid(key: i64) -> RID {
+        RID::new(key as PageId, key as SlotOffset)
+    }
+
+    /// Return every key of the tree by walking the leaf chain, checking the size bounds of the
+    /// leaves on the way.
+    fn leaf_keys(bpm: &BufferPoolManager, tree: &BPlusTree<i64>) -> Vec<i64> {
+        let mut keys = Vec::new();
+        if tree.is_empty() {
+            return keys;
+        }
+        let mut page_id = tree.get_root_page_id();
+        loop {
+            let guard = bpm.fetch_page_read(page_id).unwrap();
+            if BPlusTreePage::new(guard.get_data()).is_leaf() {
+                break;
+            }
+            page_id = BPlusTreeInternalPage::<_, i64>::new(guard.get_data()).value_at(0);
+        }
+        let root_page_id = tree.get_root_page_id();
+        while page_id != INVALID_PAGE_ID {
+            let guard = bpm.fetch_page_read(page_id).unwrap();
+            let leaf = BPlusTreeLeafPage::<_, i64>::new(guard.get_data());
+            assert!(leaf.get_size() <= leaf.get_max_size());
+            if page_id != root_page_id {
+                assert!(leaf.get_size() >= leaf.get_min_size());
+            }
+            keys.extend(leaf.get_entries().into_iter().map(|(key, _)| key));
+            page_id = leaf.get_next_page_id();
+        }
+        keys
+    }
+
+    #[test]
+    fn concurrent_insert_test() {
+        let db_name = db_file("b_plus_tree_concurrent_insert");
+        let (bpm, tree) = new_tree(&db_name);
+
+        // Threads insert interleaved keys, so they split the same pages.
+        let num_keys = 4000;
+        thread::scope(|s| {
+            for t in 0..NUM_THREADS {
+                let tree = &tree;
+                s.spawn(move || {
+                    for key in (t..num_keys).step_by(NUM_THREADS as usize) {
+                        assert!(tree.insert(&key, rid(key)));
+                    }
+                });
+            }
+        });
+
+        assert_eq!(leaf_keys(&bpm, &tree), (0..num_keys).collect::<Vec<_>>());
+        for key in 0..num_keys {
+            assert_eq!(tree.get_value(&key), Some(rid(key)));
+        }
+
+        drop(tree);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn concurrent_remove_test() {
+        let db_name = db_file("b_plus_tree_concurrent_remove");
+        let (bpm, tree) = new_tree(&db_name);
+        let num_keys = 4000;
+        for key in 0..num_keys {
+            assert!(tree.insert(&key, rid(key)));
+        }
+
+        // Threads remove interleaved keys, borrowing and merging concurrently, until
+        // the tree is empty.
+        thread::scope(|s| {
+            for t in 0..NUM_THREADS {
+                let tree = &tree;
+                s.spawn(move || {
+                    for key in (0..num_keys / NUM_THREADS)
+                        .rev()
+                        .map(|i| i * NUM_THREADS + t)
+                    {
+                        assert!(tree.remove(&key));
+                        assert!(!tree.remove(&key));
+                    }
+                });
+            }
+        });
+
+        assert!(tree.is_empty());
+        assert!(leaf_keys(&bpm, &tree).is_empty());
+        assert!(tree.insert(&1, rid(1)));
+
+        drop(tree);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn mixed_operations_test() {
+        let db_name = db_file("b_plus_tree_concurrent_mixed");
+        let (bpm, tree) = new_tree(&db_name);
+        // Even keys stay in the tree throughout; odd keys below 2000 are removed and the ones
+        // above are inserted while readers scan the even keys.
+        let num_keys = 4000;
+        for key in (0..num_keys).filter(|key| key % 2 == 0 || *key < 2000) {
+            assert!(tree.insert(&key, rid(key)));
+        }
+
+        let done = AtomicBool::new(false);
+        thread::scope(|s| {
+            let mut writers = Vec::new();
+            for t in 0..NUM_THREADS / 2 {
+                let tree = &tree;
+                writers.push(s.spawn(move || {
+                    for key in (1 + 2 * t..2000).step_by(NUM_THREADS as usize) {
+                        assert!(tree.remove(&key));
+                    }
+                    for key in (2001 + 2 * t..num_keys).step_by(NUM_THREADS as usize) {
+                        assert!(tree.insert(&key, rid(key)));
+                    }
+                }));
+            }
+            for t in 0..NUM_THREADS / 2 {
+                let (tree, done) = (&tree, &done);
+                s.spawn(move || {
+                    // Readers never miss a key that stays in the tree.
+                    let mut rounds = 0;
+                    while !done.load(Ordering::Acquire) || rounds < 2 {
+                        for key in (2 * t..num_keys).step_by(NUM_THREADS as usize) {
+                            assert_eq!(tree.get_value(&key), Some(rid(key)), "key {}", key);
+                        }
+                        rounds += 1;
+                    }
+                });
+            }
+            for writer in writers {
+                writer.join().unwrap();
+            }
+            done.store(true, Ordering::Release);
+        });
+
+        let expected: Vec<i64> = (0..num_keys)
+            .filter(|key| key % 2 == 0 || *key > 2000)
+            .collect();
+        assert_eq!(leaf_keys(&bpm, &tree), expected);
+        for key in (1..2000).step_by(2) {
+            assert_eq!(tree.get_value(&key), None);
+        }
+
+        drop(tree);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+}
@@ -0,0 +1,384 @@
+#[path = "common/mod.rs"]
+mod common;
+
+#[cfg(test)]
+mod b_plus_tree_test {
+    use std::collections::BTreeMap;
+
+    use bustub_rust::buffer::buffer_pool::*;
+    use bustub_rust::buffer::buffer_pool_manager::*;
+    use bustub_rust::storage::index::b_plus_tree::*;
+    use bustub_rust::storage::page::b_plus_tree_internal_page::*;
+    use bustub_rust::storage::page::b_plus_tree_leaf_page::*;
+    use bustub_rust::storage::page::b_plus_tree_page::*;
+    use bustub_rust::storage::page::header_page::*;
+    use bustub_rust::utils::config::*;
+    use bustub_rust::utils::rid::RID;
+
+    use super::common::*;
+
+    fn rid(key: i64) -> RID {
+        RID::new(key as PageId, key.unsigned_abs() as SlotOffset)
+    }
+
+    /// Check the size bounds and key ranges of every page under `page_id`, and return the depth
+    /// of its leaves.
+    fn check_subtree(
+        bpm: &BufferPoolManager,
+        page_id: PageId,
+        is_root: bool,
+        lower: Option<i64>,
+        upper: Option<i64>,
+    ) -> usize {
+        let guard = bpm.fetch_page_read(page_id).unwrap();
+        let page = BPlusTreePage::new(guard.get_data());
+        assert!(page.get_size() <= page.get_max_size());
+        if !is_root {
+            assert!(page.get_size() >= page.get_min_size());
+        }
+        let in_range = |key: i64| lower.is_none_or(|l| key >= l) && upper.is_none_or(|u| key < u);
+        if page.is_leaf() {
+            let leaf = BPlusTreeLeafPage::<_, i64>::new(guard.get_data());
+            assert!(leaf.get_size() > 0);
+            assert!(leaf.get_entries().iter().all(|(key, _)| in_range(*key)));
+            return 1;
+        }
+        let internal = BPlusTreeInternalPage::<_, i64>::new(guard.get_data());
+        assert!(internal.get_size() >= 2);
+        let entries = internal.get_entries();
+        let mut depths = Vec::new();
+        for (i, (_, child)) in entries.iter().enumerate() {
+            let child_lower = if i == 0 { lower } else { Some(entries[i].0) };
+            let child_upper = entries.get(i + 1).map(|(key, _)| *key).or(upper);
+            if i > 0 {
+                assert!(in_range(entries[i].0));
+            }
+            depths.push(check_subtree(bpm, *child, false, child_lower, child_upper));
+        }
+        assert!(depths.iter().all(|depth| *depth == depths[0]));
+        depths[0] + 1
+    }
+
+    /// Return every entry of the tree by walking the leaf chain from the leftmost leaf.
+    fn scan(bpm: &BufferPoolManager, tree: &BPlusTree<i64>) -> Vec<(i64, RID)> {
+        let mut entries = Vec::new();
+        if tree.is_empty() {
+            return entries;
+        }
+        let mut page_id = tree.get_root_page_id();
+        loop {
+            let guard = bpm.fetch_page_read(page_id).unwrap();
+            if BPlusTreePage::new(guard.get_data()).is_leaf() {
+                break;
+            }
+            page_id = BPlusTreeInternalPage::<_, i64>::new(guard.get_data()).value_at(0);
+        }
+        while page_id != INVALID_PAGE_ID {
+            let guard = bpm.fetch_page_read(page_id).unwrap();
+            let leaf = BPlusTreeLeafPage::<_, i64>::new(guard.get_data());
+            entries.extend(leaf.get_entries());
+            page_id = leaf.get_next_page_id();
+        }
+        entries
+    }
+
+    fn check_tree(bpm: &BufferPoolManager, tree: &BPlusTree<i64>) {
+        if !tree.is_empty() {
+            check_subtree(bpm, tree.get_root_page_id(), true, None, None);
+        }
+    }
+
+    #[test]
+    fn leaf_page_test() {
+        let mut data = vec![0u8; BUSTUB_PAGE_SIZE];
+        let mut leaf = BPlusTreeLeafPage::<_, i32>::new(&mut data[..]);
+        leaf.init(5, 4);
+        assert_eq!(leaf.get_page_id(), 5);
+        assert_eq!(leaf.get_size(), 0);
+        assert_eq!(leaf.get_max_size(), 4);
+        assert_eq!(leaf.get_min_size(), 2);
+        assert_eq!(leaf.get_prev_page_id(), INVALID_PAGE_ID);
+        assert_eq!(leaf.get_next_page_id(), INVALID_PAGE_ID);
+
+        for key in [30, 10, 20] {
+            let index = leaf.lookup(&key).unwrap_err();
+            leaf.insert_at(index, &key, RID::new(key, key as SlotOffset));
+        }
+        assert_eq!(leaf.lookup(&20), Ok(1));
+        assert_eq!(leaf.lookup(&25), Err(2));
+        assert_eq!(leaf.key_at(0), 10);
+        assert_eq!(leaf.value_at(2), RID::new(30, 30));
+
+        leaf.remove_at(0);
+        assert_eq!(
+            leaf.get_entries(),
+            vec![(20, RID::new(20, 20)), (30, RID::new(30, 30))]
+        );
+        leaf.set_prev_page_id(3);
+        leaf.set_next_page_id(9);
+        assert_eq!(leaf.get_prev_page_id(), 3);
+        assert_eq!(leaf.get_next_page_id(), 9);
+        assert!(BPlusTreePage::new(&data[..]).is_leaf());
+    }
+
+    #[test]
+    fn internal_page_test() {
+        let mut data = vec![0u8; BUSTUB_PAGE_SIZE];
+        let mut internal = BPlusTreeInternalPage::<_, i32>::new(&mut data[..]);
+        internal.init(7, 4);
+        assert_eq!(internal.get_page_id(), 7);
+        assert_eq!(internal.get_min_size(), 2);
+        internal.set_entries(&[(0, 100), (10, 101), (20, 102)]);
+
+        // Children cover the keys from their key up to the next one.
+        assert_eq!(internal.lookup_child(&-5), 0);
+        assert_eq!(internal.lookup_child(&9), 0);
+        assert_eq!(internal.lookup_child(&10), 1);
+        assert_eq!(internal.lookup_child(&19), 1);
+        assert_eq!(internal.lookup_child(&20), 2);
+        assert_eq!(internal.lookup_child(&1000), 2);
+
+        internal.insert_at(2, &15, 103);
+        assert_eq!(internal.value_at(2), 103);
+        assert_eq!(internal.lookup_child(&17), 2);
+        internal.set_key_at(3, &25);
+        assert_eq!(internal.lookup_child(&20), 2);
+        internal.remove_at(0);
+        assert_eq!(internal.get_size(), 3);
+        assert_eq!(internal.value_at(0), 101);
+        assert!(!BPlusTreePage::new(&data[..]).is_leaf());
+    }
+
+    #[test]
+    fn insert_get_test() {
+        let db_name = db_file("b_plus_tree_insert_get");
+        let bpm = new_bpm(&db_name, BUFFER_POOL_SIZE);
+        let tree = BPlusTree::<i64>::new("orders_pk", bpm.clone(), 3, 3).unwrap();
+        assert!(tree.is_empty());
+        assert_eq!(tree.get_value(&1), None);
+        assert!(!tree.remove(&1));
+
+        // Ascending, descending and interleaved inserts with tiny pages split often.
+        let keys: Vec<i64> = (0..100)
+            .chain((200..300).rev())
+            .chain((0..100).map(|i| if i % 2 == 0 { 100 + i / 2 } else { 199 - i / 2 }))
+            .collect();
+        for key in &keys {
+            assert!(tree.insert(key, rid(*key)), "insert {}", key);
+        }
+        check_tree(&bpm, &tree);
+        for key in 0..300 {
+            assert_eq!(tree.get_value(&key), Some(rid(key)));
+        }
+        assert_eq!(tree.get_value(&-1), None);
+        assert_eq!(tree.get_value(&300), None);
+        assert_eq!(
+            scan(&bpm, &tree),
+            (0..300).map(|k| (k, rid(k))).collect::<Vec<_>>()
+        );
+
+        // Keys are unique; a duplicate insert keeps the first value.
+        assert!(!tree.insert(&42, rid(1000)));
+        assert_eq!(tree.get_value(&42), Some(rid(42)));
+
+        drop(tree);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn remove_test() {
+        let db_name = db_file("b_plus_tree_remove");
+        let bpm = new_bpm(&db_name, BUFFER_POOL_SIZE);
+        let tree = BPlusTree::<i64>::new("orders_pk", bpm.clone(), 4, 4).unwrap();
+        for key in 0..200 {
+            assert!(tree.insert(&key, rid(key)));
+        }
+
+        // Removing every other key borrows from and merges with siblings.
+        for key in (0..200).step_by(2) {
+            assert!(tree.remove(&key), "remove {}", key);
+            check_tree(&bpm, &tree);
+        }
+        assert!(!tree.remove(&0));
+        for key in 0..200 {
+            let expected = (key % 2 == 1).then(|| rid(key));
+            assert_eq!(tree.get_value(&key), expected);
+        }
+
+        // Removing the rest from both ends shrinks the tree down to nothing.
+        let mut odd: Vec<i64> = (0..200).filter(|k| k % 2 == 1).collect();
+        while !odd.is_empty() {
+            let key = if odd.len().is_multiple_of(2) {
+                odd.remove(0)
+            } else {
+                odd.pop().unwrap()
+            };
+            assert!(tree.remove(&key), "remove {}", key);
+            check_tree(&bpm, &tree);
+            assert_eq!(
+                scan(&bpm, &tree),
+                odd.iter().map(|k| (*k, rid(*k))).collect::<Vec<_>>()
+            );
+        }
+        assert!(tree.is_empty());
+        assert_eq!(tree.get_root_page_id(), INVALID_PAGE_ID);
+
+        // The emptied tree can grow again.
+        assert!(tree.insert(&7, rid(7)));
+        assert_eq!(tree.get_value(&7), Some(rid(7)));
+
+        drop(tree);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn random_operations_test() {
+        let db_name = db_file("b_plus_tree_random");
+        let bpm = new_bpm(&db_name, BUFFER_POOL_SIZE);
+        let tree = BPlusTree::<i64>::new("random_pk", bpm.clone(), 5, 6).unwrap();
+        let mut expected = BTreeMap::new();
+        let mut state: u64 = 0x2545_F491_4F6C_DD1D;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        // Random inserts and removes agree with a BTreeMap.
+        for round in 0..5000 {
+            let key = (next() % 500) as i64 - 250;
+            if next().is_multiple_of(3) {
+                assert_eq!(tree.remove(&key), expected.remove(&key).is_some());
+            } else {
+                let inserted = !expected.contains_key(&key);
+                assert_eq!(tree.insert(&key, rid(key)), inserted);
+                expected.entry(key).or_insert(rid(key));
+            }
+            if round % 500 == 0 {
+                check_tree(&bpm, &tree);
+            }
+        }
+        check_tree(&bpm, &tree);
+        assert_eq!(scan(&bpm, &tree), expected.into_iter().collect::<Vec<_>>());
+
+        drop(tree);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn exhausted_pool_test() {
+        let db_name = db_file("b_plus_tree_exhausted_pool");
+        let bpm = new_bpm(&db_name, BUFFER_POOL_SIZE);
+        let tree = BPlusTree::<i64>::new("orders_pk", bpm.clone(), 3, 3).unwrap();
+        for key in (0..60).step_by(2) {
+            assert!(tree.insert(&key, rid(key)));
+        }
+
+        // With every frame pinned, nothing can be read or changed.
+        let mut pinned = Vec::new();
+        while let Some(guard) = bpm.new_page_guarded() {
+            pinned.push(guard);
+        }
+        assert_eq!(tree.get_value(&0), None);
+        assert!(!tree.insert(&1, rid(1)));
+        assert!(!tree.remove(&0));
+        assert_eq!(tree.begin().count(), 0);
+
+        // With a few frames left, inserts into a leaf with room succeed, while splits fail and
+        // leave the tree unchanged.
+        pinned.truncate(pinned.len() - 3);
+        let mut expected: BTreeMap<i64, RID> = (0..60).step_by(2).map(|k| (k, rid(k))).collect();
+        let mut outcomes = Vec::new();
+        for key in (1..60).step_by(2) {
+            let inserted = tree.insert(&key, rid(key));
+            if inserted {
+                expected.insert(key, rid(key));
+            }
+            outcomes.push(inserted);
+        }
+        assert!(outcomes.contains(&true) && outcomes.contains(&false));
+        drop(pinned);
+        check_tree(&bpm, &tree);
+        assert_eq!(
+            scan(&bpm, &tree),
+            expected.clone().into_iter().collect::<Vec<_>>()
+        );
+
+        // Removes either happen or leave the key in place; a page that could not be rebalanced
+        // stays searchable.
+        let pinned: Vec<_> = (0..BUFFER_POOL_SIZE - 3)
+            .map(|_| bpm.new_page_guarded().unwrap())
+            .collect();
+        for key in 0..60 {
+            if tree.remove(&key) {
+                expected.remove(&key);
+            }
+        }
+        drop(pinned);
+        assert_eq!(
+            scan(&bpm, &tree),
+            expected.clone().into_iter().collect::<Vec<_>>()
+        );
+        for key in 0..60 {
+            assert_eq!(tree.get_value(&key), expected.get(&key).copied());
+        }
+
+        // No pin is left behind.
+        let pinned: Vec<_> = (0..BUFFER_POOL_SIZE)
+            .map(|_| bpm.new_page_guarded().unwrap())
+            .collect();
+        drop(pinned);
+
+        drop(tree);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn persistence_test() {
+        let db_name = db_file("b_plus_tree_persistence");
+        {
+            let bpm = new_bpm(&db_name, BUFFER_POOL_SIZE);
+            let tree = BPlusTree::<i32>::new("orders_pk", bpm.clone(), 16, 16).unwrap();
+            let other = BPlusTree::<i32>::new("customers_pk", bpm.clone(), 16, 16).unwrap();
+            for key in 0..1000 {
+                assert!(tree.insert(&key, RID::new(key, 0)));
+            }
+            assert!(other.insert(&1, RID::new(1, 1)));
+            let guard = bpm.fetch_page_read(HEADER_PAGE_ID).unwrap();
+            assert_eq!(
+                HeaderPage::new(guard.get_data()).get_root_id("orders_pk"),
+                Some(tree.get_root_page_id())
+            );
+            drop(guard);
+            drop(tree);
+            drop(other);
+            bpm.flush_all_pages();
+        }
+
+        // After reopening, the trees are found by name in the header page.
+        let bpm = new_bpm(&db_name, BUFFER_POOL_SIZE);
+        let tree = BPlusTree::<i32>::new("orders_pk", bpm.clone(), 16, 16).unwrap();
+        let other = BPlusTree::<i32>::new("customers_pk", bpm.clone(), 16, 16).unwrap();
+        for key in 0..1000 {
+            assert_eq!(tree.get_value(&key), Some(RID::new(key, 0)));
+        }
+        assert_eq!(other.get_value(&1), Some(RID::new(1, 1)));
+        assert_eq!(other.get_value(&2), None);
+        assert!(tree.remove(&500));
+        assert!(tree.insert(&1000, RID::new(1000, 0)));
+        assert_eq!(tree.get_value(&500), None);
+
+        // Names that cannot be recorded are rejected.
+        assert!(BPlusTree::<i32>::new("", bpm.clone(), 16, 16).is_none());
+
+        drop(tree);
+        drop(other);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+}
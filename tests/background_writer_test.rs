@@ -0,0 +1,106 @@
+#[path = "common/mod.rs"]
+mod common;
+
+#[cfg(test)]
+mod background_writer_test {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    use bustub_rust::buffer::background_writer::*;
+    use bustub_rust::buffer::buffer_pool::*;
+    use bustub_rust::buffer::buffer_pool_manager::*;
+    use bustub_rust::buffer::replacer::*;
+    use bustub_rust::storage::disk::disk_manager::DiskManager;
+    use bustub_rust::utils::config::*;
+
+    use super::common::*;
+
+    #[test]
+    fn test_dirty_page_table() {
+        let db_name = db_file("bgw_dirty_page_table");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = BufferPoolManager::new(BUFFER_POOL_SIZE, disk_manager, LRUK_REPLACER_K);
+
+        let page_ids: Vec<PageId> = (0..4)
+            .map(|i| {
+                let mut guard = bpm.new_page_guarded().unwrap().upgrade_write();
+                guard.set_lsn(10 - i);
+                guard.page_id()
+            })
+            .collect();
+        bpm.flush_all_pages();
+        assert!(bpm.get_dirty_page_table().is_empty());
+
+        // Pages enter the table as soon as they are modified, with the LSN they had before.
+        let mut guard = bpm.fetch_page_write(page_ids[0]).unwrap();
+        guard.set_lsn(20);
+        assert_eq!(bpm.get_dirty_page_table(), HashMap::from([(page_ids[0], 10)]));
+        drop(guard);
+        bpm.fetch_page_write(page_ids[1]).unwrap().set_lsn(21);
+        drop(bpm.fetch_page_write(page_ids[2]).unwrap());
+        assert_eq!(
+            bpm.get_dirty_page_table(),
+            HashMap::from([(page_ids[0], 10), (page_ids[1], 9)])
+        );
+
+        // Dirtying the page again keeps the original recovery LSN.
+        bpm.fetch_page_write(page_ids[0]).unwrap().set_lsn(30);
+        assert_eq!(bpm.get_dirty_page_table()[&page_ids[0]], 10);
+
+        // Pinned pages are skipped and the oldest recovery LSN is written first.
+        bpm.fetch_page_write(page_ids[3]).unwrap().set_lsn(22);
+        let pinned = bpm.fetch_page(page_ids[3], AccessType::Unknown).unwrap();
+        assert_eq!(bpm.write_back_dirty_pages(1), 1);
+        assert_eq!(
+            bpm.get_dirty_page_table(),
+            HashMap::from([(page_ids[0], 10), (page_ids[3], 7)])
+        );
+        assert_eq!(bpm.write_back_dirty_pages(10), 1);
+        assert_eq!(bpm.get_dirty_page_table(), HashMap::from([(page_ids[3], 7)]));
+        assert!(!bpm.get_pages().iter().any(|page| page.is_dirty() && page.get_pin_count() == 0));
+
+        // Flushing removes the page from the table.
+        assert!(bpm.flush_page(pinned.get_page_id()));
+        assert!(bpm.get_dirty_page_table().is_empty());
+        assert!(bpm.unpin_page(page_ids[3], false));
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn test_background_writer() {
+        let db_name = db_file("bgw_background_writer");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = Arc::new(BufferPoolManager::new(BUFFER_POOL_SIZE, disk_manager.clone(), LRUK_REPLACER_K));
+        let writer = BackgroundWriter::start(bpm.clone(), Duration::from_millis(5), 2);
+
+        for i in 0..BUFFER_POOL_SIZE as u32 {
+            let mut guard = bpm.new_page_guarded().unwrap().upgrade_write();
+            guard.get_data_mut()[..4].copy_from_slice(&i.to_le_bytes());
+        }
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !bpm.get_dirty_page_table().is_empty() {
+            assert!(Instant::now() < deadline, "background writer did not catch up");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        drop(writer);
+        assert!(disk_manager.lock().unwrap().get_num_writes() >= BUFFER_POOL_SIZE as u64);
+
+        // Every frame is clean, so new pages evict without writing anything back.
+        let num_writes = disk_manager.lock().unwrap().get_num_writes();
+        for _ in 0..BUFFER_POOL_SIZE {
+            bpm.new_page_guarded().unwrap();
+        }
+        assert_eq!(disk_manager.lock().unwrap().get_num_writes(), num_writes);
+
+        let mut page_data = vec![0u8; BUSTUB_PAGE_SIZE];
+        disk_manager.lock().unwrap().read_page(3, &mut page_data);
+        assert_eq!(&page_data[..4], &3u32.to_le_bytes());
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+}
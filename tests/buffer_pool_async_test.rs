@@ -0,0 +1,131 @@
+#[path = "common/mod.rs"]
+mod common;
+
+#[cfg(test)]
+mod buffer_pool_async_test {
+    use std::sync::{Arc, Mutex};
+
+    use bustub_rust::buffer::buffer_pool::*;
+    use bustub_rust::buffer::buffer_pool_manager::*;
+    use bustub_rust::buffer::parallel_buffer_pool_manager::*;
+    use bustub_rust::storage::disk::disk_manager::DiskManager;
+    use bustub_rust::utils::config::*;
+
+    use super::common::*;
+
+    #[test]
+    fn fetch_async_test() {
+        let db_name = db_file("bpm_async_fetch");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = BufferPoolManager::new(2, disk_manager, LRUK_REPLACER_K);
+        // The synchronous API blocks on disk I/O, so it must not be used from async tasks.
+        for i in 0..8u32 {
+            let mut guard = bpm.new_page_guarded().unwrap().upgrade_write();
+            guard.get_data_mut()[..4].copy_from_slice(&i.to_le_bytes());
+        }
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(run_fetch_async(&bpm));
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    async fn run_fetch_async(bpm: &BufferPoolManager) {
+
+        // Pages evicted by the synchronous API are read back asynchronously, and
+        // modifications made through async write guards survive eviction.
+        for page_id in 0..8 {
+            let mut guard = bpm.fetch_page_write_async(page_id).await.unwrap();
+            assert_eq!(&guard.get_data()[..4], &(page_id as u32).to_le_bytes());
+            guard.get_data_mut()[4] = 42;
+        }
+        for page_id in 0..8 {
+            let guard = bpm.fetch_page_read_async(page_id).await.unwrap();
+            assert_eq!(guard.get_data()[4], 42);
+        }
+
+        // A fetch dropped while it waits, for the latch or for the read, leaves no pin
+        // behind, and the page can still be read back intact.
+        {
+            let writer = bpm.fetch_page_write_async(3).await.unwrap();
+            tokio::select! {
+                biased;
+                _ = bpm.fetch_page_read_async(3) => unreachable!("the page is write latched"),
+                _ = tokio::task::yield_now() => {}
+            }
+            assert_eq!(writer.page().get_pin_count(), 1);
+        }
+        for page_id in [5, 6] {
+            tokio::select! {
+                biased;
+                _ = bpm.fetch_page_read_async(page_id) => {}
+                _ = tokio::task::yield_now() => {}
+            }
+        }
+        for page_id in 0..8 {
+            let guard = bpm.fetch_page_read_async(page_id).await.unwrap();
+            assert_eq!(&guard.get_data()[..4], &(page_id as u32).to_le_bytes());
+            assert_eq!(guard.page().get_pin_count(), 1);
+        }
+
+        // With every frame pinned, an async fetch fails instead of waiting.
+        let _first = bpm.fetch_page_read_async(0).await.unwrap();
+        let _second = bpm.fetch_page_read_async(1).await.unwrap();
+        assert!(bpm.fetch_page_read_async(2).await.is_none());
+        assert!(bpm.fetch_page_read_async(INVALID_PAGE_ID).await.is_none());
+    }
+
+    #[test]
+    fn concurrent_tasks_test() {
+        let db_name = db_file("bpm_async_concurrent");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = Arc::new(ParallelBufferPoolManager::new(
+            2,
+            4,
+            disk_manager,
+            LRUK_REPLACER_K,
+        ));
+        let num_pages = 32;
+        for _ in 0..num_pages {
+            bpm.new_page_guarded().unwrap();
+        }
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(4)
+            .build()
+            .unwrap();
+        runtime.block_on(run_concurrent_tasks(bpm.clone(), num_pages));
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    async fn run_concurrent_tasks(bpm: Arc<ParallelBufferPoolManager>, num_pages: PageId) {
+        // Every task increments a counter on every page; no increment may be lost.
+        let tasks: Vec<_> = (0..8)
+            .map(|_| {
+                let bpm = bpm.clone();
+                tokio::spawn(async move {
+                    for page_id in 0..num_pages {
+                        let mut guard = loop {
+                            match bpm.fetch_page_write_async(page_id).await {
+                                Some(guard) => break guard,
+                                None => tokio::task::yield_now().await,
+                            }
+                        };
+                        let data = guard.get_data_mut();
+                        let count = u32::from_le_bytes(data[..4].try_into().unwrap());
+                        data[..4].copy_from_slice(&(count + 1).to_le_bytes());
+                    }
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        for page_id in 0..num_pages {
+            let guard = bpm.fetch_page_read_async(page_id).await.unwrap();
+            assert_eq!(u32::from_le_bytes(guard.get_data()[..4].try_into().unwrap()), 8);
+        }
+    }
+}
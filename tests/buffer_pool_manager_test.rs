@@ -0,0 +1,382 @@
+#[path = "common/mod.rs"]
+mod common;
+
+#[cfg(test)]
+mod buffer_pool_manager_test {
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    use bustub_rust::buffer::buffer_access_strategy::*;
+    use bustub_rust::buffer::buffer_pool::*;
+    use bustub_rust::buffer::buffer_pool_manager::*;
+    use bustub_rust::buffer::replacer::*;
+    use bustub_rust::storage::disk::disk_manager::DiskManager;
+    use bustub_rust::utils::config::*;
+
+    use super::common::*;
+
+    fn run_sample_test(name: &str, policy: ReplacerPolicy) {
+        let db_name = db_file(name);
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = BufferPoolManager::new_with_policy(BUFFER_POOL_SIZE, disk_manager, policy);
+
+        // The buffer pool is empty. We should be able to create a new page.
+        let page0 = bpm.new_page().unwrap();
+        assert_eq!(page0.get_page_id(), 0);
+
+        // Once we have a page, we should be able to read and write content.
+        {
+            let mut guard = bpm.fetch_page_write(0).unwrap();
+            guard.get_data_mut()[..5].copy_from_slice(b"Hello");
+            assert_eq!(&guard.get_data()[..5], b"Hello");
+        }
+
+        // We should be able to create new pages until we fill up the buffer pool.
+        for _ in 1..BUFFER_POOL_SIZE {
+            assert!(bpm.new_page().is_some());
+        }
+
+        // Once the buffer pool is full, we should not be able to create any new pages.
+        for _ in BUFFER_POOL_SIZE..BUFFER_POOL_SIZE * 2 {
+            assert!(bpm.new_page().is_none());
+        }
+
+        // After unpinning pages {0, 1, 2, 3, 4} and pinning another 4 new pages,
+        // there would still be one buffer page left for reading page 0.
+        for page_id in 0..5 {
+            assert!(bpm.unpin_page(page_id, true));
+        }
+        for _ in 0..4 {
+            assert!(bpm.new_page().is_some());
+        }
+
+        // We should be able to fetch the data we wrote a while ago.
+        let page0 = bpm.fetch_page_read(0).unwrap();
+        assert_eq!(&page0.get_data()[..5], b"Hello");
+
+        // If we unpin page 0 and then make a new page, all the buffer pages should
+        // now be pinned. Fetching page 0 again should fail.
+        drop(page0);
+        assert!(bpm.new_page().is_some());
+        assert!(bpm.fetch_page(0, AccessType::Unknown).is_none());
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn test_sample_lru_k() {
+        run_sample_test("bpm_sample_lru_k", ReplacerPolicy::LruK(LRUK_REPLACER_K));
+    }
+
+    #[test]
+    fn test_sample_clock() {
+        run_sample_test("bpm_sample_clock", ReplacerPolicy::Clock);
+    }
+
+    #[test]
+    fn test_sample_arc() {
+        run_sample_test("bpm_sample_arc", ReplacerPolicy::Adaptive);
+    }
+
+    #[test]
+    fn test_sample_two_queue() {
+        run_sample_test("bpm_sample_two_queue", ReplacerPolicy::TwoQueue);
+    }
+
+    #[test]
+    fn test_page_guards() {
+        let db_name = db_file("bpm_page_guards");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = BufferPoolManager::new(BUFFER_POOL_SIZE, disk_manager, LRUK_REPLACER_K);
+
+        let page_id = {
+            let guard = bpm.new_page_guarded().unwrap();
+            let mut guard = guard.upgrade_write();
+            guard.get_data_mut()[..4].copy_from_slice(b"data");
+            assert_eq!(guard.page().get_pin_count(), 1);
+            guard.page_id()
+        };
+        {
+            let guard = bpm.fetch_page_read(page_id).unwrap();
+            let other = bpm.fetch_page_read(page_id).unwrap();
+            assert_eq!(guard.page().get_pin_count(), 2);
+            assert!(guard.page().is_dirty());
+            assert_eq!(&other.get_data()[..4], b"data");
+        }
+        let page = bpm.fetch_page(page_id, AccessType::Unknown).unwrap();
+        assert_eq!(page.get_pin_count(), 1);
+        assert!(bpm.unpin_page(page_id, false));
+        assert!(!bpm.unpin_page(page_id, false));
+
+        // Pages written back on eviction are read back intact.
+        for _ in 0..BUFFER_POOL_SIZE {
+            let guard = bpm.new_page_guarded().unwrap();
+            assert_ne!(guard.page_id(), page_id);
+        }
+        assert_eq!(&bpm.fetch_page_read(page_id).unwrap().get_data()[..4], b"data");
+        assert!(bpm.delete_page(page_id));
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn test_deleted_pages_are_reused() {
+        let db_name = db_file("bpm_deleted_pages_are_reused");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = BufferPoolManager::new(BUFFER_POOL_SIZE, disk_manager, LRUK_REPLACER_K);
+
+        let page_ids: Vec<PageId> = (1..=3u8)
+            .map(|i| {
+                let mut guard = bpm.new_page_guarded().unwrap().upgrade_write();
+                guard.get_data_mut()[0] = i;
+                guard.page_id()
+            })
+            .collect();
+        bpm.flush_all_pages();
+        assert!(bpm.delete_page(page_ids[2]));
+        assert!(bpm.delete_page(page_ids[0]));
+        // A page that is already free, or was never allocated, is not deallocated again.
+        assert!(!bpm.delete_page(page_ids[0]));
+        assert!(!bpm.delete_page(page_ids[2] + 1));
+        assert!(!bpm.is_page_allocated(page_ids[0]));
+        assert!(bpm.is_page_allocated(page_ids[1]));
+
+        // The free pages survive reopening the database.
+        drop(bpm);
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = BufferPoolManager::new(BUFFER_POOL_SIZE, disk_manager, LRUK_REPLACER_K);
+        assert!(!bpm.delete_page(page_ids[0]));
+        assert!(bpm.is_page_allocated(page_ids[1]));
+
+        // The lowest deallocated page is handed out first, and the file only grows once there
+        // are none left.
+        let reused: Vec<PageId> = (0..3)
+            .map(|_| bpm.new_page_guarded().unwrap().page_id())
+            .collect();
+        assert_eq!(reused, vec![page_ids[0], page_ids[2], page_ids[2] + 1]);
+
+        // A reused page starts out zeroed, even once it is evicted without being modified.
+        for _ in 0..BUFFER_POOL_SIZE {
+            bpm.new_page_guarded().unwrap();
+        }
+        assert_eq!(bpm.fetch_page_read(page_ids[0]).unwrap().get_data()[0], 0);
+        assert_eq!(bpm.fetch_page_read(page_ids[1]).unwrap().get_data()[0], 2);
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    fn resident_pages(bpm: &BufferPoolManager) -> Vec<PageId> {
+        bpm.get_pages().iter().map(|page| page.get_page_id()).collect()
+    }
+
+    /// Load a hot working set of `num_hot` pages, then scan every other page once.
+    fn scan_after_hot_set(
+        bpm: &BufferPoolManager,
+        num_pages: i32,
+        num_hot: i32,
+        strategy: Option<&BufferAccessStrategy>,
+    ) {
+        for _ in 0..num_pages {
+            let mut guard = bpm.new_page_guarded().unwrap().upgrade_write();
+            let page_id = guard.page_id();
+            guard.get_data_mut()[..4].copy_from_slice(&page_id.to_le_bytes());
+        }
+        for _ in 0..3 {
+            for page_id in 0..num_hot {
+                drop(bpm.fetch_page_read(page_id).unwrap());
+            }
+        }
+        for page_id in num_hot..num_pages {
+            let guard = match strategy {
+                Some(strategy) => bpm.fetch_page_read_with_strategy(page_id, strategy).unwrap(),
+                None => bpm.fetch_page_read(page_id).unwrap(),
+            };
+            assert_eq!(&guard.get_data()[..4], &page_id.to_le_bytes());
+        }
+    }
+
+    #[test]
+    fn test_ring_buffer_keeps_hot_set() {
+        let db_name = db_file("bpm_ring_buffer");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        // With K = 1 the replacer is plain LRU, which a scan would flush completely.
+        let bpm = BufferPoolManager::new(16, disk_manager, 1);
+        let strategy = bpm.get_access_strategy(BufferAccessStrategyType::BulkRead);
+        assert_eq!(strategy.ring_size(), 2);
+
+        scan_after_hot_set(&bpm, 64, 8, Some(&strategy));
+        let resident = resident_pages(&bpm);
+        for page_id in 0..8 {
+            assert!(resident.contains(&page_id), "hot page {} was evicted", page_id);
+        }
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn test_scan_without_strategy_evicts_hot_set() {
+        let db_name = db_file("bpm_no_ring_buffer");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = BufferPoolManager::new(16, disk_manager, 1);
+
+        scan_after_hot_set(&bpm, 64, 8, None);
+        let resident = resident_pages(&bpm);
+        assert!((0..8).any(|page_id| !resident.contains(&page_id)));
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn test_bulk_write_strategy() {
+        let db_name = db_file("bpm_bulk_write");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = BufferPoolManager::new(BUFFER_POOL_SIZE, disk_manager, LRUK_REPLACER_K);
+        let strategy = BufferAccessStrategy::new(BufferAccessStrategyType::BulkWrite, 3);
+
+        let hot_page_id = bpm.new_page_guarded().unwrap().page_id();
+        for i in 0..50u32 {
+            let mut guard = bpm
+                .new_page_guarded_with_strategy(&strategy)
+                .unwrap()
+                .upgrade_write();
+            guard.get_data_mut()[..4].copy_from_slice(&i.to_le_bytes());
+        }
+        // The bulk load only ever used its three ring frames.
+        let resident = resident_pages(&bpm);
+        assert!(resident.contains(&hot_page_id));
+        assert_eq!(resident.iter().filter(|&&page_id| page_id != INVALID_PAGE_ID).count(), 4);
+
+        for i in 0..50u32 {
+            let guard = bpm.fetch_page_read(hot_page_id + 1 + i as PageId).unwrap();
+            assert_eq!(&guard.get_data()[..4], &i.to_le_bytes());
+        }
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    fn run_resize_test(name: &str, policy: ReplacerPolicy) {
+        let db_name = db_file(name);
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = BufferPoolManager::new_with_policy(4, disk_manager, policy);
+
+        let mut page_ids = Vec::new();
+        for i in 0..4u32 {
+            let mut guard = bpm.new_page_guarded().unwrap().upgrade_write();
+            guard.get_data_mut()[..4].copy_from_slice(&i.to_le_bytes());
+            page_ids.push(guard.page_id());
+        }
+
+        // Growing the pool makes room for more pinned pages.
+        bpm.resize(8);
+        assert_eq!(bpm.get_pool_size(), 8);
+        assert_eq!(bpm.get_num_frames(), 8);
+        let pinned: Vec<_> = (0..8).map(|_| bpm.new_page().unwrap()).collect();
+        assert!(bpm.new_page().is_none());
+        for page in &pinned {
+            assert!(bpm.unpin_page(page.get_page_id(), false));
+        }
+
+        // Shrinking while a page beyond the new size is pinned keeps its frame until
+        // the pin is released.
+        let frames = bpm.get_pages();
+        let last_page_id = frames[7].get_page_id();
+        let last_page = bpm.fetch_page(last_page_id, AccessType::Unknown).unwrap();
+        bpm.resize(2);
+        assert_eq!(bpm.get_pool_size(), 2);
+        assert_eq!(bpm.get_num_frames(), 8);
+        assert_eq!(last_page.get_page_id(), last_page_id);
+        assert!(bpm.unpin_page(last_page_id, false));
+        assert_eq!(bpm.get_num_frames(), 2);
+
+        // Only two pages fit now, and the evicted pages were written back.
+        let first = bpm.new_page().unwrap();
+        let second = bpm.new_page().unwrap();
+        assert!(bpm.new_page().is_none());
+        assert!(bpm.unpin_page(first.get_page_id(), false));
+        assert!(bpm.unpin_page(second.get_page_id(), false));
+        for (i, &page_id) in page_ids.iter().enumerate() {
+            let guard = bpm.fetch_page_read(page_id).unwrap();
+            assert_eq!(&guard.get_data()[..4], &(i as u32).to_le_bytes());
+        }
+
+        // Growing back re-creates the frames.
+        bpm.resize(4);
+        assert_eq!(bpm.get_num_frames(), 4);
+        let pinned: Vec<_> = (0..4).map(|_| bpm.new_page().unwrap()).collect();
+        assert!(bpm.new_page().is_none());
+        drop(pinned);
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn test_resize_lru_k() {
+        run_resize_test("bpm_resize_lru_k", ReplacerPolicy::LruK(LRUK_REPLACER_K));
+    }
+
+    #[test]
+    fn test_resize_clock() {
+        run_resize_test("bpm_resize_clock", ReplacerPolicy::Clock);
+    }
+
+    #[test]
+    fn test_resize_arc() {
+        run_resize_test("bpm_resize_arc", ReplacerPolicy::Adaptive);
+    }
+
+    #[test]
+    fn test_resize_two_queue() {
+        run_resize_test("bpm_resize_two_queue", ReplacerPolicy::TwoQueue);
+    }
+
+    #[test]
+    fn test_shrink_during_flush_all_pages() {
+        let db_name = db_file("bpm_shrink_during_flush");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = Arc::new(BufferPoolManager::new(4, disk_manager, LRUK_REPLACER_K));
+
+        let mut page_ids = Vec::new();
+        for i in 0..4u32 {
+            let mut guard = bpm.new_page_guarded().unwrap().upgrade_write();
+            guard.get_data_mut()[..4].copy_from_slice(&i.to_le_bytes());
+            page_ids.push(guard.page_id());
+        }
+
+        // Holding the write latch of the last page stops the flush there, with that page pinned.
+        let guard = bpm.fetch_page_write(page_ids[3]).unwrap();
+        let (done_sender, done) = mpsc::channel();
+        let flusher = {
+            let bpm = bpm.clone();
+            thread::spawn(move || {
+                bpm.flush_all_pages();
+                done_sender.send(()).unwrap();
+            })
+        };
+        while guard.page().get_pin_count() < 2 {
+            thread::yield_now();
+        }
+
+        // The flush then releases the last pin of frames beyond the new size, which retires them.
+        bpm.resize(1);
+        drop(guard);
+        done.recv_timeout(Duration::from_secs(10))
+            .expect("flush_all_pages deadlocked while the pool shrank");
+        flusher.join().unwrap();
+        assert_eq!(bpm.get_num_frames(), 1);
+        for (i, &page_id) in page_ids.iter().enumerate() {
+            let guard = bpm.fetch_page_read(page_id).unwrap();
+            assert_eq!(&guard.get_data()[..4], &(i as u32).to_le_bytes());
+        }
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+}
@@ -0,0 +1,134 @@
+#[path = "common/mod.rs"]
+mod common;
+
+#[cfg(test)]
+mod buffer_pool_stats_test {
+    use std::sync::{Arc, Mutex};
+
+    use bustub_rust::buffer::buffer_pool::*;
+    use bustub_rust::buffer::buffer_pool_manager::*;
+    use bustub_rust::buffer::buffer_pool_stats::*;
+    use bustub_rust::buffer::parallel_buffer_pool_manager::*;
+    use bustub_rust::buffer::replacer::*;
+    use bustub_rust::storage::disk::disk_manager::DiskManager;
+
+    use super::common::*;
+
+    #[test]
+    fn counters_test() {
+        let db_name = db_file("stats_counters");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = BufferPoolManager::new(2, disk_manager, 2);
+        assert_eq!(bpm.get_stats(), BufferPoolStats::default());
+
+        let page0 = bpm.new_page().unwrap().get_page_id();
+        let page1 = bpm.new_page().unwrap().get_page_id();
+
+        // Both frames pinned: a third page has to wait.
+        assert!(bpm.new_page().is_none());
+        assert_eq!(bpm.get_stats().pin_waits, 1);
+
+        // Resident page: hit.
+        bpm.fetch_page(page0, AccessType::Unknown).unwrap();
+        assert_eq!(bpm.get_stats().hits, 1);
+        assert!(bpm.unpin_page(page0, false));
+        assert!(bpm.unpin_page(page0, true));
+        assert!(bpm.unpin_page(page1, false));
+
+        // Page 0 is dirty, so evicting it is also a write-back.
+        let page2 = bpm.new_page().unwrap().get_page_id();
+        let page3 = bpm.new_page().unwrap().get_page_id();
+        let stats = bpm.get_stats();
+        assert_eq!(stats.evictions, 2);
+        assert_eq!(stats.dirty_write_backs, 1);
+        assert!(bpm.unpin_page(page2, false));
+        assert!(bpm.unpin_page(page3, false));
+
+        // Page 0 is no longer resident: miss.
+        bpm.fetch_page(page0, AccessType::Unknown).unwrap();
+        let stats = bpm.get_stats();
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.evictions, 3);
+
+        bpm.reset_stats();
+        assert_eq!(bpm.get_stats(), BufferPoolStats::default());
+
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn pin_tracking_test() {
+        let db_name = db_file("stats_pin_tracking");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = BufferPoolManager::new(4, disk_manager, 2);
+
+        // Pins taken before tracking is enabled are not recorded.
+        let untracked = bpm.new_page().unwrap().get_page_id();
+        bpm.set_pin_tracking(true);
+        assert!(bpm.get_outstanding_pins().is_empty());
+
+        let page0 = bpm.new_page().unwrap().get_page_id();
+        let line = line!() - 1;
+        bpm.fetch_page(page0, AccessType::Unknown).unwrap();
+        let page1 = bpm.new_page().unwrap().get_page_id();
+
+        let pins = bpm.get_outstanding_pins();
+        assert_eq!(pins.len(), 3);
+        assert_eq!(pins[0].page_id, page0);
+        assert_eq!(pins[0].location.file(), file!());
+        assert_eq!(pins[0].location.line(), line);
+        assert_eq!(pins[0].thread_id, std::thread::current().id());
+        assert!(pins[0].to_string().contains(file!()));
+
+        assert!(bpm.unpin_page(page0, false));
+        assert!(bpm.unpin_page(page0, false));
+        assert!(bpm.unpin_page(untracked, false));
+        let pins = bpm.get_outstanding_pins();
+        assert_eq!(pins.len(), 1);
+        assert_eq!(pins[0].page_id, page1);
+
+        // Guards record the line that created them and release the record when dropped.
+        {
+            let guard = bpm.fetch_page_read(page0).unwrap();
+            let line = line!() - 1;
+            let pins = bpm.get_outstanding_pins();
+            assert_eq!(pins.len(), 2);
+            assert_eq!(pins[0].page_id, guard.page_id());
+            assert_eq!(pins[0].location.line(), line);
+        }
+        assert_eq!(bpm.get_outstanding_pins().len(), 1);
+
+        bpm.set_pin_tracking(false);
+        assert!(bpm.get_outstanding_pins().is_empty());
+        assert!(bpm.unpin_page(page1, false));
+
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn parallel_pin_tracking_test() {
+        let db_name = db_file("stats_parallel_pin_tracking");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = ParallelBufferPoolManager::new(2, 2, disk_manager, 2);
+        bpm.set_pin_tracking(true);
+
+        let page_ids: Vec<_> = (0..4)
+            .map(|_| bpm.new_page().unwrap().get_page_id())
+            .collect();
+        let pins = bpm.get_outstanding_pins();
+        assert_eq!(pins.len(), 4);
+        assert!(pins.iter().all(|pin| pin.location.file() == file!()));
+
+        for &page_id in &page_ids[..3] {
+            assert!(bpm.unpin_page(page_id, false));
+            bpm.fetch_page(page_id, AccessType::Unknown).unwrap();
+            assert!(bpm.unpin_page(page_id, false));
+        }
+        assert_eq!(bpm.get_stats().hits, 3);
+        let pins = bpm.get_outstanding_pins();
+        assert_eq!(pins.len(), 1);
+        assert_eq!(pins[0].page_id, page_ids[3]);
+
+        remove_db_file(&db_name);
+    }
+}
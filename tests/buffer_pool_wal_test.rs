@@ -0,0 +1,217 @@
+#[path = "common/mod.rs"]
+mod common;
+
+#[cfg(test)]
+mod buffer_pool_wal_test {
+    use std::sync::{Arc, Mutex};
+
+    use bustub_rust::buffer::buffer_pool::*;
+    use bustub_rust::buffer::buffer_pool_manager::*;
+    use bustub_rust::recovery::log_manager::*;
+    use bustub_rust::storage::disk::disk_manager::DiskManager;
+    use bustub_rust::storage::page::page::SIZE_PAGE_HEADER;
+    use bustub_rust::utils::config::*;
+
+    use super::common::*;
+
+    /// A log manager that records every forced flush together with the number of page writes
+    /// the disk manager had performed at that moment.
+    struct FakeLogManager {
+        persistent_lsn: Mutex<Lsn>,
+        flushes: Mutex<Vec<(Lsn, u64)>>,
+        disk_manager: Arc<Mutex<DiskManager>>,
+    }
+
+    impl FakeLogManager {
+        fn new(disk_manager: Arc<Mutex<DiskManager>>, persistent_lsn: Lsn) -> Self {
+            FakeLogManager {
+                persistent_lsn: Mutex::new(persistent_lsn),
+                flushes: Mutex::new(vec![]),
+                disk_manager,
+            }
+        }
+
+        fn flushes(&self) -> Vec<(Lsn, u64)> {
+            self.flushes.lock().unwrap().clone()
+        }
+    }
+
+    impl LogManager for FakeLogManager {
+        fn get_persistent_lsn(&self) -> Lsn {
+            *self.persistent_lsn.lock().unwrap()
+        }
+
+        fn flush(&self, lsn: Lsn) {
+            let num_writes = self.disk_manager.lock().unwrap().get_num_writes();
+            self.flushes.lock().unwrap().push((lsn, num_writes));
+            *self.persistent_lsn.lock().unwrap() = lsn;
+        }
+    }
+
+    type Setup = (String, Arc<Mutex<DiskManager>>, BufferPoolManager, Arc<FakeLogManager>);
+
+    fn setup(name: &str, persistent_lsn: Lsn) -> Setup {
+        let db_name = db_file(name);
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = BufferPoolManager::new(2, disk_manager.clone(), LRUK_REPLACER_K);
+        let log_manager = Arc::new(FakeLogManager::new(disk_manager.clone(), persistent_lsn));
+        bpm.set_log_manager(log_manager.clone());
+        (db_name, disk_manager, bpm, log_manager)
+    }
+
+    #[test]
+    fn test_eviction_flushes_log_first() {
+        let (db_name, disk_manager, bpm, log_manager) = setup("wal_eviction", INVALID_LSN);
+
+        for lsn in [42, 7] {
+            bpm.new_page_guarded().unwrap().upgrade_write().set_lsn(lsn);
+        }
+        assert!(log_manager.flushes().is_empty());
+
+        // Bringing in a third page evicts the first one: the log is forced up to its LSN while
+        // the disk has not seen the page yet.
+        let num_writes = disk_manager.lock().unwrap().get_num_writes();
+        bpm.new_page_guarded().unwrap();
+        assert_eq!(log_manager.flushes(), vec![(42, num_writes)]);
+        assert_eq!(disk_manager.lock().unwrap().get_num_writes(), num_writes + 1);
+
+        // The second page's LSN is already covered by the durable log, so no flush is forced.
+        bpm.new_page_guarded().unwrap();
+        assert_eq!(log_manager.flushes().len(), 1);
+        assert_eq!(disk_manager.lock().unwrap().get_num_writes(), num_writes + 2);
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn test_flush_page_flushes_log_first() {
+        let (db_name, disk_manager, bpm, log_manager) = setup("wal_flush_page", 10);
+
+        let page_id = {
+            let mut guard = bpm.new_page_guarded().unwrap().upgrade_write();
+            guard.set_lsn(5);
+            guard.page_id()
+        };
+        assert!(bpm.flush_page(page_id));
+        assert!(log_manager.flushes().is_empty());
+
+        {
+            let mut guard = bpm.fetch_page_write(page_id).unwrap();
+            guard.set_lsn(11);
+        }
+        let num_writes = disk_manager.lock().unwrap().get_num_writes();
+        assert_eq!(bpm.write_back_dirty_pages(1), 1);
+        assert_eq!(log_manager.flushes(), vec![(11, num_writes)]);
+
+        {
+            let mut guard = bpm.fetch_page_write(page_id).unwrap();
+            guard.set_lsn(12);
+        }
+        let num_writes = disk_manager.lock().unwrap().get_num_writes();
+        bpm.flush_all_pages();
+        assert_eq!(log_manager.flushes().last(), Some(&(12, num_writes)));
+        assert_eq!(log_manager.get_persistent_lsn(), 12);
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn test_unlogged_pages_skip_log_flush() {
+        let (db_name, _, bpm, log_manager) = setup("wal_unlogged", INVALID_LSN);
+
+        // Pages modified without a log record keep the `INVALID_LSN` of a fresh page.
+        let page_id = {
+            let mut guard = bpm.new_page_guarded().unwrap().upgrade_write();
+            guard.get_data_mut()[SIZE_PAGE_HEADER] = 1;
+            guard.page_id()
+        };
+        assert!(bpm.flush_page(page_id));
+        bpm.flush_all_pages();
+        assert!(log_manager.flushes().is_empty());
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn test_first_lsn_flushes_log_first() {
+        let (db_name, disk_manager, bpm, log_manager) = setup("wal_first_lsn", INVALID_LSN);
+
+        // LSN 0 is the first log record, not an unlogged page.
+        let page_id = {
+            let mut guard = bpm.new_page_guarded().unwrap().upgrade_write();
+            guard.set_lsn(0);
+            guard.page_id()
+        };
+        let num_writes = disk_manager.lock().unwrap().get_num_writes();
+        assert!(bpm.flush_page(page_id));
+        assert_eq!(log_manager.flushes(), vec![(0, num_writes)]);
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+    #[test]
+    fn test_async_eviction_flushes_log_first() {
+        let (db_name, disk_manager, bpm, log_manager) = setup("wal_async_eviction", INVALID_LSN);
+
+        let page_ids: Vec<_> = (0..3)
+            .map(|_| bpm.new_page_guarded().unwrap().page_id())
+            .collect();
+        for (page_id, lsn) in [(page_ids[1], 42), (page_ids[2], 7)] {
+            bpm.fetch_page_write(page_id).unwrap().set_lsn(lsn);
+        }
+        assert!(log_manager.flushes().is_empty());
+
+        // The async fetch evicts the second page: the log is forced up to its LSN on a blocking
+        // thread, before the write-back is queued.
+        let num_writes = disk_manager.lock().unwrap().get_num_writes();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let guard = bpm.fetch_page_read_async(page_ids[0]).await.unwrap();
+            assert_eq!(guard.page_id(), page_ids[0]);
+        });
+        assert_eq!(log_manager.flushes(), vec![(42, num_writes)]);
+        assert_eq!(disk_manager.lock().unwrap().get_num_writes(), num_writes + 1);
+        assert_eq!(bpm.fetch_page_read(page_ids[1]).unwrap().page().get_lsn(), 42);
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn test_cancelled_async_fetch_gives_victim_back() {
+        let (db_name, _, bpm, log_manager) = setup("wal_async_cancel", INVALID_LSN);
+
+        let page_ids: Vec<_> = (0..3)
+            .map(|_| bpm.new_page_guarded().unwrap().page_id())
+            .collect();
+        for (page_id, lsn) in [(page_ids[1], 10), (page_ids[2], 20)] {
+            bpm.fetch_page_write(page_id).unwrap().set_lsn(lsn);
+        }
+
+        // Whichever page is evicted, the fetch is dropped while the log is flushed ahead of it.
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            tokio::select! {
+                biased;
+                _ = bpm.fetch_page_read_async(page_ids[0]) => unreachable!("the log is flushed"),
+                _ = std::future::ready(()) => {}
+            }
+        });
+        assert_eq!(bpm.get_stats().evictions, 1);
+        for page_id in page_ids {
+            let guard = bpm.fetch_page_basic(page_id).unwrap();
+            assert_eq!(guard.page().get_pin_count(), 1);
+        }
+        assert!(log_manager.get_persistent_lsn() >= 10);
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+}
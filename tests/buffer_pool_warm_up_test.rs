@@ -0,0 +1,131 @@
+#[path = "common/mod.rs"]
+mod common;
+
+#[cfg(test)]
+mod buffer_pool_warm_up_test {
+    use std::sync::{Arc, Mutex};
+
+    use bustub_rust::buffer::buffer_pool::*;
+    use bustub_rust::buffer::buffer_pool_manager::*;
+    use bustub_rust::buffer::parallel_buffer_pool_manager::*;
+    use bustub_rust::buffer::replacer::*;
+    use bustub_rust::storage::disk::disk_manager::DiskManager;
+    use bustub_rust::utils::config::*;
+
+    use super::common::*;
+
+    /// Write `num_pages` pages tagged with their id, then touch `hot` again so it is the hottest.
+    fn load_pages(bpm: &BufferPoolManager, num_pages: i32, hot: &[PageId]) {
+        for _ in 0..num_pages {
+            let mut guard = bpm.new_page_guarded().unwrap().upgrade_write();
+            let page_id = guard.page_id();
+            guard.get_data_mut()[..4].copy_from_slice(&page_id.to_le_bytes());
+        }
+        for &page_id in hot {
+            drop(bpm.fetch_page_read(page_id).unwrap());
+        }
+        bpm.flush_all_pages();
+    }
+
+    fn resident_pages(bpm: &BufferPoolManager) -> Vec<PageId> {
+        let mut page_ids: Vec<PageId> = bpm
+            .get_pages()
+            .iter()
+            .map(|page| page.get_page_id())
+            .filter(|&page_id| page_id != INVALID_PAGE_ID)
+            .collect();
+        page_ids.sort_unstable();
+        page_ids
+    }
+
+    fn run_warm_up_test(name: &str, policy: ReplacerPolicy) {
+        let db_name = db_file(name);
+        let warm_name = db_name.replace(".db", ".warm");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = BufferPoolManager::new_with_policy(8, disk_manager, policy);
+        load_pages(&bpm, 16, &[9, 8]);
+        let resident = resident_pages(&bpm);
+        assert_eq!(bpm.dump_warm_pages(&warm_name).unwrap(), 8);
+        drop(bpm);
+
+        // A restarted pool holds the same pages without touching them first.
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = BufferPoolManager::new_with_policy(8, disk_manager, policy);
+        assert_eq!(bpm.restore_warm_pages(&warm_name).unwrap(), 8);
+        assert_eq!(resident_pages(&bpm), resident);
+        assert_eq!(bpm.get_stats().misses, 8);
+        for &page_id in &resident {
+            let guard = bpm.fetch_page_read(page_id).unwrap();
+            assert_eq!(&guard.get_data()[..4], &page_id.to_le_bytes());
+        }
+        assert_eq!(bpm.get_stats().hits, 8);
+        assert_eq!(bpm.get_stats().misses, 8);
+
+        // Restoring again is a no-op, every page is already resident.
+        assert_eq!(bpm.restore_warm_pages(&warm_name).unwrap(), 0);
+        drop(bpm);
+
+        // A smaller pool keeps the hottest pages of the dump.
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = BufferPoolManager::new_with_policy(2, disk_manager, policy);
+        assert_eq!(bpm.restore_warm_pages(&warm_name).unwrap(), 2);
+        assert_eq!(resident_pages(&bpm), vec![8, 9]);
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn warm_up_lru_k_test() {
+        run_warm_up_test("warm_up_lru_k", ReplacerPolicy::LruK(2));
+    }
+
+    #[test]
+    fn warm_up_arc_test() {
+        run_warm_up_test("warm_up_arc", ReplacerPolicy::Adaptive);
+    }
+
+    #[test]
+    fn restore_missing_or_corrupt_file_test() {
+        let db_name = db_file("warm_up_corrupt");
+        let warm_name = db_name.replace(".db", ".warm");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = BufferPoolManager::new(4, disk_manager, LRUK_REPLACER_K);
+
+        let err = bpm.restore_warm_pages(&warm_name).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+        std::fs::write(&warm_name, [1, 2, 3]).unwrap();
+        let err = bpm.restore_warm_pages(&warm_name).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn parallel_warm_up_test() {
+        let db_name = db_file("warm_up_parallel");
+        let warm_name = db_name.replace(".db", ".warm");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = ParallelBufferPoolManager::new(2, 4, disk_manager, LRUK_REPLACER_K);
+        for _ in 0..12 {
+            let page_id = bpm.new_page().unwrap().get_page_id();
+            assert!(bpm.unpin_page(page_id, true));
+        }
+        assert_eq!(bpm.dump_warm_pages(&warm_name).unwrap(), 8);
+        drop(bpm);
+
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = ParallelBufferPoolManager::new(2, 4, disk_manager, LRUK_REPLACER_K);
+        assert_eq!(bpm.restore_warm_pages(&warm_name).unwrap(), 8);
+        for page_id in 4..12 {
+            bpm.fetch_page(page_id, AccessType::Unknown).unwrap();
+            assert!(bpm.unpin_page(page_id, false));
+        }
+        assert_eq!(bpm.get_stats().hits, 8);
+        assert_eq!(bpm.get_stats().misses, 8);
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+}
@@ -0,0 +1,36 @@
+//! Helpers shared by the integration tests. Every test file includes this module with
+//! `#[path = "common/mod.rs"]`, so that it also builds as part of `tests/mod.rs`.
+#![allow(dead_code)]
+
+use std::sync::{Arc, Mutex};
+
+use bustub_rust::buffer::buffer_pool_manager::BufferPoolManager;
+use bustub_rust::storage::disk::disk_manager::DiskManager;
+use bustub_rust::storage::page::header_page::ensure_header_page;
+use bustub_rust::utils::config::LRUK_REPLACER_K;
+
+/// Return a path in the temporary directory for the database file of test `name`, unique to this
+/// process.
+pub fn db_file(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!("{}_{}.db", name, std::process::id()));
+    path.to_str().unwrap().to_string()
+}
+
+/// Remove a database file and the log and warm page dump kept next to it.
+pub fn remove_db_file(db_name: &str) {
+    let _ = std::fs::remove_file(db_name);
+    let _ = std::fs::remove_file(db_name.replace(".db", ".log"));
+    let _ = std::fs::remove_file(db_name.replace(".db", ".warm"));
+}
+
+/// Create a buffer pool of `pool_size` frames over `db_name`, with its header page initialized.
+pub fn new_bpm(db_name: &str, pool_size: usize) -> Arc<BufferPoolManager> {
+    let disk_manager = Arc::new(Mutex::new(DiskManager::new(db_name)));
+    let bpm = Arc::new(BufferPoolManager::new(
+        pool_size,
+        disk_manager,
+        LRUK_REPLACER_K,
+    ));
+    assert!(ensure_header_page(bpm.as_ref()));
+    bpm
+}
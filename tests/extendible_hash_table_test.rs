@@ -0,0 +1,423 @@
+#[path = "common/mod.rs"]
+mod common;
+
+#[cfg(test)]
+mod extendible_hash_table_test {
+    use std::collections::HashMap;
+    use std::thread;
+
+    use bustub_rust::buffer::buffer_pool::*;
+    use bustub_rust::buffer::buffer_pool_manager::*;
+    use bustub_rust::storage::index::extendible_hash_table::*;
+    use bustub_rust::storage::page::extendible_hash_table_bucket_page::*;
+    use bustub_rust::storage::page::extendible_hash_table_directory_page::*;
+    use bustub_rust::storage::page::extendible_hash_table_header_page::*;
+    use bustub_rust::storage::page::header_page::*;
+    use bustub_rust::storage::page::page_header::*;
+    use bustub_rust::utils::config::*;
+    use bustub_rust::utils::rid::RID;
+
+    use super::common::*;
+
+    fn rid(key: i64) -> RID {
+        RID::new(key as PageId, key.unsigned_abs() as SlotOffset)
+    }
+
+    /// Hash a key to itself, so that tests control which bucket it lands in.
+    fn identity_hash(key: &i64) -> u32 {
+        *key as u32
+    }
+
+    /// Return the global depth of the only directory of a table with a header max depth of 0.
+    fn global_depth(bpm: &BufferPoolManager, table: &ExtendibleHashTable<i64>) -> u32 {
+        let guard = bpm.fetch_page_read(table.get_header_page_id()).unwrap();
+        let directory_page_id =
+            ExtendibleHashTableHeaderPage::new(guard.get_data()).get_directory_page_id(0);
+        drop(guard);
+        let guard = bpm.fetch_page_read(directory_page_id).unwrap();
+        ExtendibleHashTableDirectoryPage::new(guard.get_data()).get_global_depth()
+    }
+
+    #[test]
+    fn header_page_test() {
+        let mut data = vec![0u8; BUSTUB_PAGE_SIZE];
+        let mut header = ExtendibleHashTableHeaderPage::new(&mut data[..]);
+        header.init(3, 2);
+        assert_eq!(header.get_max_depth(), 2);
+        assert_eq!(header.get_max_size(), 4);
+        assert!((0..4).all(|index| header.get_directory_page_id(index) == INVALID_PAGE_ID));
+
+        // Directories are picked by the top bits of the hash.
+        assert_eq!(header.hash_to_directory_index(0x0000_0000), 0);
+        assert_eq!(header.hash_to_directory_index(0x4fff_ffff), 1);
+        assert_eq!(header.hash_to_directory_index(0xc000_0001), 3);
+        header.set_directory_page_id(2, 9);
+        assert_eq!(header.get_directory_page_id(2), 9);
+        assert_eq!(
+            PageHeader::new(&data[..]).get_page_type(),
+            Some(PageType::HashHeader)
+        );
+
+        let mut data = vec![0u8; BUSTUB_PAGE_SIZE];
+        let mut header = ExtendibleHashTableHeaderPage::new(&mut data[..]);
+        header.init(3, 0);
+        assert_eq!(header.hash_to_directory_index(u32::MAX), 0);
+    }
+
+    #[test]
+    fn directory_page_test() {
+        let mut data = vec![0u8; BUSTUB_PAGE_SIZE];
+        let mut directory = ExtendibleHashTableDirectoryPage::new(&mut data[..]);
+        directory.init(4, 2, 10);
+        assert_eq!(directory.get_global_depth(), 0);
+        assert_eq!(directory.get_size(), 1);
+        assert_eq!(directory.hash_to_bucket_index(0xffff_ffff), 0);
+        assert!(!directory.can_shrink());
+
+        // Doubling mirrors the slots, then a split points half of them elsewhere.
+        assert!(directory.incr_global_depth());
+        assert_eq!(directory.get_bucket_page_id(1), 10);
+        directory.set_local_depth(0, 1);
+        directory.set_local_depth(1, 1);
+        directory.set_bucket_page_id(1, 11);
+        assert_eq!(directory.get_split_image_index(1), 0);
+        assert!(directory.incr_global_depth());
+        assert_eq!(directory.get_size(), 4);
+        assert_eq!(directory.get_bucket_page_id(2), 10);
+        assert_eq!(directory.get_bucket_page_id(3), 11);
+        assert_eq!(directory.get_local_depth(3), 1);
+        assert_eq!(directory.hash_to_bucket_index(0b1110), 2);
+        directory.verify_integrity();
+        assert!(!directory.incr_global_depth());
+
+        // The directory shrinks once no bucket uses all of its bits.
+        assert!(directory.can_shrink());
+        directory.decr_global_depth();
+        assert_eq!(directory.get_global_depth(), 1);
+        assert!(!directory.can_shrink());
+        directory.verify_integrity();
+    }
+
+    #[test]
+    fn bucket_page_test() {
+        let mut data = vec![0u8; BUSTUB_PAGE_SIZE];
+        let mut bucket = ExtendibleHashTableBucketPage::<_, i64>::new(&mut data[..]);
+        assert_eq!(bucket_page_capacity::<i64>(), BUCKET_SIZE);
+        bucket.init(5, 3);
+        assert!(bucket.is_empty());
+
+        // Keys are unique, and a full bucket takes no more entries.
+        assert!(bucket.insert(&7, rid(7)));
+        assert!(bucket.insert(&-3, rid(-3)));
+        assert!(!bucket.insert(&7, rid(8)));
+        assert!(bucket.insert(&12, rid(12)));
+        assert!(bucket.is_full());
+        assert!(!bucket.insert(&13, rid(13)));
+        assert_eq!(
+            bucket.lookup(&-3).map(|index| bucket.value_at(index)),
+            Some(rid(-3))
+        );
+
+        assert!(bucket.remove(&7));
+        assert!(!bucket.remove(&7));
+        assert_eq!(bucket.get_size(), 2);
+        let mut entries = bucket.get_entries();
+        entries.sort();
+        assert_eq!(entries, vec![(-3, rid(-3)), (12, rid(12))]);
+
+        // Duplicate keys are allowed with different RIDs.
+        assert!(bucket.insert_duplicate(&12, rid(13)));
+        assert!(!bucket.insert_duplicate(&-3, rid(-3)));
+        assert_eq!(bucket.get_values(&12), vec![rid(12), rid(13)]);
+        assert_eq!(bucket.lookup_entry(&12, rid(13)), Some(2));
+        assert_eq!(bucket.lookup_entry(&12, rid(14)), None);
+    }
+
+    #[test]
+    fn split_merge_test() {
+        let db_name = db_file("extendible_hash_table_split_merge");
+        let bpm = new_bpm(&db_name, 64);
+        let table = ExtendibleHashTable::new_with_hash_fn(
+            "orders_hash",
+            bpm.clone(),
+            0,
+            3,
+            2,
+            identity_hash,
+        )
+        .unwrap();
+        assert_eq!(table.get_value(&0), None);
+        assert!(!table.remove(&0));
+
+        // Splits double the directory until every bucket fits.
+        for key in 0..8 {
+            assert!(table.insert(&key, rid(key)));
+            table.verify_integrity();
+        }
+        assert!(!table.insert(&3, rid(3)));
+        assert_eq!(global_depth(&bpm, &table), 2);
+        for key in 0..8 {
+            assert_eq!(table.get_value(&key), Some(rid(key)));
+        }
+
+        // A bucket splits up to the max depth, then inserts into it fail.
+        assert!(table.insert(&8, rid(8)));
+        assert_eq!(global_depth(&bpm, &table), 3);
+        assert!(!table.insert(&16, rid(16)));
+        assert_eq!(table.get_value(&16), None);
+        table.verify_integrity();
+
+        // Emptied buckets merge with their split images and the directory shrinks.
+        for key in [8, 4, 0] {
+            assert!(table.remove(&key));
+            table.verify_integrity();
+        }
+        assert_eq!(global_depth(&bpm, &table), 2);
+        for key in 1..8 {
+            if key != 4 {
+                assert!(table.remove(&key));
+            }
+            table.verify_integrity();
+        }
+        assert_eq!(global_depth(&bpm, &table), 0);
+        assert!(table.insert(&16, rid(16)));
+        assert_eq!(table.get_value(&16), Some(rid(16)));
+
+        drop(table);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn duplicate_keys_test() {
+        let db_name = db_file("extendible_hash_table_duplicate_keys");
+        let bpm = new_bpm(&db_name, 64);
+        let table = ExtendibleHashTable::new_with_hash_fn(
+            "orders_hash",
+            bpm.clone(),
+            0,
+            3,
+            4,
+            identity_hash,
+        )
+        .unwrap();
+
+        // A key takes several RIDs, but each only once.
+        for key in 0..8 {
+            for slot in 0..3 {
+                assert!(table.insert_duplicate(&key, RID::new(key as PageId, slot)));
+            }
+            assert!(!table.insert_duplicate(&key, RID::new(key as PageId, 0)));
+            assert!(!table.insert(&key, rid(key)));
+            table.verify_integrity();
+        }
+        let rids = |key: i64| {
+            (0..3)
+                .map(|slot| RID::new(key as PageId, slot))
+                .collect::<Vec<_>>()
+        };
+        for key in 0..8 {
+            let mut values = table.get_values(&key);
+            values.sort();
+            assert_eq!(values, rids(key));
+        }
+        assert!(table.get_values(&8).is_empty());
+
+        // Entries are removed by RID.
+        assert!(!table.remove_entry(&5, RID::new(6, 0)));
+        assert!(table.remove_entry(&5, RID::new(5, 1)));
+        assert!(!table.remove_entry(&5, RID::new(5, 1)));
+        assert_eq!(table.get_values(&5).len(), 2);
+
+        // A key cannot have more RIDs than a bucket holds.
+        assert!(table.insert_duplicate(&5, RID::new(5, 1)));
+        assert!(table.insert_duplicate(&5, RID::new(5, 3)));
+        assert!(!table.insert_duplicate(&5, RID::new(5, 4)));
+        assert_eq!(table.get_values(&5).len(), 4);
+        table.verify_integrity();
+
+        drop(table);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn exhausted_pool_test() {
+        let db_name = db_file("extendible_hash_table_exhausted_pool");
+        let bpm = new_bpm(&db_name, 8);
+        let table = ExtendibleHashTable::new_with_hash_fn(
+            "orders_hash",
+            bpm.clone(),
+            0,
+            3,
+            2,
+            identity_hash,
+        )
+        .unwrap();
+        assert!(table.insert(&0, rid(0)));
+        assert!(table.insert(&1, rid(1)));
+
+        // With every frame pinned, nothing can be read or changed.
+        let mut pinned = Vec::new();
+        while let Some(guard) = bpm.new_page_guarded() {
+            pinned.push(guard);
+        }
+        assert_eq!(table.get_value(&0), None);
+        assert!(!table.insert(&2, rid(2)));
+        assert!(!table.remove(&0));
+
+        // With two frames left, the bucket can be read but not split, and the directory keeps its
+        // depth.
+        pinned.truncate(pinned.len() - 2);
+        assert_eq!(table.get_value(&1), Some(rid(1)));
+        assert!(!table.insert(&2, rid(2)));
+        drop(pinned);
+        assert_eq!(global_depth(&bpm, &table), 0);
+        table.verify_integrity();
+
+        assert!(table.insert(&2, rid(2)));
+        assert_eq!(global_depth(&bpm, &table), 1);
+        table.verify_integrity();
+        for key in 0..3 {
+            assert_eq!(table.get_value(&key), Some(rid(key)));
+        }
+
+        drop(table);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn random_operations_test() {
+        let db_name = db_file("extendible_hash_table_random");
+        let bpm = new_bpm(&db_name, 64);
+        let table =
+            ExtendibleHashTable::<i64>::new("orders_hash", bpm.clone(), 2, 9, BUCKET_SIZE).unwrap();
+
+        // Random inserts and removes with the default hash match a HashMap.
+        let mut state = 0x9E3779B97F4A7C15u64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        let mut expected = HashMap::new();
+        for _ in 0..20000 {
+            let key = (next() % 8000) as i64 - 4000;
+            if next() % 3 == 0 {
+                assert_eq!(table.remove(&key), expected.remove(&key).is_some());
+            } else {
+                assert_eq!(
+                    table.insert(&key, rid(key)),
+                    expected.insert(key, rid(key)).is_none()
+                );
+            }
+        }
+        table.verify_integrity();
+        for key in -4000..4000 {
+            assert_eq!(table.get_value(&key), expected.get(&key).copied());
+        }
+        for key in expected.keys() {
+            assert!(table.remove(key));
+        }
+        table.verify_integrity();
+        assert!((-4000..4000).all(|key| table.get_value(&key).is_none()));
+
+        drop(table);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn persistence_test() {
+        let db_name = db_file("extendible_hash_table_persistence");
+        {
+            let bpm = new_bpm(&db_name, 64);
+            let table =
+                ExtendibleHashTable::<i64>::new("orders_hash", bpm.clone(), 1, 9, 8).unwrap();
+            for key in 0..1000 {
+                assert!(table.insert(&key, rid(key)));
+            }
+            let guard = bpm.fetch_page_read(HEADER_PAGE_ID).unwrap();
+            assert_eq!(
+                HeaderPage::new(guard.get_data()).get_root_id("orders_hash"),
+                Some(table.get_header_page_id())
+            );
+            drop(guard);
+            drop(table);
+            bpm.flush_all_pages();
+        }
+
+        // After reopening, the table is found by name and keeps its shape.
+        let bpm = new_bpm(&db_name, 64);
+        let table = ExtendibleHashTable::<i64>::new("orders_hash", bpm.clone(), 1, 9, 8).unwrap();
+        for key in 0..1000 {
+            assert_eq!(table.get_value(&key), Some(rid(key)));
+        }
+        assert!(table.remove(&500));
+        assert!(table.insert(&1000, rid(1000)));
+        table.verify_integrity();
+
+        // Names that cannot be recorded are rejected.
+        assert!(ExtendibleHashTable::<i64>::new("", bpm.clone(), 1, 9, 8).is_none());
+
+        drop(table);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn concurrent_test() {
+        let db_name = db_file("extendible_hash_table_concurrent");
+        let bpm = new_bpm(&db_name, 256);
+        let table = ExtendibleHashTable::<i64>::new("orders_hash", bpm.clone(), 1, 9, 16).unwrap();
+        let num_threads = 8;
+        let num_keys = 4000;
+
+        // Threads insert interleaved keys, splitting the same buckets.
+        thread::scope(|s| {
+            for t in 0..num_threads {
+                let table = &table;
+                s.spawn(move || {
+                    for key in (t..num_keys).step_by(num_threads as usize) {
+                        assert!(table.insert(&key, rid(key)));
+                    }
+                });
+            }
+        });
+        table.verify_integrity();
+
+        // Half of the threads remove the odd keys, merging buckets, while the others
+        // keep finding the even ones.
+        thread::scope(|s| {
+            for t in 0..num_threads / 2 {
+                let table = &table;
+                s.spawn(move || {
+                    for key in (2 * t + 1..num_keys).step_by(num_threads as usize) {
+                        assert!(table.remove(&key));
+                    }
+                });
+            }
+            for t in 0..num_threads / 2 {
+                let table = &table;
+                s.spawn(move || {
+                    for _ in 0..2 {
+                        for key in (2 * t..num_keys).step_by(num_threads as usize) {
+                            assert_eq!(table.get_value(&key), Some(rid(key)), "key {}", key);
+                        }
+                    }
+                });
+            }
+        });
+        table.verify_integrity();
+        for key in 0..num_keys {
+            let expected = (key % 2 == 0).then(|| rid(key));
+            assert_eq!(table.get_value(&key), expected);
+        }
+
+        drop(table);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+}
@@ -0,0 +1,218 @@
+#[path = "common/mod.rs"]
+mod common;
+
+#[cfg(test)]
+mod free_space_map_test {
+    use std::sync::{Arc, Mutex};
+
+    use bustub_rust::buffer::buffer_pool::*;
+    use bustub_rust::buffer::buffer_pool_manager::*;
+    use bustub_rust::storage::disk::disk_manager::DiskManager;
+    use bustub_rust::storage::page::free_space_map_page::*;
+    use bustub_rust::storage::table::free_space_map::FreeSpaceMap;
+    use bustub_rust::storage::table::table_heap::*;
+    use bustub_rust::utils::config::*;
+    use bustub_rust::utils::rid::RID;
+
+    use super::common::*;
+
+    #[test]
+    fn category_test() {
+        assert_eq!(free_space_category(0), 0);
+        assert_eq!(free_space_category(FREE_SPACE_CATEGORY_STEP - 1), 0);
+        assert_eq!(free_space_category(FREE_SPACE_CATEGORY_STEP), 1);
+        assert_eq!(free_space_category(BUSTUB_PAGE_SIZE), u8::MAX);
+        assert_eq!(required_category(1), 1);
+        assert_eq!(required_category(FREE_SPACE_CATEGORY_STEP), 1);
+        assert_eq!(required_category(FREE_SPACE_CATEGORY_STEP + 1), 2);
+        // A page in the required category always has the room asked for.
+        for needed in 1..BUSTUB_PAGE_SIZE {
+            let category = required_category(needed);
+            assert!(category * FREE_SPACE_CATEGORY_STEP >= needed);
+        }
+    }
+
+    #[test]
+    fn find_and_update_test() {
+        let db_name = db_file("free_space_map_basic");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = Arc::new(BufferPoolManager::new(
+            BUFFER_POOL_SIZE,
+            disk_manager,
+            LRUK_REPLACER_K,
+        ));
+        let fsm = FreeSpaceMap::new(bpm.clone()).unwrap();
+        assert_eq!(fsm.find_page(1), None);
+        assert_eq!(fsm.get_last_page_id(), None);
+
+        // Enough heap pages to need several leaf pages, all of them nearly full.
+        let num_pages = 2 * FREE_SPACE_MAP_PAGE_CAPACITY + 10;
+        for page_id in 0..num_pages as PageId {
+            assert!(fsm.update(1000 + page_id, 20));
+        }
+        assert_eq!(fsm.get_num_pages(), num_pages);
+        assert_eq!(fsm.get_last_page_id(), Some(1000 + num_pages as PageId - 1));
+        assert_eq!(fsm.find_page(16), Some(1000));
+        assert_eq!(fsm.find_page(100), None);
+
+        // Pages that gain space are found, in any leaf.
+        let late_page_id = 1000 + num_pages as PageId - 5;
+        assert!(fsm.update(late_page_id, 500));
+        assert_eq!(
+            fsm.get_category(late_page_id),
+            Some(free_space_category(500))
+        );
+        assert_eq!(fsm.find_page(100), Some(late_page_id));
+        assert!(fsm.update(1500, 1000));
+        assert_eq!(fsm.find_page(100), Some(1500));
+        assert_eq!(fsm.find_page(600), Some(1500));
+        assert_eq!(fsm.find_page(2000), None);
+
+        // Pages that lose space are no longer found.
+        assert!(fsm.update(1500, 0));
+        assert_eq!(fsm.find_page(600), None);
+        assert_eq!(fsm.find_page(100), Some(late_page_id));
+        assert_eq!(fsm.get_category(1), None);
+
+        // The map survives a restart.
+        let root_page_id = fsm.get_root_page_id();
+        drop(fsm);
+        bpm.flush_all_pages();
+        drop(bpm);
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = Arc::new(BufferPoolManager::new(
+            BUFFER_POOL_SIZE,
+            disk_manager,
+            LRUK_REPLACER_K,
+        ));
+        let fsm = FreeSpaceMap::open(bpm.clone(), root_page_id).unwrap();
+        assert_eq!(fsm.get_num_pages(), num_pages);
+        assert_eq!(fsm.get_last_page_id(), Some(1000 + num_pages as PageId - 1));
+        assert_eq!(fsm.find_page(100), Some(late_page_id));
+        assert!(fsm.update(1000, 800));
+        assert_eq!(fsm.find_page(100), Some(1000));
+
+        drop(fsm);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn grow_levels_test() {
+        let db_name = db_file("free_space_map_grow");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = Arc::new(BufferPoolManager::new(
+            BUFFER_POOL_SIZE,
+            disk_manager,
+            LRUK_REPLACER_K,
+        ));
+        // With 4 entries per page, 100 heap pages need 4 levels of map pages.
+        let fsm = FreeSpaceMap::new_with_max_size(bpm.clone(), 4).unwrap();
+        let root_page_id = fsm.get_root_page_id();
+        for page_id in 0..100 {
+            assert!(fsm.update(1000 + page_id, 20));
+            assert_eq!(fsm.get_last_page_id(), Some(1000 + page_id));
+        }
+        assert_eq!(fsm.get_height(), Some(4));
+        assert_eq!(fsm.get_root_page_id(), root_page_id);
+        assert_eq!(fsm.get_num_pages(), 100);
+        assert_eq!(fsm.find_page(16), Some(1000));
+        assert_eq!(fsm.find_page(100), None);
+
+        // Every page is still found once it has room, wherever it ended up.
+        for page_id in [1000, 1003, 1004, 1063, 1064, 1099] {
+            assert!(fsm.update(page_id, 500));
+            assert_eq!(fsm.get_category(page_id), Some(free_space_category(500)));
+            assert_eq!(fsm.find_page(100), Some(page_id));
+            assert!(fsm.update(page_id, 0));
+        }
+        assert_eq!(fsm.find_page(100), None);
+        assert!(fsm.update(1077, 900));
+
+        drop(fsm);
+        let fsm = FreeSpaceMap::open(bpm.clone(), root_page_id).unwrap();
+        assert_eq!(fsm.get_num_pages(), 100);
+        assert_eq!(fsm.get_height(), Some(4));
+        assert_eq!(fsm.get_last_page_id(), Some(1099));
+        assert_eq!(fsm.find_page(800), Some(1077));
+        assert!(fsm.update(1100, 1000));
+        assert_eq!(fsm.find_page(900), Some(1100));
+
+        drop(fsm);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn table_heap_reuses_space_test() {
+        let db_name = db_file("free_space_map_table_heap");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = Arc::new(BufferPoolManager::new(
+            BUFFER_POOL_SIZE,
+            disk_manager,
+            LRUK_REPLACER_K,
+        ));
+        let heap = TableHeap::new(bpm.clone()).unwrap();
+        let tuple = vec![1u8; 400];
+        let rids: Vec<RID> = (0..200)
+            .map(|_| heap.insert_tuple(&tuple).unwrap())
+            .collect();
+        let fsm = heap.get_free_space_map();
+        let num_pages = fsm.get_num_pages();
+        assert!(num_pages > 1);
+
+        // Full pages are skipped, so a new tuple goes to the last page or a new one.
+        let last_page_id = rids.last().unwrap().page_id;
+        let rid = heap.insert_tuple(&tuple).unwrap();
+        assert!(rid.page_id >= last_page_id);
+
+        // Space freed by deletes in an early page is found by the next insert.
+        let first_page_id = heap.get_first_page_id();
+        let freed: Vec<RID> = rids
+            .iter()
+            .filter(|rid| rid.page_id == first_page_id)
+            .take(3)
+            .copied()
+            .collect();
+        for &rid in &freed {
+            assert!(heap.mark_delete(rid));
+            assert!(heap.apply_delete(rid));
+        }
+        assert!(fsm.get_category(first_page_id).unwrap() as usize >= required_category(1200));
+        let big = vec![2u8; 1000];
+        let rid = heap.insert_tuple(&big).unwrap();
+        assert_eq!(rid.page_id, first_page_id);
+        assert_eq!(heap.get_tuple(rid), Some(big));
+
+        // Shrinking updates free space too.
+        let second_page_id = rids
+            .iter()
+            .map(|rid| rid.page_id)
+            .find(|&page_id| page_id != first_page_id)
+            .unwrap();
+        let before = fsm.get_category(second_page_id).unwrap();
+        let rid = *rids
+            .iter()
+            .find(|rid| rid.page_id == second_page_id)
+            .unwrap();
+        assert!(heap.update_tuple(rid, b"small"));
+        assert!(fsm.get_category(second_page_id).unwrap() > before);
+
+        // Reopening the heap keeps using the map and appends after the last page.
+        let free_space_map_root_page_id = heap.get_free_space_map_root_page_id();
+        let count = heap.iter().count();
+        let num_pages = heap.get_free_space_map().get_num_pages();
+        drop(heap);
+        let heap =
+            TableHeap::open(bpm.clone(), first_page_id, free_space_map_root_page_id).unwrap();
+        assert_eq!(heap.get_free_space_map().get_num_pages(), num_pages);
+        for _ in 0..20 {
+            heap.insert_tuple(&tuple).unwrap();
+        }
+        assert_eq!(heap.iter().count(), count + 20);
+
+        drop(heap);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+}
@@ -0,0 +1,224 @@
+#[path = "common/mod.rs"]
+mod common;
+
+#[cfg(test)]
+mod generic_key_test {
+    use std::cmp::Ordering;
+    use std::sync::{Arc, Mutex};
+
+    use bustub_rust::buffer::buffer_pool_manager::*;
+    use bustub_rust::storage::disk::disk_manager::DiskManager;
+    use bustub_rust::storage::index::b_plus_tree::*;
+    use bustub_rust::storage::index::generic_key::*;
+    use bustub_rust::storage::index::index_key::*;
+    use bustub_rust::storage::page::header_page::*;
+    use bustub_rust::utils::config::*;
+    use bustub_rust::utils::rid::RID;
+
+    use super::common::*;
+
+    /// Check that the encoded keys of `values`, which are in ascending order, are in ascending
+    /// byte order and decode back to the values.
+    fn assert_sorted<const N: usize>(schema: &KeySchema, values: &[Vec<KeyValue>]) {
+        let comparator = GenericComparator::new(Arc::new(schema.clone()));
+        let keys: Vec<GenericKey<N>> = values
+            .iter()
+            .map(|values| GenericKey::from_values(schema, values).unwrap())
+            .collect();
+        for (key, values) in keys.iter().zip(values) {
+            assert_eq!(&key.to_values(schema), values);
+        }
+        for pair in keys.windows(2) {
+            assert!(pair[0] < pair[1], "{:?} >= {:?}", pair[0], pair[1]);
+            assert_eq!(comparator.compare(&pair[0], &pair[1]), Ordering::Less);
+            assert_eq!(comparator.compare(&pair[1], &pair[0]), Ordering::Greater);
+            assert_eq!(comparator.compare(&pair[0], &pair[0]), Ordering::Equal);
+        }
+    }
+
+    #[test]
+    fn integer_test() {
+        // Signed integers of every width sort negative values first.
+        let schema = KeySchema::new(vec![KeyColumnType::TinyInt]);
+        let values: Vec<Vec<KeyValue>> = [i8::MIN, -100, -1, 0, 1, 100, i8::MAX]
+            .into_iter()
+            .map(|v| vec![KeyValue::TinyInt(v)])
+            .collect();
+        assert_sorted::<8>(&schema, &values);
+
+        let schema = KeySchema::new(vec![KeyColumnType::SmallInt]);
+        let values: Vec<Vec<KeyValue>> = [i16::MIN, -300, -1, 0, 255, 256, i16::MAX]
+            .into_iter()
+            .map(|v| vec![KeyValue::SmallInt(v)])
+            .collect();
+        assert_sorted::<8>(&schema, &values);
+
+        let schema = KeySchema::new(vec![KeyColumnType::Integer]);
+        let values: Vec<Vec<KeyValue>> = [i32::MIN, -65536, -256, -1, 0, 1, 256, i32::MAX]
+            .into_iter()
+            .map(|v| vec![KeyValue::Integer(v)])
+            .collect();
+        assert_sorted::<8>(&schema, &values);
+
+        let schema = KeySchema::new(vec![KeyColumnType::BigInt]);
+        let values: Vec<Vec<KeyValue>> = [i64::MIN, -(1 << 40), -1, 0, 1 << 32, i64::MAX]
+            .into_iter()
+            .map(|v| vec![KeyValue::BigInt(v)])
+            .collect();
+        assert_sorted::<8>(&schema, &values);
+        assert_eq!(schema.get_key_size(), 8);
+    }
+
+    #[test]
+    fn varchar_test() {
+        // Strings sort lexicographically, prefixes and trailing zero bytes included.
+        let schema = KeySchema::new(vec![KeyColumnType::Varchar(6)]);
+        assert_eq!(schema.get_key_size(), 8);
+        let values: Vec<Vec<KeyValue>> =
+            ["", "\0", "\0\0", "a", "a\0", "a\0b", "ab", "b", "zzzzzz"]
+                .into_iter()
+                .map(|v| vec![KeyValue::Varchar(v.to_string())])
+                .collect();
+        assert_sorted::<8>(&schema, &values);
+
+        // Values that do not fit or do not match the schema are rejected.
+        let too_long = vec![KeyValue::Varchar("abcdefg".to_string())];
+        assert!(GenericKey::<16>::from_values(&schema, &too_long).is_none());
+        assert!(GenericKey::<8>::from_values(&schema, &[KeyValue::Integer(1)]).is_none());
+        assert!(GenericKey::<8>::from_values(&schema, &[]).is_none());
+        let short = KeySchema::new(vec![KeyColumnType::Varchar(7)]);
+        let value = vec![KeyValue::Varchar("a".to_string())];
+        assert!(GenericKey::<8>::from_values(&short, &value).is_none());
+        assert!(GenericKey::<16>::from_values(&short, &value).is_some());
+    }
+
+    #[test]
+    fn composite_test() {
+        // Composite keys sort by their first column, then by the next ones.
+        let schema = KeySchema::new(vec![
+            KeyColumnType::Varchar(4),
+            KeyColumnType::Integer,
+            KeyColumnType::SmallInt,
+        ]);
+        assert_eq!(schema.get_key_size(), 12);
+        let mut values = Vec::new();
+        for name in ["", "a", "ab", "b"] {
+            for id in [-5, 0, 7] {
+                for seq in [i16::MIN, 3] {
+                    values.push(vec![
+                        KeyValue::Varchar(name.to_string()),
+                        KeyValue::Integer(id),
+                        KeyValue::SmallInt(seq),
+                    ]);
+                }
+            }
+        }
+        assert_sorted::<16>(&schema, &values);
+
+        // The byte order and the comparator agree with the order of the values on random keys.
+        let schema = Arc::new(KeySchema::new(vec![
+            KeyColumnType::BigInt,
+            KeyColumnType::Varchar(3),
+        ]));
+        let comparator = GenericComparator::new(schema.clone());
+        let mut state = 0x2545F4914F6CDD1Du64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        let mut keys = Vec::new();
+        for _ in 0..500 {
+            let id = (next() % 7) as i64 - 3;
+            let len = (next() % 4) as usize;
+            let name: String = (0..len)
+                .map(|_| (b'a' + (next() % 3) as u8) as char)
+                .collect();
+            let values = vec![KeyValue::BigInt(id), KeyValue::Varchar(name)];
+            keys.push((
+                GenericKey::<16>::from_values(&schema, &values).unwrap(),
+                values,
+            ));
+        }
+        for (key, values) in &keys {
+            for (other_key, other_values) in keys.iter().take(50) {
+                assert_eq!(key.cmp(other_key), values.cmp(other_values));
+                assert_eq!(comparator.compare(key, other_key), key.cmp(other_key));
+            }
+        }
+
+        // Keys round-trip through their serialized form.
+        let (key, _) = keys[0];
+        let mut buf = [0; 16];
+        key.serialize(&mut buf);
+        assert_eq!(GenericKey::<16>::deserialize(&buf), key);
+        assert_eq!(key.as_bytes(), &buf);
+
+        // A corrupt varchar length decodes to at most the max length.
+        buf[8..11].copy_from_slice(b"abc");
+        buf[11..13].copy_from_slice(&u16::MAX.to_be_bytes());
+        assert_eq!(
+            GenericKey::<16>::deserialize(&buf).to_values(&schema)[1],
+            KeyValue::Varchar("abc".to_string())
+        );
+    }
+
+    #[test]
+    fn b_plus_tree_test() {
+        let db_name = db_file("generic_key_b_plus_tree");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = Arc::new(BufferPoolManager::new(64, disk_manager, LRUK_REPLACER_K));
+        assert!(ensure_header_page(bpm.as_ref()));
+        let schema = KeySchema::new(vec![KeyColumnType::Varchar(10), KeyColumnType::Integer]);
+        let key = |name: &str, id: i32| {
+            GenericKey::<16>::from_values(
+                &schema,
+                &[KeyValue::Varchar(name.to_string()), KeyValue::Integer(id)],
+            )
+            .unwrap()
+        };
+
+        // A B+ tree over (varchar, integer) keys scans them in logical order.
+        let tree = BPlusTree::new("customers_name_idx", bpm.clone(), 8, 8).unwrap();
+        let names = ["carol", "alice", "bob", "alice2", "dave", "al"];
+        for (i, name) in names.iter().enumerate() {
+            for id in [3, -2, 10] {
+                let rid = RID::new(i as PageId, (id + 2) as SlotOffset);
+                assert!(tree.insert(&key(name, id), rid));
+            }
+        }
+        assert!(!tree.insert(&key("bob", 3), RID::new(0, 0)));
+        assert_eq!(tree.get_value(&key("bob", -2)), Some(RID::new(2, 0)));
+        assert_eq!(tree.get_value(&key("bob", 4)), None);
+
+        let scanned: Vec<Vec<KeyValue>> = tree
+            .begin()
+            .map(|(key, _)| key.to_values(&schema))
+            .collect();
+        let mut sorted_names = names;
+        sorted_names.sort();
+        let expected: Vec<Vec<KeyValue>> = sorted_names
+            .iter()
+            .flat_map(|name| {
+                [-2, 3, 10]
+                    .map(|id| vec![KeyValue::Varchar(name.to_string()), KeyValue::Integer(id)])
+            })
+            .collect();
+        assert_eq!(scanned, expected);
+
+        // A prefix scan over the first column.
+        let alices: Vec<i32> = tree
+            .range(key("alice", i32::MIN)..=key("alice", i32::MAX))
+            .map(|(key, _)| match key.to_values(&schema)[1] {
+                KeyValue::Integer(id) => id,
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(alices, vec![-2, 3, 10]);
+
+        drop(tree);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+}
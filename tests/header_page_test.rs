@@ -0,0 +1,230 @@
+#[path = "common/mod.rs"]
+mod common;
+
+#[cfg(test)]
+mod header_page_test {
+    use std::sync::{Arc, Mutex};
+
+    use bustub_rust::buffer::buffer_pool::*;
+    use bustub_rust::buffer::buffer_pool_manager::*;
+    use bustub_rust::buffer::parallel_buffer_pool_manager::*;
+    use bustub_rust::buffer::replacer::ReplacerPolicy;
+    use bustub_rust::storage::disk::disk_manager::DiskManager;
+    use bustub_rust::storage::index::b_plus_tree::*;
+    use bustub_rust::storage::index::extendible_hash_table::*;
+    use bustub_rust::storage::page::header_page::*;
+    use bustub_rust::storage::table::table_heap::*;
+    use bustub_rust::utils::config::*;
+    use bustub_rust::utils::rid::RID;
+
+    use super::common::*;
+
+    #[test]
+    fn records_test() {
+        let mut data = vec![0u8; BUSTUB_PAGE_SIZE];
+        let mut header = HeaderPage::new(&mut data[..]);
+        header.init();
+        assert_eq!(header.get_record_count(), 0);
+        assert_eq!(header.get_root_id("orders"), None);
+
+        assert!(header.insert_record("orders", 3));
+        assert!(header.insert_record("orders_pk", 7));
+        assert!(header.insert_record(&"x".repeat(MAX_RECORD_NAME_SIZE), 9));
+        assert_eq!(header.get_root_id("orders"), Some(3));
+        assert_eq!(header.get_root_id("orders_pk"), Some(7));
+        assert_eq!(header.get_root_id("order"), None);
+        assert_eq!(header.get_record_count(), 3);
+
+        // Duplicate and invalid names are rejected.
+        assert!(!header.insert_record("orders", 4));
+        assert!(!header.insert_record("", 4));
+        assert!(!header.insert_record(&"x".repeat(MAX_RECORD_NAME_SIZE + 1), 4));
+        assert!(!header.insert_record("a\0b", 4));
+        assert_eq!(header.get_record_count(), 3);
+
+        // Updates and deletes.
+        assert!(header.update_record("orders_pk", 11));
+        assert_eq!(header.get_root_id("orders_pk"), Some(11));
+        assert!(!header.update_record("missing", 1));
+        assert!(header.delete_record("orders"));
+        assert!(!header.delete_record("orders"));
+        assert_eq!(header.get_root_id("orders"), None);
+        assert_eq!(
+            header.get_records(),
+            vec![
+                ("orders_pk".to_string(), 11),
+                ("x".repeat(MAX_RECORD_NAME_SIZE), 9)
+            ]
+        );
+
+        // The page fills up.
+        for i in header.get_record_count()..HEADER_PAGE_CAPACITY {
+            assert!(header.insert_record(&format!("table_{}", i), i as PageId));
+        }
+        assert!(!header.insert_record("one_too_many", 0));
+        assert!(header.delete_record("orders_pk"));
+        assert!(header.insert_record("one_too_many", 0));
+        assert_eq!(header.get_root_id("table_100"), Some(100));
+    }
+
+    #[test]
+    fn ensure_header_page_test() {
+        let db_name = db_file("header_page_ensure");
+        {
+            let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+            let bpm = BufferPoolManager::new(BUFFER_POOL_SIZE, disk_manager, LRUK_REPLACER_K);
+            assert!(ensure_header_page(&bpm));
+            assert!(ensure_header_page(&bpm));
+            // The header page takes page 0, other pages come after it.
+            let page = bpm.new_page().unwrap();
+            assert_eq!(page.get_page_id(), HEADER_PAGE_ID + 1);
+            bpm.unpin_page(page.get_page_id(), false);
+            let mut guard = bpm.fetch_page_write(HEADER_PAGE_ID).unwrap();
+            assert!(HeaderPage::new(guard.get_data_mut()).insert_record("catalog", 1));
+            drop(guard);
+            bpm.flush_all_pages();
+        }
+
+        // After reopening, the header page is kept as is.
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = BufferPoolManager::new(BUFFER_POOL_SIZE, disk_manager, LRUK_REPLACER_K);
+        assert!(ensure_header_page(&bpm));
+        let guard = bpm.fetch_page_read(HEADER_PAGE_ID).unwrap();
+        assert_eq!(
+            HeaderPage::new(guard.get_data()).get_root_id("catalog"),
+            Some(1)
+        );
+        drop(guard);
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn ensure_header_page_instances_test() {
+        let db_name = db_file("header_page_instances");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+
+        // An instance that does not own `HEADER_PAGE_ID` cannot create the header page.
+        let bpm = BufferPoolManager::new_instance(
+            2,
+            2,
+            1,
+            disk_manager,
+            ReplacerPolicy::LruK(LRUK_REPLACER_K),
+        );
+        assert!(!ensure_header_page(&bpm));
+        assert!(!bpm.is_page_allocated(HEADER_PAGE_ID));
+        assert_eq!(bpm.new_page_guarded().unwrap().page_id(), HEADER_PAGE_ID + 1);
+        drop(bpm);
+        remove_db_file(&db_name);
+
+        // A parallel buffer pool routes it to the right instance.
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = ParallelBufferPoolManager::new(2, 2, disk_manager, LRUK_REPLACER_K);
+        assert!(ensure_header_page(&bpm));
+        assert!(ensure_header_page(&bpm));
+        let guard = bpm.fetch_page_read(HEADER_PAGE_ID).unwrap();
+        assert!(HeaderPage::new(guard.get_data()).is_header_page());
+        drop(guard);
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn named_table_heap_test() {
+        let db_name = db_file("header_page_table_heap");
+        let (orders, customers) = {
+            let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+            let bpm = Arc::new(BufferPoolManager::new(
+                BUFFER_POOL_SIZE,
+                disk_manager,
+                LRUK_REPLACER_K,
+            ));
+            assert!(ensure_header_page(bpm.as_ref()));
+            let orders_heap = TableHeap::create_named(bpm.clone(), "orders").unwrap();
+            let customers_heap = TableHeap::create_named(bpm.clone(), "customers").unwrap();
+            // Names are unique and must leave room for the suffix of the map record.
+            assert!(TableHeap::create_named(bpm.clone(), "orders").is_none());
+            assert!(
+                TableHeap::create_named(bpm.clone(), &"x".repeat(MAX_RECORD_NAME_SIZE)).is_none()
+            );
+            assert!(TableHeap::open_named(bpm.clone(), "missing").is_none());
+
+            let orders: Vec<RID> = (0..50)
+                .map(|i| {
+                    orders_heap
+                        .insert_tuple(format!("order {}", i).as_bytes())
+                        .unwrap()
+                })
+                .collect();
+            let customers = customers_heap.insert_tuple(b"customer").unwrap();
+            drop(orders_heap);
+            drop(customers_heap);
+            bpm.flush_all_pages();
+            (orders, customers)
+        };
+
+        // Both heaps are found again by name after reopening the database.
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = Arc::new(BufferPoolManager::new(
+            BUFFER_POOL_SIZE,
+            disk_manager,
+            LRUK_REPLACER_K,
+        ));
+        assert!(ensure_header_page(bpm.as_ref()));
+        let orders_heap = TableHeap::open_named(bpm.clone(), "orders").unwrap();
+        let customers_heap = TableHeap::open_named(bpm.clone(), "customers").unwrap();
+        for (i, &rid) in orders.iter().enumerate() {
+            assert_eq!(
+                orders_heap.get_tuple(rid),
+                Some(format!("order {}", i).into_bytes())
+            );
+        }
+        assert_eq!(
+            customers_heap.get_tuple(customers),
+            Some(b"customer".to_vec())
+        );
+        assert_eq!(customers_heap.iter().count(), 1);
+        let guard = bpm.fetch_page_read(HEADER_PAGE_ID).unwrap();
+        let records = HeaderPage::new(guard.get_data()).get_records();
+        let names: Vec<&str> = records.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["orders", "orders.fsm", "customers", "customers.fsm"]
+        );
+        drop(guard);
+
+        drop(orders_heap);
+        drop(customers_heap);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn uninitialized_header_page_test() {
+        let db_name = db_file("header_page_uninitialized");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = Arc::new(BufferPoolManager::new(
+            BUFFER_POOL_SIZE,
+            disk_manager,
+            LRUK_REPLACER_K,
+        ));
+        // The first page is allocated without ensure_header_page, so it holds no header.
+        let guard = bpm.new_page_guarded().unwrap();
+        assert_eq!(guard.page_id(), HEADER_PAGE_ID);
+        drop(guard);
+        let guard = bpm.fetch_page_read(HEADER_PAGE_ID).unwrap();
+        assert!(!HeaderPage::new(guard.get_data()).is_header_page());
+        drop(guard);
+
+        assert!(TableHeap::create_named(bpm.clone(), "orders").is_none());
+        assert!(TableHeap::open_named(bpm.clone(), "orders").is_none());
+        assert!(BPlusTree::<i64>::new("orders_pk", bpm.clone(), 3, 3).is_none());
+        assert!(ExtendibleHashTable::<i64>::new("orders_hash", bpm.clone(), 2, 9, 8).is_none());
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+}
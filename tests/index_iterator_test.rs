@@ -0,0 +1,204 @@
+#[path = "common/mod.rs"]
+mod common;
+
+#[cfg(test)]
+mod index_iterator_test {
+    use std::ops::Bound::{Excluded, Included};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    use bustub_rust::buffer::buffer_pool_manager::*;
+    use bustub_rust::storage::disk::disk_manager::DiskManager;
+    use bustub_rust::storage::index::b_plus_tree::*;
+    use bustub_rust::storage::page::header_page::*;
+    use bustub_rust::utils::config::*;
+    use bustub_rust::utils::rid::RID;
+
+    use super::common::*;
+
+    fn new_tree(db_name: &str, pool_size: usize) -> (Arc<BufferPoolManager>, BPlusTree<i64>) {
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(db_name)));
+        let bpm = Arc::new(BufferPoolManager::new(
+            pool_size,
+            disk_manager,
+            LRUK_REPLACER_K,
+        ));
+        assert!(ensure_header_page(bpm.as_ref()));
+        let tree = BPlusTree::new("orders_pk", bpm.clone(), 4, 4).unwrap();
+        (bpm, tree)
+    }
+
+    fn rid(key: i64) -> RID {
+        RID::new(key as PageId, key as SlotOffset)
+    }
+
+    fn keys<'a>(iter: impl Iterator<Item = (i64, RID)> + 'a) -> Vec<i64> {
+        iter.map(|(key, value)| {
+            assert_eq!(value, rid(key));
+            key
+        })
+        .collect()
+    }
+
+    #[test]
+    fn empty_tree_test() {
+        let db_name = db_file("index_iterator_empty");
+        let (bpm, tree) = new_tree(&db_name, 64);
+        assert!(tree.begin().is_end());
+        assert!(tree.begin() == tree.end());
+        assert!(tree.rbegin().is_end());
+        assert_eq!(tree.range(0..10).count(), 0);
+
+        drop(tree);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn forward_test() {
+        let db_name = db_file("index_iterator_forward");
+        let (bpm, tree) = new_tree(&db_name, 64);
+        // Even keys from 0 to 198, spread over many leaves.
+        for key in (0..200).rev().filter(|key| key % 2 == 0) {
+            assert!(tree.insert(&key, rid(key)));
+        }
+        let all: Vec<i64> = (0..200).step_by(2).collect();
+
+        assert_eq!(keys(tree.begin()), all);
+        assert_eq!(keys(tree.begin_at(&100)), all[50..]);
+        assert_eq!(keys(tree.begin_at(&101)), all[51..]);
+        assert_eq!(keys(tree.begin_at(&-5)), all);
+        assert!(tree.begin_at(&1000).next().is_none());
+
+        // Ranges with inclusive and exclusive ends.
+        assert_eq!(keys(tree.range(10..20)), vec![10, 12, 14, 16, 18]);
+        assert_eq!(keys(tree.range(10..=20)), vec![10, 12, 14, 16, 18, 20]);
+        assert_eq!(keys(tree.range(11..19)), vec![12, 14, 16, 18]);
+        assert_eq!(
+            keys(tree.range((Excluded(10), Included(20)))),
+            vec![12, 14, 16, 18, 20]
+        );
+        assert_eq!(keys(tree.range((Excluded(10), Excluded(12)))).len(), 0);
+        assert_eq!(keys(tree.range(..6)), vec![0, 2, 4]);
+        assert_eq!(keys(tree.range(194..)), vec![194, 196, 198]);
+        assert_eq!(keys(tree.range(50..50)).len(), 0);
+
+        // An iterator equals `end` once it has run past its last entry.
+        let mut iter = tree.range(0..4);
+        assert!(iter != tree.end());
+        assert_eq!(iter.next(), Some((0, rid(0))));
+        assert_eq!(iter.next(), Some((2, rid(2))));
+        assert_eq!(iter.next(), None);
+        assert!(iter.is_end());
+        assert!(iter == tree.end());
+        assert!(tree.begin() == tree.begin());
+
+        drop(iter);
+        drop(tree);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn reverse_test() {
+        let db_name = db_file("index_iterator_reverse");
+        let (bpm, tree) = new_tree(&db_name, 64);
+        for key in (0..200).filter(|key| key % 2 == 0) {
+            assert!(tree.insert(&key, rid(key)));
+        }
+        let all: Vec<i64> = (0..200).rev().filter(|key| key % 2 == 0).collect();
+
+        assert_eq!(keys(tree.rbegin()), all);
+        assert_eq!(keys(tree.range_rev(10..20)), vec![18, 16, 14, 12, 10]);
+        assert_eq!(keys(tree.range_rev(10..=20)), vec![20, 18, 16, 14, 12, 10]);
+        assert_eq!(
+            keys(tree.range_rev((Excluded(9), Excluded(15)))),
+            vec![14, 12, 10]
+        );
+        assert_eq!(keys(tree.range_rev(..=5)), vec![4, 2, 0]);
+        assert_eq!(keys(tree.range_rev(195..)), vec![198, 196]);
+        assert_eq!(keys(tree.range_rev(..-1)).len(), 0);
+
+        // The prev links survive splits and merges.
+        for key in (1..200).step_by(2) {
+            assert!(tree.insert(&key, rid(key)));
+        }
+        for key in (0..200).filter(|key| key % 3 == 0) {
+            assert!(tree.remove(&key));
+        }
+        let expected: Vec<i64> = (0..200).rev().filter(|key| key % 3 != 0).collect();
+        assert_eq!(keys(tree.rbegin()), expected);
+        assert_eq!(
+            keys(tree.begin()),
+            expected.iter().rev().copied().collect::<Vec<_>>()
+        );
+
+        drop(tree);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn concurrent_scan_test() {
+        let db_name = db_file("index_iterator_concurrent");
+        let (bpm, tree) = new_tree(&db_name, 256);
+        // Multiples of 4 stay in the tree; other keys come and go while scans run.
+        let num_keys = 2000;
+        for key in 0..num_keys {
+            assert!(tree.insert(&key, rid(key)));
+        }
+        let stable: Vec<i64> = (0..num_keys).step_by(4).collect();
+
+        let done = AtomicBool::new(false);
+        thread::scope(|s| {
+            let tree = &tree;
+            let mut writers = Vec::new();
+            for t in 1..4 {
+                writers.push(s.spawn(move || {
+                    for round in 0..3 {
+                        for key in (t..num_keys).step_by(4) {
+                            if round % 2 == 0 {
+                                assert!(tree.remove(&key));
+                            } else {
+                                assert!(tree.insert(&key, rid(key)));
+                            }
+                        }
+                    }
+                }));
+            }
+            for reverse in [false, true, false, true] {
+                let (stable, done) = (&stable, &done);
+                s.spawn(move || {
+                    // Scans in both directions stay sorted and see every stable key.
+                    let mut rounds = 0;
+                    while !done.load(Ordering::Acquire) || rounds < 2 {
+                        let mut seen = if reverse {
+                            keys(tree.rbegin())
+                        } else {
+                            keys(tree.begin())
+                        };
+                        if reverse {
+                            seen.reverse();
+                        }
+                        assert!(seen.windows(2).all(|pair| pair[0] < pair[1]));
+                        seen.retain(|key| key % 4 == 0);
+                        assert_eq!(&seen, stable);
+                        rounds += 1;
+                    }
+                });
+            }
+            for writer in writers {
+                writer.join().unwrap();
+            }
+            done.store(true, Ordering::Release);
+        });
+
+        let expected: Vec<i64> = (0..num_keys).filter(|key| key % 4 == 0).collect();
+        assert_eq!(keys(tree.begin()), expected);
+
+        drop(tree);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+}
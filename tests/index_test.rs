@@ -0,0 +1,240 @@
+#[path = "common/mod.rs"]
+mod common;
+
+#[cfg(test)]
+mod index_test {
+    use std::ops::Bound::{Excluded, Included, Unbounded};
+
+    use bustub_rust::storage::index::b_plus_tree_index::*;
+    use bustub_rust::storage::index::extendible_hash_table_index::*;
+    use bustub_rust::storage::index::generic_key::*;
+    use bustub_rust::storage::index::index::*;
+    use bustub_rust::utils::config::*;
+    use bustub_rust::utils::rid::RID;
+
+    use super::common::*;
+
+    /// A row of the `orders` table: (id, customer, amount).
+    fn row(id: i32) -> Vec<KeyValue> {
+        vec![
+            KeyValue::Integer(id),
+            KeyValue::Varchar(format!("customer{}", id % 7)),
+            KeyValue::BigInt(id as i64 * 100),
+        ]
+    }
+
+    fn rid(id: i32) -> RID {
+        RID::new(id / 10, (id % 10) as SlotOffset)
+    }
+
+    fn id_metadata(name: &str) -> IndexMetadata {
+        IndexMetadata::new(
+            name,
+            "orders",
+            KeySchema::new(vec![KeyColumnType::Integer]),
+            vec![0],
+            true,
+        )
+    }
+
+    fn customer_metadata(name: &str) -> IndexMetadata {
+        IndexMetadata::new(
+            name,
+            "orders",
+            KeySchema::new(vec![KeyColumnType::Varchar(12)]),
+            vec![1],
+            false,
+        )
+    }
+
+    #[test]
+    fn metadata_test() {
+        let metadata = IndexMetadata::new(
+            "orders_customer_amount_idx",
+            "orders",
+            KeySchema::new(vec![KeyColumnType::Varchar(12), KeyColumnType::BigInt]),
+            vec![1, 2],
+            false,
+        );
+        assert_eq!(metadata.get_name(), "orders_customer_amount_idx");
+        assert_eq!(metadata.get_table_name(), "orders");
+        assert_eq!(metadata.get_key_attrs(), &[1, 2]);
+        assert_eq!(metadata.get_index_column_count(), 2);
+        assert!(!metadata.is_unique());
+        assert_eq!(metadata.get_key_schema().get_key_size(), 22);
+
+        // The key of a row is made of the key columns, in key schema order.
+        assert_eq!(
+            metadata.get_key_values(&row(9)),
+            vec![
+                KeyValue::Varchar("customer2".to_string()),
+                KeyValue::BigInt(900)
+            ]
+        );
+    }
+
+    #[test]
+    fn unique_index_test() {
+        let db_name = db_file("index_unique");
+        let bpm = new_bpm(&db_name, 64);
+        let indexes: Vec<Box<dyn Index>> = vec![
+            Box::new(
+                BPlusTreeIndex::<8>::new(id_metadata("orders_pk"), bpm.clone(), 8, 8).unwrap(),
+            ),
+            Box::new(
+                ExtendibleHashTableIndex::<8>::new(
+                    id_metadata("orders_id_hash"),
+                    bpm.clone(),
+                    1,
+                    9,
+                    8,
+                )
+                .unwrap(),
+            ),
+        ];
+
+        // Both structures behave the same behind the `Index` interface.
+        for index in &indexes {
+            for id in 0..200 {
+                let key = index.get_metadata().get_key_values(&row(id));
+                assert!(index.insert_entry(&key, rid(id)));
+            }
+            let key = vec![KeyValue::Integer(5)];
+            assert!(!index.insert_entry(&key, rid(500)));
+            assert_eq!(index.scan_key(&key), vec![rid(5)]);
+            assert!(index.scan_key(&[KeyValue::Integer(200)]).is_empty());
+
+            // Keys that do not match the key schema are rejected.
+            assert!(!index.insert_entry(&[KeyValue::BigInt(1)], rid(1)));
+            assert!(index.scan_key(&[]).is_empty());
+
+            // An entry is only deleted with its own RID.
+            assert!(!index.delete_entry(&key, rid(500)));
+            assert_eq!(index.scan_key(&key), vec![rid(5)]);
+            assert!(index.delete_entry(&key, rid(5)));
+            assert!(!index.delete_entry(&key, rid(5)));
+            assert!(index.scan_key(&key).is_empty());
+        }
+
+        // Only the B+ tree scans ranges.
+        let btree = &indexes[0];
+        let ids = |entries: Vec<(Vec<KeyValue>, RID)>| -> Vec<i32> {
+            entries
+                .into_iter()
+                .map(|(key, value)| match key[..] {
+                    [KeyValue::Integer(id)] => {
+                        assert_eq!(value, rid(id));
+                        id
+                    }
+                    _ => unreachable!(),
+                })
+                .collect()
+        };
+        let (low, high) = ([KeyValue::Integer(3)], [KeyValue::Integer(8)]);
+        assert_eq!(
+            ids(btree.scan_range(Included(&low), Included(&high)).unwrap()),
+            vec![3, 4, 6, 7, 8]
+        );
+        assert_eq!(
+            ids(btree.scan_range(Excluded(&low), Excluded(&high)).unwrap()),
+            vec![4, 6, 7]
+        );
+        assert_eq!(
+            ids(btree
+                .scan_range(Included(&[KeyValue::Integer(197)]), Unbounded)
+                .unwrap()),
+            vec![197, 198, 199]
+        );
+        assert!(indexes[1].scan_range(Unbounded, Unbounded).is_none());
+
+        drop(indexes);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn non_unique_index_test() {
+        let db_name = db_file("index_non_unique");
+        let bpm = new_bpm(&db_name, 64);
+        let indexes: Vec<Box<dyn Index>> = vec![
+            Box::new(
+                BPlusTreeIndex::<32>::new(
+                    customer_metadata("orders_customer_idx"),
+                    bpm.clone(),
+                    8,
+                    8,
+                )
+                .unwrap(),
+            ),
+            Box::new(
+                ExtendibleHashTableIndex::<32>::new(
+                    customer_metadata("orders_customer_hash"),
+                    bpm.clone(),
+                    1,
+                    9,
+                    32,
+                )
+                .unwrap(),
+            ),
+        ];
+
+        // Entries with the same key are told apart by their RID.
+        let customer3 = vec![KeyValue::Varchar("customer3".to_string())];
+        let expected: Vec<RID> = (0..140).filter(|id| id % 7 == 3).map(rid).collect();
+        for index in &indexes {
+            for id in 0..140 {
+                let key = index.get_metadata().get_key_values(&row(id));
+                assert!(index.insert_entry(&key, rid(id)));
+                assert!(!index.insert_entry(&key, rid(id)));
+            }
+            let mut values = index.scan_key(&customer3);
+            values.sort();
+            assert_eq!(values, expected);
+
+            assert!(index.delete_entry(&customer3, rid(3)));
+            assert!(!index.delete_entry(&customer3, rid(3)));
+            assert!(!index.delete_entry(&customer3, rid(11)));
+            let mut values = index.scan_key(&customer3);
+            values.sort();
+            assert_eq!(values, expected[1..]);
+        }
+
+        // Range scans return every entry of every key in the range.
+        let index = &indexes[0];
+        let customer4 = vec![KeyValue::Varchar("customer4".to_string())];
+        let entries = index
+            .scan_range(Included(&customer3), Included(&customer4))
+            .unwrap();
+        assert_eq!(entries.len(), 39);
+        assert!(entries[..19].iter().all(|(key, _)| *key == customer3));
+        assert!(entries[19..].iter().all(|(key, _)| *key == customer4));
+        let entries = index
+            .scan_range(Excluded(&customer3), Excluded(&customer4))
+            .unwrap();
+        assert!(entries.is_empty());
+
+        drop(indexes);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn key_size_test() {
+        let db_name = db_file("index_key_size");
+        let bpm = new_bpm(&db_name, 64);
+
+        // An index whose keys do not fit in `N` bytes cannot be opened. A non-unique B+ tree
+        // index also stores the RID in its keys.
+        let metadata = customer_metadata("orders_customer_idx");
+        assert!(BPlusTreeIndex::<8>::new(metadata.clone(), bpm.clone(), 8, 8).is_none());
+        assert!(BPlusTreeIndex::<16>::new(metadata.clone(), bpm.clone(), 8, 8).is_none());
+        assert!(
+            ExtendibleHashTableIndex::<8>::new(metadata.clone(), bpm.clone(), 1, 9, 8).is_none()
+        );
+        assert!(BPlusTreeIndex::<4>::new(id_metadata("orders_pk"), bpm.clone(), 8, 8).is_some());
+        assert!(ExtendibleHashTableIndex::<16>::new(metadata, bpm.clone(), 1, 9, 8).is_some());
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+}
@@ -1,3 +1,27 @@
+#![allow(clippy::module_inception, clippy::duplicate_mod)]
+
+pub mod b_plus_tree_bulk_load_test;
+pub mod b_plus_tree_concurrent_test;
+pub mod b_plus_tree_test;
+pub mod background_writer_test;
+pub mod buffer_pool_async_test;
+pub mod buffer_pool_manager_test;
+pub mod buffer_pool_stats_test;
+pub mod buffer_pool_wal_test;
+pub mod buffer_pool_warm_up_test;
+pub mod extendible_hash_table_test;
+pub mod free_space_map_test;
+pub mod generic_key_test;
+pub mod header_page_test;
+pub mod index_iterator_test;
+pub mod index_test;
+pub mod optimistic_page_guard_test;
+pub mod overflow_test;
+pub mod page_header_test;
+pub mod parallel_buffer_pool_manager_test;
+pub mod replacer_test;
+pub mod table_heap_test;
+pub mod table_page_test;
 pub mod trie_test; 
 // use bustub_rust::primer::trie::*;
 // use bustub_rust::primer::trie_store::*;
\ No newline at end of file
@@ -0,0 +1,138 @@
+#[path = "common/mod.rs"]
+mod common;
+
+#[cfg(test)]
+mod optimistic_page_guard_test {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use bustub_rust::buffer::buffer_pool::*;
+    use bustub_rust::buffer::buffer_pool_manager::*;
+    use bustub_rust::storage::disk::disk_manager::DiskManager;
+    use bustub_rust::utils::config::*;
+
+    use super::common::*;
+
+    #[test]
+    fn validate_test() {
+        let db_name = db_file("optimistic_validate");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = BufferPoolManager::new(BUFFER_POOL_SIZE, disk_manager, LRUK_REPLACER_K);
+        let page_id = {
+            let mut guard = bpm.new_page_guarded().unwrap().upgrade_write();
+            guard.get_data_mut()[0] = 1;
+            guard.page_id()
+        };
+
+        // Without writers, optimistic reads validate and do not take the latch.
+        let mut guard = bpm.fetch_page_optimistic(page_id).unwrap();
+        assert_eq!(guard.version() % 2, 0);
+        assert_eq!(guard.read(|data| data[0]), Some(1));
+        {
+            let reader = bpm.fetch_page_read(page_id).unwrap();
+            assert_eq!(reader.get_data()[0], 1);
+            assert!(guard.validate());
+        }
+
+        // A writer in between invalidates the read until it is restarted.
+        {
+            let mut writer = bpm.fetch_page_write(page_id).unwrap();
+            assert_eq!(writer.page().get_version() % 2, 1);
+            writer.get_data_mut()[0] = 2;
+        }
+        assert!(!guard.validate());
+        assert_eq!(guard.read(|data| data[0]), None);
+        guard.restart();
+        assert_eq!(guard.read(|data| data[0]), Some(2));
+        assert_eq!(guard.read_with_retry(|data| data[0]), 2);
+
+        // Upgrading succeeds only if the page did not change.
+        let writer = guard.upgrade_write().unwrap();
+        drop(writer);
+        let guard = bpm.fetch_page_optimistic(page_id).unwrap();
+        drop(bpm.fetch_page_write(page_id).unwrap());
+        assert!(guard.upgrade_read().is_none());
+        let guard = bpm.fetch_page_optimistic(page_id).unwrap();
+        assert_eq!(guard.upgrade_read().unwrap().get_data()[0], 2);
+
+        // Every guard released its pin.
+        let page = bpm.fetch_page(page_id, Default::default()).unwrap();
+        assert_eq!(page.get_pin_count(), 1);
+        assert!(bpm.unpin_page(page_id, false));
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn reload_test() {
+        let db_name = db_file("optimistic_reload");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = BufferPoolManager::new(1, disk_manager, LRUK_REPLACER_K);
+        let page_ids: Vec<PageId> = (1..=2)
+            .map(|value| {
+                let mut guard = bpm.new_page_guarded().unwrap().upgrade_write();
+                guard.get_data_mut()[0] = value;
+                guard.page_id()
+            })
+            .collect();
+
+        // Optimistic reads see pages read back into a recycled frame, not what it held before.
+        for (value, &page_id) in (1..=2).zip(&page_ids).rev() {
+            let mut guard = bpm.fetch_page_optimistic(page_id).unwrap();
+            assert_eq!(guard.read_with_retry(|data| data[0]), value);
+        }
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn concurrent_readers_test() {
+        let db_name = db_file("optimistic_concurrent");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = Arc::new(BufferPoolManager::new(
+            BUFFER_POOL_SIZE,
+            disk_manager,
+            LRUK_REPLACER_K,
+        ));
+        let page_id = bpm.new_page_guarded().unwrap().page_id();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        // The writer keeps every byte of the page equal, so a torn read shows mixed bytes.
+        let writer = {
+            let bpm = bpm.clone();
+            let stop = stop.clone();
+            std::thread::spawn(move || {
+                let mut value = 0u8;
+                while !stop.load(Ordering::SeqCst) {
+                    value = value.wrapping_add(1);
+                    let mut guard = bpm.fetch_page_write(page_id).unwrap();
+                    guard.get_data_mut().fill(value);
+                }
+            })
+        };
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let bpm = bpm.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..1000 {
+                        let mut guard = bpm.fetch_page_optimistic(page_id).unwrap();
+                        // Closures only ever see a consistent copy of the page.
+                        let is_consistent = |data: &[u8]| data.iter().all(|&byte| byte == data[0]);
+                        assert_ne!(guard.read(is_consistent), Some(false));
+                        assert!(guard.read_with_retry(is_consistent));
+                    }
+                })
+            })
+            .collect();
+        for reader in readers {
+            reader.join().unwrap();
+        }
+        stop.store(true, Ordering::SeqCst);
+        writer.join().unwrap();
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+}
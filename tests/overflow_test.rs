@@ -0,0 +1,144 @@
+#[path = "common/mod.rs"]
+mod common;
+
+#[cfg(test)]
+mod overflow_test {
+    use std::sync::{Arc, Mutex};
+
+    use bustub_rust::buffer::buffer_pool::*;
+    use bustub_rust::buffer::buffer_pool_manager::*;
+    use bustub_rust::storage::disk::disk_manager::DiskManager;
+    use bustub_rust::storage::page::overflow_page::OVERFLOW_PAGE_CAPACITY;
+    use bustub_rust::storage::table::overflow::*;
+    use bustub_rust::storage::table::table_heap::*;
+    use bustub_rust::utils::config::*;
+    use bustub_rust::utils::rid::RID;
+
+    use super::common::*;
+
+    fn value(len: usize, seed: u8) -> Vec<u8> {
+        (0..len)
+            .map(|i| (i as u8).wrapping_mul(31).wrapping_add(seed))
+            .collect()
+    }
+
+    fn is_cached(bpm: &BufferPoolManager, page_id: PageId) -> bool {
+        bpm.get_pages()
+            .iter()
+            .any(|page| page.get_page_id() == page_id)
+    }
+
+    #[test]
+    fn overflow_pointer_test() {
+        let db_name = db_file("overflow_pointer");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = BufferPoolManager::new(BUFFER_POOL_SIZE, disk_manager, LRUK_REPLACER_K);
+
+        // A value spanning more pages than the buffer pool holds is read back whole.
+        let long = value(OVERFLOW_PAGE_CAPACITY * BUFFER_POOL_SIZE * 2 + 17, 1);
+        let pointer = OverflowPointer::write(&bpm, &long).unwrap();
+        assert_eq!(pointer.len, long.len());
+        assert_eq!(pointer.read(&bpm), Some(long));
+
+        // Pointers survive serialization.
+        let bytes = pointer.to_bytes();
+        assert_eq!(bytes.len(), SIZE_OVERFLOW_POINTER);
+        assert_eq!(OverflowPointer::from_bytes(&bytes), pointer);
+
+        // A value filling exactly one page, and an empty one.
+        let exact = value(OVERFLOW_PAGE_CAPACITY, 2);
+        let exact_pointer = OverflowPointer::write(&bpm, &exact).unwrap();
+        assert_eq!(exact_pointer.read(&bpm), Some(exact));
+        let empty_pointer = OverflowPointer::write(&bpm, &[]).unwrap();
+        assert_eq!(empty_pointer.first_page_id, INVALID_PAGE_ID);
+        assert_eq!(empty_pointer.read(&bpm), Some(vec![]));
+
+        // Deleting a chain drops its pages from the buffer pool.
+        exact_pointer.delete(&bpm);
+        assert!(!is_cached(&bpm, exact_pointer.first_page_id));
+
+        // The pages of a deleted chain are reused by the next one.
+        let reused_pointer = OverflowPointer::write(&bpm, b"reused").unwrap();
+        assert_eq!(reused_pointer.first_page_id, exact_pointer.first_page_id);
+        assert_eq!(reused_pointer.read(&bpm), Some(b"reused".to_vec()));
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn table_heap_overflow_test() {
+        let db_name = db_file("overflow_table_heap");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = Arc::new(BufferPoolManager::new(
+            BUFFER_POOL_SIZE,
+            disk_manager,
+            LRUK_REPLACER_K,
+        ));
+        let heap = TableHeap::new(bpm.clone()).unwrap();
+
+        // Large and small tuples mixed; large ones only take a pointer in the page.
+        let tuples: Vec<Vec<u8>> = (0..30)
+            .map(|i| match i % 3 {
+                0 => value(OVERFLOW_THRESHOLD + 1, i as u8),
+                1 => value(20_000 + i, i as u8),
+                _ => value(50, i as u8),
+            })
+            .collect();
+        let rids: Vec<RID> = tuples
+            .iter()
+            .map(|tuple| heap.insert_tuple(tuple).unwrap())
+            .collect();
+        assert!(rids
+            .iter()
+            .all(|rid| rid.page_id == heap.get_first_page_id()));
+        for (rid, tuple) in rids.iter().zip(&tuples) {
+            assert_eq!(heap.get_tuple(*rid).as_ref(), Some(tuple));
+        }
+        let scanned: Vec<(RID, Vec<u8>)> = heap.iter().collect();
+        let expected: Vec<(RID, Vec<u8>)> = rids.iter().copied().zip(tuples.clone()).collect();
+        assert_eq!(scanned, expected);
+
+        // Updates between inline and out of line storage, both ways.
+        let large = value(10_000, 7);
+        assert!(heap.update_tuple(rids[2], &large));
+        assert_eq!(heap.get_tuple(rids[2]), Some(large.clone()));
+        assert!(heap.update_tuple(rids[1], b"small again"));
+        assert_eq!(heap.get_tuple(rids[1]), Some(b"small again".to_vec()));
+        let larger = value(30_000, 8);
+        assert!(heap.update_tuple(rids[2], &larger));
+        assert_eq!(heap.get_tuple(rids[2]), Some(larger.clone()));
+
+        // Marked deletes keep the chain until applied, which frees it.
+        assert!(heap.mark_delete(rids[4]));
+        assert_eq!(heap.get_tuple(rids[4]), None);
+        assert!(heap.rollback_delete(rids[4]));
+        assert_eq!(heap.get_tuple(rids[4]).as_ref(), Some(&tuples[4]));
+        assert!(heap.mark_delete(rids[4]));
+        assert!(heap.apply_delete(rids[4]));
+        assert_eq!(heap.get_tuple(rids[4]), None);
+        assert_eq!(heap.iter().count(), tuples.len() - 1);
+
+        // Out of line tuples survive a restart.
+        let first_page_id = heap.get_first_page_id();
+        let free_space_map_root_page_id = heap.get_free_space_map_root_page_id();
+        drop(heap);
+        bpm.flush_all_pages();
+        drop(bpm);
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = Arc::new(BufferPoolManager::new(
+            BUFFER_POOL_SIZE,
+            disk_manager,
+            LRUK_REPLACER_K,
+        ));
+        let heap =
+            TableHeap::open(bpm.clone(), first_page_id, free_space_map_root_page_id).unwrap();
+        assert_eq!(heap.get_tuple(rids[2]), Some(larger));
+        assert_eq!(heap.get_tuple(rids[7]).as_ref(), Some(&tuples[7]));
+        assert_eq!(heap.get_tuple(rids[1]), Some(b"small again".to_vec()));
+
+        drop(heap);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+}
@@ -0,0 +1,165 @@
+#[path = "common/mod.rs"]
+mod common;
+
+#[cfg(test)]
+mod page_header_test {
+    use std::collections::HashSet;
+    use std::sync::{Arc, Mutex};
+
+    use bustub_rust::buffer::buffer_pool::*;
+    use bustub_rust::buffer::buffer_pool_manager::*;
+    use bustub_rust::storage::disk::disk_manager::DiskManager;
+    use bustub_rust::storage::page::header_page::ensure_header_page;
+    use bustub_rust::storage::page::page::*;
+    use bustub_rust::storage::page::page_header::*;
+    use bustub_rust::storage::table::table_heap::*;
+    use bustub_rust::utils::config::*;
+
+    use super::common::*;
+
+    #[test]
+    fn header_fields_test() {
+        let mut data = vec![0u8; BUSTUB_PAGE_SIZE];
+        let mut header = PageHeader::new(&mut data[..]);
+        assert!(!header.is_formatted());
+        assert_eq!(header.validate(None), Err(PageHeaderError::BadMagic(0)));
+
+        header.init(7, PageType::BPlusTreeLeaf);
+        header.set_lsn(42);
+        assert!(header.is_formatted());
+        assert_eq!(header.get_page_id(), 7);
+        assert_eq!(header.get_lsn(), 42);
+        assert_eq!(header.get_magic(), PAGE_MAGIC);
+        assert_eq!(header.get_page_type(), Some(PageType::BPlusTreeLeaf));
+        assert_eq!(header.get_format_version(), PAGE_FORMAT_VERSION);
+
+        // The checksum is only valid once stamped.
+        assert!(matches!(
+            header.validate(Some(7)),
+            Err(PageHeaderError::ChecksumMismatch { .. })
+        ));
+        header.update_checksum();
+        assert_eq!(header.get_checksum(), header.compute_checksum());
+        assert_eq!(header.validate(Some(7)), Ok(PageType::BPlusTreeLeaf));
+        assert_eq!(
+            header.validate(Some(8)),
+            Err(PageHeaderError::PageIdMismatch {
+                expected: 8,
+                found: 7
+            })
+        );
+
+        // Any flipped bit, in the header or the body, breaks the checksum.
+        for offset in [0, OFFSET_LSN, SIZE_PAGE_HEADER, BUSTUB_PAGE_SIZE - 1] {
+            data[offset] ^= 0x10;
+            let header = PageHeader::new(&data[..]);
+            assert!(matches!(
+                header.validate(None),
+                Err(PageHeaderError::ChecksumMismatch { .. })
+            ));
+            data[offset] ^= 0x10;
+        }
+        assert!(PageHeader::new(&data[..]).validate(None).is_ok());
+
+        // Unknown page types and format versions are reported.
+        let mut bad_type = data.clone();
+        bad_type[10] = 0xFF;
+        assert_eq!(
+            PageHeader::new(&bad_type[..]).validate(None),
+            Err(PageHeaderError::UnknownPageType(0xFF))
+        );
+        let mut bad_version = data.clone();
+        bad_version[11] = PAGE_FORMAT_VERSION + 1;
+        let error = PageHeader::new(&bad_version[..]).validate(None).unwrap_err();
+        assert_eq!(
+            error,
+            PageHeaderError::UnsupportedVersion(PAGE_FORMAT_VERSION + 1)
+        );
+        assert!(error.to_string().contains("version"));
+    }
+
+    #[test]
+    fn page_types_test() {
+        for tag in 0..=u8::MAX {
+            if let Some(page_type) = PageType::from_u8(tag) {
+                assert_eq!(page_type as u8, tag);
+            }
+        }
+        assert_eq!(PageType::from_u8(0), None);
+        assert_eq!(PageType::from_u8(PageType::Free as u8 + 1), None);
+    }
+
+    #[test]
+    fn page_lsn_test() {
+        // A page can be latched and modified directly, without a page guard.
+        let page = Page::new();
+        page.w_latch();
+        PageHeader::new(page.get_data_mut()).init(3, PageType::Table);
+        page.set_lsn(42);
+        page.w_unlatch();
+
+        page.r_latch();
+        assert_eq!(page.get_lsn(), 42);
+        assert_eq!(PageHeader::new(page.get_data()).get_lsn(), 42);
+        page.r_unlatch();
+    }
+
+    #[test]
+    fn written_pages_validate_test() {
+        let db_name = db_file("page_header_written");
+        {
+            let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+            let bpm = Arc::new(BufferPoolManager::new(
+                BUFFER_POOL_SIZE,
+                disk_manager,
+                LRUK_REPLACER_K,
+            ));
+            assert!(ensure_header_page(bpm.as_ref()));
+            let heap = TableHeap::create_named(bpm.clone(), "t").unwrap();
+            for i in 0..200 {
+                heap.insert_tuple(format!("tuple {}", i).as_bytes())
+                    .unwrap();
+            }
+            heap.insert_tuple(&vec![1; 3 * BUSTUB_PAGE_SIZE]).unwrap();
+            // Pages modified after an earlier write get a fresh checksum.
+            bpm.flush_all_pages();
+            heap.insert_tuple(b"after flush").unwrap();
+            drop(heap);
+            bpm.flush_all_pages();
+        }
+
+        // Every page on disk can be identified and checked without context.
+        let mut disk_manager = DiskManager::new(&db_name);
+        let mut data = vec![0; BUSTUB_PAGE_SIZE];
+        let mut page_types = HashSet::new();
+        for page_id in 0..disk_manager.get_num_pages() as PageId {
+            disk_manager.read_page(page_id, &mut data);
+            let page_type = PageHeader::new(&data[..]).validate(Some(page_id)).unwrap();
+            page_types.insert(page_type);
+        }
+        let expected: HashSet<PageType> = [
+            PageType::Header,
+            PageType::Table,
+            PageType::FreeSpaceMap,
+            PageType::Overflow,
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(page_types, expected);
+
+        // A page damaged on disk is reported when the buffer pool reads it back.
+        disk_manager.read_page(HEADER_PAGE_ID, &mut data);
+        data[BUSTUB_PAGE_SIZE - 1] ^= 0xff;
+        disk_manager.write_page(HEADER_PAGE_ID, &data);
+        drop(disk_manager);
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = BufferPoolManager::new(BUFFER_POOL_SIZE, disk_manager, LRUK_REPLACER_K);
+        assert!(bpm.fetch_page_read(HEADER_PAGE_ID + 1).is_some());
+        assert_eq!(bpm.get_stats().corrupt_reads, 0);
+        assert!(bpm.fetch_page_read(HEADER_PAGE_ID).is_some());
+        assert_eq!(bpm.get_stats().corrupt_reads, 1);
+        drop(bpm);
+
+        remove_db_file(&db_name);
+    }
+}
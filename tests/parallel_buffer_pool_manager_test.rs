@@ -0,0 +1,128 @@
+#[path = "common/mod.rs"]
+mod common;
+
+#[cfg(test)]
+mod parallel_buffer_pool_manager_test {
+    use std::sync::{Arc, Mutex};
+
+    use bustub_rust::buffer::buffer_pool::*;
+    use bustub_rust::buffer::parallel_buffer_pool_manager::*;
+    use bustub_rust::buffer::replacer::*;
+    use bustub_rust::storage::disk::disk_manager::DiskManager;
+    use bustub_rust::storage::table::table_heap::*;
+    use bustub_rust::utils::config::*;
+    use bustub_rust::utils::rid::RID;
+
+    use super::common::*;
+
+    #[test]
+    fn test_sample() {
+        let db_name = db_file("pbpm_sample");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let num_instances = 5;
+        let bpm = ParallelBufferPoolManager::new(
+            num_instances,
+            BUFFER_POOL_SIZE,
+            disk_manager,
+            LRUK_REPLACER_K,
+        );
+        assert_eq!(bpm.get_pool_size(), num_instances * BUFFER_POOL_SIZE);
+
+        // New pages are spread over the instances in round-robin order.
+        let page0 = bpm.new_page().unwrap();
+        assert_eq!(page0.get_page_id(), 0);
+        bpm.fetch_page_write(0).unwrap().get_data_mut()[..5].copy_from_slice(b"Hello");
+        for i in 1..(num_instances * BUFFER_POOL_SIZE) as PageId {
+            assert_eq!(bpm.new_page().unwrap().get_page_id(), i);
+        }
+
+        // Once every instance is full, no new page can be created.
+        assert!(bpm.new_page().is_none());
+
+        // Freeing a frame in one instance is enough for the next allocation, which
+        // lands in that instance.
+        assert!(bpm.unpin_page(0, true));
+        let page = bpm.new_page().unwrap();
+        assert_eq!(page.get_page_id() as usize % num_instances, 0);
+        assert!(bpm.fetch_page(0, AccessType::Unknown).is_none());
+
+        // Page 0 was written back on eviction and can be read again.
+        assert!(bpm.unpin_page(page.get_page_id(), false));
+        assert_eq!(&bpm.fetch_page_read(0).unwrap().get_data()[..5], b"Hello");
+        assert!(bpm.delete_page(0));
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn test_concurrent() {
+        let db_name = db_file("pbpm_concurrent");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = Arc::new(ParallelBufferPoolManager::new(
+            4,
+            BUFFER_POOL_SIZE,
+            disk_manager,
+            LRUK_REPLACER_K,
+        ));
+
+        let mut handles = vec![];
+        for thread in 0..4u32 {
+            let bpm = bpm.clone();
+            handles.push(std::thread::spawn(move || {
+                let mut page_ids = vec![];
+                for i in 0..25u32 {
+                    let mut guard = bpm.new_page_guarded().unwrap().upgrade_write();
+                    guard.get_data_mut()[..8]
+                        .copy_from_slice(&[thread.to_le_bytes(), i.to_le_bytes()].concat());
+                    page_ids.push(guard.page_id());
+                }
+                for (i, page_id) in page_ids.into_iter().enumerate() {
+                    let guard = bpm.fetch_page_read(page_id).unwrap();
+                    assert_eq!(&guard.get_data()[..4], &thread.to_le_bytes());
+                    assert_eq!(&guard.get_data()[4..8], &(i as u32).to_le_bytes());
+                }
+            }));
+        }
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn test_as_buffer_pool() {
+        let db_name = db_file("pbpm_as_buffer_pool");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm: Arc<dyn BufferPool> = Arc::new(ParallelBufferPoolManager::new(
+            3,
+            BUFFER_POOL_SIZE,
+            disk_manager,
+            LRUK_REPLACER_K,
+        ));
+
+        // A table heap works on top of the parallel pool, with its pages spread over the
+        // instances.
+        let table_heap = TableHeap::new(bpm.clone()).unwrap();
+        let tuple = vec![7u8; 1000];
+        let rids: Vec<RID> = (0..20)
+            .map(|_| table_heap.insert_tuple(&tuple).unwrap())
+            .collect();
+        for rid in &rids {
+            assert_eq!(table_heap.get_tuple(*rid).unwrap(), tuple);
+        }
+
+        // The dirty page tables of the instances are merged, and write-backs drain all of them.
+        let dirty_page_table = bpm.get_dirty_page_table();
+        assert!(dirty_page_table.keys().any(|&page_id| page_id % 3 == 1));
+        assert!(dirty_page_table.keys().any(|&page_id| page_id % 3 == 2));
+        while bpm.write_back_dirty_pages(2) > 0 {}
+        assert!(bpm.get_dirty_page_table().is_empty());
+
+        drop(table_heap);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+}
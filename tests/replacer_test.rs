@@ -0,0 +1,270 @@
+#[cfg(test)]
+mod lru_k_replacer_test {
+    use bustub_rust::buffer::lru_k_replacer::*;
+    use bustub_rust::buffer::replacer::*;
+
+    #[test]
+    fn test_sample() {
+        let lru_replacer = LRUKReplacer::new(7, 2);
+
+        // Add six elements to the replacer. We have [1,2,3,4,5]. Frame 6 is non-evictable.
+        for frame_id in 1..=6 {
+            lru_replacer.record_access(frame_id, frame_id, AccessType::Unknown);
+        }
+        for frame_id in 1..=5 {
+            lru_replacer.set_evictable(frame_id, true);
+        }
+        lru_replacer.set_evictable(6, false);
+        assert_eq!(lru_replacer.size(), 5);
+
+        // Insert access history for frame 1. Now frame 1 has two access histories.
+        // All other frames have max backward k-dist. The order of eviction is [2,3,4,5,1].
+        lru_replacer.record_access(1, 1, AccessType::Unknown);
+
+        // Evict three pages from the replacer. Elements with max k-distance should be
+        // popped first based on LRU.
+        assert_eq!(lru_replacer.evict(), Some(2));
+        assert_eq!(lru_replacer.evict(), Some(3));
+        assert_eq!(lru_replacer.evict(), Some(4));
+        assert_eq!(lru_replacer.size(), 2);
+
+        // Now replacer has frames [5,1]. Insert new frames 3, 4, and update access
+        // history for 5. We should end with [3,1,5,4].
+        lru_replacer.record_access(3, 3, AccessType::Unknown);
+        lru_replacer.record_access(4, 4, AccessType::Unknown);
+        lru_replacer.record_access(5, 5, AccessType::Unknown);
+        lru_replacer.record_access(4, 4, AccessType::Unknown);
+        lru_replacer.set_evictable(3, true);
+        lru_replacer.set_evictable(4, true);
+        assert_eq!(lru_replacer.size(), 4);
+
+        // Continue looking for victims. We expect 3 to be evicted next.
+        assert_eq!(lru_replacer.evict(), Some(3));
+        assert_eq!(lru_replacer.size(), 3);
+
+        // Set 6 to be evictable. 6 should be evicted next since it has max backward k-dist.
+        lru_replacer.set_evictable(6, true);
+        assert_eq!(lru_replacer.size(), 4);
+        assert_eq!(lru_replacer.evict(), Some(6));
+        assert_eq!(lru_replacer.size(), 3);
+
+        // Now we have [1,5,4]. Continue looking for victims.
+        lru_replacer.set_evictable(1, false);
+        assert_eq!(lru_replacer.size(), 2);
+        assert_eq!(lru_replacer.evict(), Some(5));
+        assert_eq!(lru_replacer.size(), 1);
+
+        // Update access history for 1. Now we have [4,1]. Next victim is 4.
+        lru_replacer.record_access(1, 1, AccessType::Unknown);
+        lru_replacer.record_access(1, 1, AccessType::Unknown);
+        lru_replacer.set_evictable(1, true);
+        assert_eq!(lru_replacer.size(), 2);
+        assert_eq!(lru_replacer.evict(), Some(4));
+
+        assert_eq!(lru_replacer.size(), 1);
+        assert_eq!(lru_replacer.evict(), Some(1));
+        assert_eq!(lru_replacer.size(), 0);
+
+        // This operation should not modify size.
+        assert_eq!(lru_replacer.evict(), None);
+        assert_eq!(lru_replacer.size(), 0);
+    }
+
+    #[test]
+    fn test_scan_does_not_promote() {
+        let lru_replacer = LRUKReplacer::new(3, 2);
+        lru_replacer.record_access(0, 0, AccessType::Unknown);
+        lru_replacer.record_access(1, 1, AccessType::Unknown);
+        // A second access from a scan leaves frame 0 with a single access in its history.
+        lru_replacer.record_access(0, 0, AccessType::Scan);
+        lru_replacer.set_evictable(0, true);
+        lru_replacer.set_evictable(1, true);
+        assert_eq!(lru_replacer.evict(), Some(0));
+        assert_eq!(lru_replacer.evict(), Some(1));
+
+        let stats = lru_replacer.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 2);
+        assert_eq!(stats.evictions, 2);
+    }
+}
+
+mod clock_replacer_test {
+    use bustub_rust::buffer::clock_replacer::*;
+    use bustub_rust::buffer::replacer::*;
+
+    #[test]
+    fn test_sample() {
+        let clock_replacer = ClockReplacer::new(7);
+
+        for frame_id in 1..=6 {
+            clock_replacer.record_access(frame_id, frame_id, AccessType::Unknown);
+            clock_replacer.set_evictable(frame_id, true);
+        }
+        clock_replacer.set_evictable(6, false);
+        assert_eq!(clock_replacer.size(), 5);
+
+        // Every frame is referenced, so the first sweep clears all bits and the hand comes back
+        // around to frame 1.
+        assert_eq!(clock_replacer.evict(), Some(1));
+        assert_eq!(clock_replacer.evict(), Some(2));
+
+        // Frame 3 gets a second chance after being accessed again.
+        clock_replacer.record_access(3, 3, AccessType::Unknown);
+        assert_eq!(clock_replacer.evict(), Some(4));
+        assert_eq!(clock_replacer.size(), 2);
+
+        clock_replacer.remove(5);
+        assert_eq!(clock_replacer.size(), 1);
+        assert_eq!(clock_replacer.evict(), Some(3));
+        assert_eq!(clock_replacer.evict(), None);
+
+        clock_replacer.set_evictable(6, true);
+        assert_eq!(clock_replacer.evict(), Some(6));
+        assert_eq!(clock_replacer.size(), 0);
+    }
+
+    #[test]
+    fn test_scan_does_not_promote() {
+        let clock_replacer = ClockReplacer::new(3);
+        clock_replacer.record_access(0, 0, AccessType::Unknown);
+        clock_replacer.record_access(1, 1, AccessType::Unknown);
+        // A frame loaded by a scan starts without a second chance.
+        clock_replacer.record_access(2, 2, AccessType::Scan);
+        for frame_id in 0..3 {
+            clock_replacer.set_evictable(frame_id, true);
+        }
+        assert_eq!(clock_replacer.evict(), Some(2));
+
+        let stats = clock_replacer.stats();
+        assert_eq!(stats.hits, 0);
+        assert_eq!(stats.misses, 3);
+        assert_eq!(stats.evictions, 1);
+    }
+}
+
+mod arc_replacer_test {
+    use bustub_rust::buffer::arc_replacer::*;
+    use bustub_rust::buffer::replacer::*;
+
+    #[test]
+    fn test_sample() {
+        let arc_replacer = ARCReplacer::new(4);
+
+        // Pages 1..=4 are loaded into frames 0..=3, then page 1 is referenced again and moves to
+        // the MFU list.
+        for frame_id in 0..4 {
+            arc_replacer.record_access(frame_id, frame_id + 1, AccessType::Unknown);
+        }
+        arc_replacer.record_access(0, 1, AccessType::Unknown);
+        for frame_id in 0..4 {
+            arc_replacer.set_evictable(frame_id, true);
+        }
+        assert_eq!(arc_replacer.size(), 4);
+
+        // The MRU list is over its target, so its LRU frame (page 2) goes first.
+        assert_eq!(arc_replacer.evict(), Some(1));
+        arc_replacer.record_access(1, 5, AccessType::Scan);
+        arc_replacer.set_evictable(1, true);
+        assert_eq!(arc_replacer.evict(), Some(2));
+
+        // Page 2 comes back while it is remembered in the MRU ghost list: it is admitted straight
+        // into the MFU list.
+        arc_replacer.record_access(2, 2, AccessType::Unknown);
+        arc_replacer.set_evictable(2, true);
+        // The eviction order follows the MRU target as the MRU list shrinks, instead of emptying
+        // one list before the other.
+        assert_eq!(arc_replacer.eviction_order(), [3, 0, 2, 1]);
+        assert_eq!(arc_replacer.evict(), Some(3));
+
+        // The ghost hit grew the MRU target to one frame, so the MFU list now gives up its LRU
+        // frame before the last page seen only once.
+        assert_eq!(arc_replacer.evict(), Some(0));
+        assert_eq!(arc_replacer.evict(), Some(2));
+        assert_eq!(arc_replacer.evict(), Some(1));
+        assert_eq!(arc_replacer.evict(), None);
+
+        let stats = arc_replacer.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 6);
+        assert_eq!(stats.evictions, 6);
+    }
+
+    #[test]
+    fn test_scan_does_not_promote() {
+        let arc_replacer = ARCReplacer::new(2);
+        arc_replacer.record_access(0, 1, AccessType::Unknown);
+        arc_replacer.record_access(1, 2, AccessType::Unknown);
+        // Page 1 is hit again by a scan and stays in the MRU list, so it is still the LRU frame.
+        arc_replacer.record_access(0, 1, AccessType::Scan);
+        arc_replacer.set_evictable(0, true);
+        arc_replacer.set_evictable(1, true);
+        assert_eq!(arc_replacer.evict(), Some(0));
+
+        // A scan hitting the ghost of page 1 brings it back as a new page.
+        arc_replacer.record_access(0, 1, AccessType::Scan);
+        arc_replacer.set_evictable(0, true);
+        assert_eq!(arc_replacer.evict(), Some(1));
+        assert_eq!(arc_replacer.evict(), Some(0));
+
+        let stats = arc_replacer.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 3);
+        assert_eq!(stats.evictions, 3);
+    }
+}
+
+mod two_queue_replacer_test {
+    use bustub_rust::buffer::replacer::*;
+    use bustub_rust::buffer::two_queue_replacer::*;
+
+    #[test]
+    fn test_sample() {
+        let two_queue_replacer = TwoQueueReplacer::new_with_sizes(4, 1, 2);
+
+        for frame_id in 0..4 {
+            two_queue_replacer.record_access(frame_id, frame_id + 1, AccessType::Unknown);
+            two_queue_replacer.set_evictable(frame_id, true);
+        }
+
+        // A1in is over its target size, so the oldest page (1) is evicted and remembered in A1out.
+        assert_eq!(two_queue_replacer.evict(), Some(0));
+
+        // Page 1 is re-referenced while in A1out and gets admitted to Am.
+        two_queue_replacer.record_access(0, 1, AccessType::Unknown);
+
+        // A hit in A1in does not promote page 2.
+        two_queue_replacer.record_access(1, 2, AccessType::Unknown);
+        assert_eq!(two_queue_replacer.evict(), Some(1));
+        two_queue_replacer.set_evictable(0, true);
+        assert_eq!(two_queue_replacer.eviction_order(), [2, 0, 3]);
+        assert_eq!(two_queue_replacer.evict(), Some(2));
+
+        // A1in is back at its target size, so Am gives up its LRU page next.
+        assert_eq!(two_queue_replacer.evict(), Some(0));
+        assert_eq!(two_queue_replacer.evict(), Some(3));
+        assert_eq!(two_queue_replacer.evict(), None);
+
+        let stats = two_queue_replacer.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 5);
+        assert_eq!(stats.evictions, 5);
+    }
+
+    #[test]
+    fn test_scan_does_not_promote() {
+        let two_queue_replacer = TwoQueueReplacer::new_with_sizes(3, 1, 2);
+        for frame_id in 0..3 {
+            two_queue_replacer.record_access(frame_id, frame_id + 1, AccessType::Unknown);
+            two_queue_replacer.set_evictable(frame_id, true);
+        }
+        assert_eq!(two_queue_replacer.evict(), Some(0));
+
+        // Page 1 comes back from A1out through a scan, so it re-enters A1in instead of Am.
+        two_queue_replacer.record_access(0, 1, AccessType::Scan);
+        two_queue_replacer.set_evictable(0, true);
+        assert_eq!(two_queue_replacer.evict(), Some(1));
+        assert_eq!(two_queue_replacer.evict(), Some(2));
+        assert_eq!(two_queue_replacer.evict(), Some(0));
+    }
+}
@@ -0,0 +1,206 @@
+#[path = "common/mod.rs"]
+mod common;
+
+#[cfg(test)]
+mod table_heap_test {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use bustub_rust::buffer::buffer_pool::*;
+    use bustub_rust::buffer::buffer_pool_manager::*;
+    use bustub_rust::storage::disk::disk_manager::DiskManager;
+    use bustub_rust::storage::table::table_heap::*;
+    use bustub_rust::utils::config::*;
+    use bustub_rust::utils::rid::RID;
+
+    use super::common::*;
+
+    fn tuple(i: usize) -> Vec<u8> {
+        format!("tuple-{:04}-{}", i, "x".repeat(i % 50)).into_bytes()
+    }
+
+    #[test]
+    fn insert_get_iterate_test() {
+        let db_name = db_file("table_heap_basic");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = Arc::new(BufferPoolManager::new(
+            BUFFER_POOL_SIZE,
+            disk_manager,
+            LRUK_REPLACER_K,
+        ));
+        let heap = TableHeap::new(bpm.clone()).unwrap();
+
+        // Enough tuples to span many more pages than the buffer pool holds.
+        let rids: Vec<RID> = (0..1000)
+            .map(|i| heap.insert_tuple(&tuple(i)).unwrap())
+            .collect();
+        let num_pages = rids
+            .iter()
+            .map(|rid| rid.page_id)
+            .collect::<std::collections::HashSet<_>>();
+        assert!(num_pages.len() > BUFFER_POOL_SIZE);
+        for (i, &rid) in rids.iter().enumerate() {
+            assert_eq!(heap.get_tuple(rid), Some(tuple(i)));
+        }
+        // Later, smaller tuples fill the gaps left in earlier pages, so the scan is in RID order
+        // rather than insertion order.
+        let scanned: Vec<(RID, Vec<u8>)> = heap.iter().collect();
+        let mut expected: Vec<(RID, Vec<u8>)> = rids
+            .iter()
+            .enumerate()
+            .map(|(i, &rid)| (rid, tuple(i)))
+            .collect();
+        expected.sort();
+        assert_eq!(scanned, expected);
+
+        // Empty tuples are rejected, tuples larger than a page are stored out of line.
+        assert!(heap.insert_tuple(&[]).is_none());
+        let rid = heap.insert_tuple(&vec![7; MAX_TUPLE_SIZE]).unwrap();
+        assert_eq!(heap.get_tuple(rid), Some(vec![7; MAX_TUPLE_SIZE]));
+        let rid = heap.insert_tuple(&vec![8; MAX_TUPLE_SIZE + 1]).unwrap();
+        assert_eq!(heap.get_tuple(rid), Some(vec![8; MAX_TUPLE_SIZE + 1]));
+
+        drop(heap);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn update_delete_test() {
+        let db_name = db_file("table_heap_update_delete");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = Arc::new(BufferPoolManager::new(
+            BUFFER_POOL_SIZE,
+            disk_manager,
+            LRUK_REPLACER_K,
+        ));
+        let heap = TableHeap::new(bpm.clone()).unwrap();
+        let rids: Vec<RID> = (0..100)
+            .map(|i| heap.insert_tuple(&tuple(i)).unwrap())
+            .collect();
+
+        assert!(heap.update_tuple(rids[3], b"updated"));
+        assert_eq!(heap.get_tuple(rids[3]), Some(b"updated".to_vec()));
+
+        // Marked deletes are hidden from gets and scans until rolled back.
+        assert!(heap.mark_delete(rids[5]));
+        assert_eq!(heap.get_tuple(rids[5]), None);
+        assert!(!heap.update_tuple(rids[5], b"x"));
+        assert_eq!(heap.iter().count(), 99);
+        assert!(heap.rollback_delete(rids[5]));
+        assert_eq!(heap.get_tuple(rids[5]), Some(tuple(5)));
+
+        // Applied deletes free the slot, which the next insert may reuse.
+        assert!(heap.mark_delete(rids[7]));
+        assert!(heap.apply_delete(rids[7]));
+        assert!(!heap.apply_delete(rids[7]));
+        assert_eq!(heap.iter().count(), 99);
+        assert_eq!(heap.insert_tuple(b"reused"), Some(rids[7]));
+
+        // Unknown record ids are reported as such.
+        let bogus = RID::new(rids[0].page_id, 10_000);
+        assert_eq!(heap.get_tuple(bogus), None);
+        assert!(!heap.mark_delete(bogus));
+        assert_eq!(heap.get_tuple(RID::default()), None);
+
+        // A scan that finds no frame for its next page ends early and says so.
+        let pinned: Vec<_> = (0..BUFFER_POOL_SIZE)
+            .map(|_| bpm.new_page_guarded().unwrap())
+            .collect();
+        let mut iter = heap.iter();
+        assert!(iter.next().is_none());
+        assert!(iter.is_interrupted());
+        drop(pinned);
+        let mut iter = heap.iter();
+        assert_eq!(iter.by_ref().count(), 100);
+        assert!(!iter.is_interrupted());
+
+        drop(heap);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn restart_test() {
+        let db_name = db_file("table_heap_restart");
+        let (first_page_id, free_space_map_root_page_id, rids) = {
+            let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+            let bpm = Arc::new(BufferPoolManager::new(
+                BUFFER_POOL_SIZE,
+                disk_manager,
+                LRUK_REPLACER_K,
+            ));
+            let heap = TableHeap::new(bpm.clone()).unwrap();
+            let rids: Vec<RID> = (0..300)
+                .map(|i| heap.insert_tuple(&tuple(i)).unwrap())
+                .collect();
+            bpm.flush_all_pages();
+            (
+                heap.get_first_page_id(),
+                heap.get_free_space_map_root_page_id(),
+                rids,
+            )
+        };
+
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = Arc::new(BufferPoolManager::new(
+            BUFFER_POOL_SIZE,
+            disk_manager,
+            LRUK_REPLACER_K,
+        ));
+        let heap =
+            TableHeap::open(bpm.clone(), first_page_id, free_space_map_root_page_id).unwrap();
+        for (i, &rid) in rids.iter().enumerate() {
+            assert_eq!(heap.get_tuple(rid), Some(tuple(i)));
+        }
+
+        // Pages allocated after the restart do not overwrite the existing ones.
+        let max_page_id = rids.iter().map(|rid| rid.page_id).max().unwrap();
+        let other = TableHeap::new(bpm.clone()).unwrap();
+        assert!(other.get_first_page_id() > max_page_id);
+        other.insert_tuple(b"other table").unwrap();
+        assert_eq!(heap.iter().count(), rids.len());
+        assert_eq!(other.iter().count(), 1);
+
+        drop(heap);
+        drop(other);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+
+    #[test]
+    fn concurrent_insert_test() {
+        let db_name = db_file("table_heap_concurrent");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = Arc::new(BufferPoolManager::new(
+            BUFFER_POOL_SIZE,
+            disk_manager,
+            LRUK_REPLACER_K,
+        ));
+        let heap = Arc::new(TableHeap::new(bpm.clone()).unwrap());
+        let threads: Vec<_> = (0..4)
+            .map(|t| {
+                let heap = heap.clone();
+                std::thread::spawn(move || {
+                    (0..200)
+                        .map(|i| {
+                            let i = t * 1000 + i;
+                            (heap.insert_tuple(&tuple(i)).unwrap(), tuple(i))
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        let inserted: HashMap<RID, Vec<u8>> = threads
+            .into_iter()
+            .flat_map(|thread| thread.join().unwrap())
+            .collect();
+        assert_eq!(inserted.len(), 800);
+        let scanned: HashMap<RID, Vec<u8>> = heap.iter().collect();
+        assert_eq!(scanned, inserted);
+
+        drop(heap);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+}
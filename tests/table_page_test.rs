@@ -0,0 +1,154 @@
+#[path = "common/mod.rs"]
+mod common;
+
+#[cfg(test)]
+mod table_page_test {
+    use std::sync::{Arc, Mutex};
+
+    use bustub_rust::buffer::buffer_pool::*;
+    use bustub_rust::buffer::buffer_pool_manager::*;
+    use bustub_rust::storage::disk::disk_manager::DiskManager;
+    use bustub_rust::storage::page::table_page::*;
+    use bustub_rust::utils::config::*;
+
+    use super::common::*;
+
+    fn empty_page() -> TablePage<Vec<u8>> {
+        let mut page = TablePage::new(vec![0; BUSTUB_PAGE_SIZE]);
+        page.init(3, 2, INVALID_PAGE_ID);
+        page
+    }
+
+    #[test]
+    fn header_test() {
+        let mut page = empty_page();
+        assert_eq!(page.get_page_id(), 3);
+        assert_eq!(page.get_prev_page_id(), 2);
+        assert_eq!(page.get_next_page_id(), INVALID_PAGE_ID);
+        assert_eq!(page.get_tuple_count(), 0);
+        assert_eq!(
+            page.get_free_space_remaining(),
+            BUSTUB_PAGE_SIZE - SIZE_TABLE_PAGE_HEADER
+        );
+        page.set_next_page_id(4);
+        assert_eq!(page.get_next_page_id(), 4);
+    }
+
+    #[test]
+    fn insert_and_delete_test() {
+        let mut page = empty_page();
+        assert_eq!(page.insert_tuple(b"first"), Some(0));
+        assert_eq!(page.insert_tuple(b"second"), Some(1));
+        assert_eq!(page.insert_tuple(b"third"), Some(2));
+        assert_eq!(page.get_tuple(1), Some(&b"second"[..]));
+        assert_eq!(page.get_tuple(3), None);
+
+        // A marked delete hides the tuple until it is rolled back.
+        assert!(page.mark_delete(1));
+        assert!(!page.mark_delete(1));
+        assert!(page.is_deleted(1));
+        assert_eq!(page.get_tuple(1), None);
+        assert_eq!(page.get_live_slots(), vec![0, 2]);
+        assert!(page.rollback_delete(1));
+        assert_eq!(page.get_tuple(1), Some(&b"second"[..]));
+
+        // Applying a delete frees the slot for the next insert; the other slots
+        // keep their numbers.
+        assert!(page.mark_delete(1));
+        assert!(page.apply_delete(1));
+        assert!(!page.apply_delete(1));
+        assert_eq!(page.get_fragmented_space(), 6);
+        assert_eq!(page.insert_tuple(b"fourth"), Some(1));
+        assert_eq!(page.get_tuple(0), Some(&b"first"[..]));
+        assert_eq!(page.get_tuple(1), Some(&b"fourth"[..]));
+        assert_eq!(page.get_tuple(2), Some(&b"third"[..]));
+
+        // Deleting the last slots shrinks the slot array.
+        assert!(page.apply_delete(2));
+        assert_eq!(page.get_tuple_count(), 2);
+    }
+
+    #[test]
+    fn update_test() {
+        let mut page = empty_page();
+        assert_eq!(page.insert_tuple(b"aaaa"), Some(0));
+        assert_eq!(page.insert_tuple(b"bbbb"), Some(1));
+
+        // Tuples that are not larger are updated in place.
+        assert!(page.update_tuple(0, b"cc"));
+        assert_eq!(page.get_tuple(0), Some(&b"cc"[..]));
+        assert_eq!(page.get_fragmented_space(), 2);
+
+        // Larger tuples move to the free space but keep their slot.
+        assert!(page.update_tuple(1, b"dddddddd"));
+        assert_eq!(page.get_tuple(1), Some(&b"dddddddd"[..]));
+        assert_eq!(page.get_tuple(0), Some(&b"cc"[..]));
+
+        // Deleted or missing tuples cannot be updated, nor can tuples that do not fit.
+        assert!(page.mark_delete(0));
+        assert!(!page.update_tuple(0, b"e"));
+        assert!(!page.update_tuple(5, b"e"));
+        assert!(!page.update_tuple(1, &vec![1; BUSTUB_PAGE_SIZE]));
+        assert_eq!(page.get_tuple(1), Some(&b"dddddddd"[..]));
+    }
+
+    #[test]
+    fn compaction_test() {
+        let mut page = empty_page();
+        let tuple_size = 100;
+        let mut slots = Vec::new();
+        while let Some(slot_num) = page.insert_tuple(&vec![slots.len() as u8; tuple_size]) {
+            slots.push(slot_num);
+        }
+        assert_eq!(
+            slots.len(),
+            (BUSTUB_PAGE_SIZE - SIZE_TABLE_PAGE_HEADER) / (tuple_size + SIZE_SLOT)
+        );
+
+        // Every other tuple is deleted; a tuple larger than any single hole fits only
+        // after compaction, which the insert does on its own.
+        for &slot_num in slots.iter().step_by(2) {
+            assert!(page.apply_delete(slot_num));
+        }
+        let big = vec![0xff; 3 * tuple_size];
+        assert!(page.get_free_space_remaining() < big.len());
+        let slot_num = page.insert_tuple(&big).unwrap();
+        assert_eq!(page.get_tuple(slot_num), Some(&big[..]));
+        for &slot_num in slots.iter().skip(1).step_by(2) {
+            assert_eq!(page.get_tuple(slot_num), Some(&vec![slot_num as u8; tuple_size][..]));
+        }
+
+        page.compact();
+        assert_eq!(page.get_fragmented_space(), 0);
+    }
+
+    #[test]
+    fn buffer_pool_page_test() {
+        let db_name = db_file("table_page");
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = BufferPoolManager::new(BUFFER_POOL_SIZE, disk_manager, LRUK_REPLACER_K);
+
+        let page_id = {
+            let mut guard = bpm.new_page_guarded().unwrap().upgrade_write();
+            let page_id = guard.page_id();
+            let mut page = TablePage::new(guard.get_data_mut());
+            page.init(page_id, INVALID_PAGE_ID, INVALID_PAGE_ID);
+            assert_eq!(page.insert_tuple(b"persisted"), Some(0));
+            page_id
+        };
+        assert!(bpm.flush_page(page_id));
+
+        // The page is read back from disk by a fresh buffer pool.
+        drop(bpm);
+        let disk_manager = Arc::new(Mutex::new(DiskManager::new(&db_name)));
+        let bpm = BufferPoolManager::new(BUFFER_POOL_SIZE, disk_manager, LRUK_REPLACER_K);
+        let guard = bpm.fetch_page_read(page_id).unwrap();
+        let page = TablePage::new(guard.get_data());
+        assert_eq!(page.get_page_id(), page_id);
+        assert_eq!(page.get_tuple(0), Some(&b"persisted"[..]));
+
+        drop(guard);
+        drop(bpm);
+        remove_db_file(&db_name);
+    }
+}
@@ -1,46 +1,107 @@
 #[cfg(test)]
 mod trie_test {
+    use std::sync::Arc;
+
     use bustub_rust::primer::trie::*;
 
     #[test]
     fn test_trie() {
         let trie = Trie::<i32>::new();
-        trie.put("hello".to_string(), 1);
-        trie.put("world".to_string(), 2);
-        trie.put("hello world".to_string(), 3);
-        assert_eq!(*trie.get("hello".to_string()).unwrap().read().unwrap(), 1);
+        let trie = trie.put("hello".to_string(), 1);
+        let trie = trie.put("world".to_string(), 2);
+        let trie = trie.put("hello world".to_string(), 3);
+        assert_eq!(*trie.get("hello".to_string()).unwrap(), 1);
+        assert_eq!(*trie.get("hello world".to_string()).unwrap(), 3);
+        assert!(trie.get("hell".to_string()).is_none());
     }
 
     #[test]
     fn test_remove() {
         let trie = Trie::<i32>::new();
-        trie.put("hello".to_string(), 1);
-        trie.put("hel".to_string(), 2);
-        trie.put("h".to_string(), 3);
-        trie.remove("hello".to_string());
+        let trie = trie.put("hello".to_string(), 1);
+        let trie = trie.put("hel".to_string(), 2);
+        let trie = trie.put("h".to_string(), 3);
+        let trie = trie.remove("hello".to_string());
         assert!(trie.get("hello".to_string()).is_none());
         assert!(trie.get("hell" .to_string()).is_none());
         assert!(trie.get("hel" .to_string()).is_some());
+        // The emptied branch below "hel" is pruned.
+        assert!(trie.get_root().get_child('h').unwrap().get_child('e').unwrap()
+            .get_child('l').unwrap().get_children().is_empty());
+    }
+
+    #[test]
+    fn test_put_keeps_old_versions() {
+        let empty = Trie::<i32>::new();
+        let v1 = empty.put("ab".to_string(), 1);
+        let v2 = v1.put("ac".to_string(), 2);
+        let v3 = v2.put("ab".to_string(), 3);
+        let v4 = v3.put("".to_string(), 4);
+
+        // Every version keeps seeing the values it was built with.
+        assert!(empty.get("ab".to_string()).is_none());
+        assert_eq!(*v1.get("ab".to_string()).unwrap(), 1);
+        assert!(v1.get("ac".to_string()).is_none());
+        assert_eq!(*v2.get("ab".to_string()).unwrap(), 1);
+        assert_eq!(*v2.get("ac".to_string()).unwrap(), 2);
+        assert_eq!(*v3.get("ab".to_string()).unwrap(), 3);
+        assert!(v3.get("".to_string()).is_none());
+        assert_eq!(*v4.get("".to_string()).unwrap(), 4);
+
+        // Only the nodes on the key path are copied.
+        let ac = |trie: &Trie<i32>| trie.get_root().get_child('a').unwrap().get_child('c').unwrap();
+        assert!(Arc::ptr_eq(&ac(&v2), &ac(&v3)));
+        assert!(!Arc::ptr_eq(&v2.get_root(), &v3.get_root()));
+        assert!(Arc::ptr_eq(&v2.get("ac".to_string()).unwrap(), &v4.get("ac".to_string()).unwrap()));
+    }
+
+    #[test]
+    fn test_remove_keeps_old_versions() {
+        let trie = Trie::<String>::new();
+        let full = trie
+            .put("test".to_string(), "a".to_string())
+            .put("tea".to_string(), "b".to_string())
+            .put("te".to_string(), "c".to_string());
+        let removed = full.remove("test".to_string()).remove("te".to_string());
+
+        // Removing keys leaves the version they were removed from untouched.
+        assert_eq!(*full.get("test".to_string()).unwrap(), "a");
+        assert_eq!(*full.get("te".to_string()).unwrap(), "c");
+        assert!(removed.get("test".to_string()).is_none());
+        assert!(removed.get("te".to_string()).is_none());
+        assert_eq!(*removed.get("tea".to_string()).unwrap(), "b");
+
+        // Removing a missing key returns the same version.
+        let same = removed.remove("tx".to_string()).remove("tes".to_string());
+        assert!(Arc::ptr_eq(&same.get_root(), &removed.get_root()));
+
+        // Removing the last key leaves an empty trie.
+        let empty = removed.remove("tea".to_string());
+        assert!(empty.get_root().get_children().is_empty());
+        assert_eq!(*removed.get("tea".to_string()).unwrap(), "b");
     }
 }
 
 mod trie_store_test {
 
+    use bustub_rust::primer::trie::TrieFn;
     use bustub_rust::primer::trie_store::*;
     #[test]
     fn test_trie_store() {
-        // let trie_store = TrieStore::<i32>::new();
-        // trie_store.put("hello".to_string(), 1);
-        // trie_store.put("world".to_string(), 2);
-        // trie_store.put("hello world".to_string(), 3);
-        // assert_eq!(
-        //     *trie_store
-        //         .get("hello".to_string())
-        //         .unwrap()
-        //         .get_value()
-        //         .borrow::<i32>(),
-        //     1
-        // );
+        let trie_store = TrieStore::<i32>::new();
+        trie_store.put("hello".to_string(), 1);
+        trie_store.put("world".to_string(), 2);
+        trie_store.put("hello world".to_string(), 3);
+        assert_eq!(*trie_store.get("hello".to_string()).unwrap().get_value(), 1);
+
+        // A ValueGuard keeps its snapshot while the store moves on.
+        let guard = trie_store.get("world".to_string()).unwrap();
+        trie_store.put("world".to_string(), 20);
+        trie_store.remove("hello".to_string());
+        assert_eq!(*guard.get_value(), 2);
+        assert_eq!(*guard.get_root().get("hello".to_string()).unwrap(), 1);
+        assert_eq!(*trie_store.get("world".to_string()).unwrap().get_value(), 20);
+        assert!(trie_store.get("hello".to_string()).is_none());
     }
 
     #[test]
@@ -64,8 +125,7 @@ mod trie_store_test {
                 *trie_store
                     .get(format!("hello{}", i))
                     .unwrap()
-                    .get_value()
-                    .read().unwrap(),
+                    .get_value(),
                 i
             );
         }